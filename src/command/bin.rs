@@ -0,0 +1,229 @@
+use std::io::{self, IsTerminal};
+
+use anyhow::{bail, Context, Result};
+use dialoguer::{theme::ColorfulTheme, Confirm};
+use structopt::StructOpt;
+
+use cargo_trim::{utils::get_size, BinStatus, DirPath, InstallTracker, Journal, Trash};
+
+use crate::output::{
+    convert_pretty, show_bin_entry, show_installed_package, show_removal_summary,
+    show_removed_crate,
+};
+
+#[derive(Debug, StructOpt)]
+#[structopt(about = "Query, check and uninstall packages installed by cargo install")]
+pub(crate) struct Bin {
+    #[structopt(
+        long = "check",
+        short = "c",
+        help = "Classify binaries of bin directory and install metadata as tracked, untracked, \
+                rustup proxy or missing",
+        conflicts_with = "uninstall"
+    )]
+    check: bool,
+    #[structopt(
+        long = "dry-run",
+        short = "n",
+        help = "Run command in dry run mode to see what would be done"
+    )]
+    dry_run: bool,
+    #[structopt(
+        long = "uninstall",
+        short = "u",
+        help = "Uninstall provided packages by removing their binaries and install metadata",
+        value_name = "package"
+    )]
+    uninstall: Option<Vec<String>>,
+    #[structopt(
+        long = "remove-untracked",
+        help = "Remove binaries which are not owned by any installed package without prompt",
+        requires = "check"
+    )]
+    remove_untracked: bool,
+    #[structopt(
+        long = "repair",
+        help = "Remove missing binaries from install metadata without prompt",
+        requires = "check"
+    )]
+    repair: bool,
+}
+
+impl Bin {
+    pub(super) fn run(
+        &self,
+        dir_path: &DirPath,
+        trash: Option<&Trash>,
+        journal: &Journal,
+        json_format: bool,
+    ) -> Result<()> {
+        let dry_run = self.dry_run;
+        let mut install_tracker = InstallTracker::new(dir_path)?;
+        if self.check {
+            return self.check(&mut install_tracker, trash, journal, json_format);
+        }
+        let packages = install_tracker.packages();
+        let Some(uninstall) = &self.uninstall else {
+            if packages.is_empty() && !json_format {
+                println!("No package is installed by cargo install");
+            }
+            for package in &packages {
+                show_installed_package(package, json_format);
+            }
+            return Ok(());
+        };
+
+        // every package is checked before uninstall so nothing is removed for typo
+        for name in uninstall {
+            if !packages.iter().any(|package| package.name() == name) {
+                bail!("package {:?} is not installed by cargo install", name);
+            }
+        }
+        journal.set_reason("uninstall");
+        let mut total_removed = 0;
+        let mut size_cleaned = 0;
+        for package in packages
+            .iter()
+            .filter(|package| uninstall.iter().any(|name| name == package.name()))
+        {
+            let size = package
+                .bins()
+                .iter()
+                .map(|bin| get_size(&install_tracker.bin_path(bin)).unwrap_or(0))
+                .sum::<u64>();
+            let is_success = install_tracker
+                .uninstall(package, trash, Some(journal), dry_run)
+                .is_ok();
+            show_removed_crate(
+                &format!("{}-{}", package.name(), package.version()),
+                "bin",
+                is_success,
+                dry_run,
+                json_format,
+            );
+            if is_success {
+                total_removed += 1;
+                size_cleaned += size;
+            }
+        }
+        show_removal_summary(
+            "bin uninstall",
+            &format!(
+                "{} packages uninstalled which had occupied {}",
+                total_removed,
+                convert_pretty(size_cleaned)
+            ),
+            total_removed,
+            size_cleaned,
+            dry_run,
+            json_format,
+        );
+        Ok(())
+    }
+
+    // classify binaries and clean up untracked binaries and missing binaries of
+    // install metadata if it is requested through flag or confirmed in prompt
+    fn check(
+        &self,
+        install_tracker: &mut InstallTracker,
+        trash: Option<&Trash>,
+        journal: &Journal,
+        json_format: bool,
+    ) -> Result<()> {
+        let dry_run = self.dry_run;
+        let entries = install_tracker.check()?;
+        if entries.is_empty() && !json_format {
+            println!("Bin directory and install metadata are empty");
+        }
+        for entry in &entries {
+            show_bin_entry(entry, json_format);
+        }
+        let untracked = entries
+            .iter()
+            .filter(|entry| entry.status() == BinStatus::Untracked)
+            .collect::<Vec<_>>();
+        let missing = entries
+            .iter()
+            .filter(|entry| entry.status() == BinStatus::Missing)
+            .count();
+
+        if !untracked.is_empty()
+            && (self.remove_untracked
+                || confirm(
+                    &format!("Remove {} untracked binaries?", untracked.len()),
+                    json_format,
+                )?)
+        {
+            journal.set_reason("untracked-bin");
+            let mut total_removed = 0;
+            let mut size_cleaned = 0;
+            for entry in untracked {
+                let size = get_size(entry.path()).unwrap_or(0);
+                let is_success = install_tracker
+                    .remove_untracked(entry, trash, Some(journal), dry_run)
+                    .is_ok();
+                show_removed_crate(entry.name(), "bin", is_success, dry_run, json_format);
+                if is_success {
+                    total_removed += 1;
+                    size_cleaned += size;
+                }
+            }
+            show_removal_summary(
+                "bin clean",
+                &format!(
+                    "{} untracked binaries removed which had occupied {}",
+                    total_removed,
+                    convert_pretty(size_cleaned)
+                ),
+                total_removed,
+                size_cleaned,
+                dry_run,
+                json_format,
+            );
+        }
+
+        if missing > 0
+            && (self.repair
+                || confirm(
+                    &format!("Remove {missing} missing binaries from install metadata?"),
+                    json_format,
+                )?)
+        {
+            let repaired = install_tracker.repair(dry_run)?;
+            for (package, bin) in &repaired {
+                show_removed_crate(
+                    &format!("{package}/{bin}"),
+                    "bin-metadata",
+                    true,
+                    dry_run,
+                    json_format,
+                );
+            }
+            show_removal_summary(
+                "bin repair",
+                &format!(
+                    "{} missing binaries removed from install metadata",
+                    repaired.len()
+                ),
+                repaired.len(),
+                0,
+                dry_run,
+                json_format,
+            );
+        }
+        Ok(())
+    }
+}
+
+// ask for confirmation. Prompt is only shown in interactive terminal with text
+// output otherwise it is treated as declined
+fn confirm(prompt: &str, json_format: bool) -> Result<bool> {
+    if json_format || !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return Ok(false);
+    }
+    Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .default(false)
+        .interact()
+        .context("failed to read confirmation")
+}
@@ -1,7 +1,9 @@
 use anyhow::{Context, Result};
 use structopt::StructOpt;
 
-use crate::config_file::ConfigFile;
+use cargo_trim::ConfigFile;
+
+use crate::output::show_config_removed;
 
 #[derive(Debug, StructOpt)]
 #[structopt(about = "Clear current working directory from cargo cache config")]
@@ -16,14 +18,13 @@ pub(crate) struct Clear {
 
 impl Clear {
     pub(super) fn run(&self, config_file: &mut ConfigFile) -> Result<()> {
-        config_file.remove_directory(
-            std::env::current_dir()
-                .context("Current working directory is invalid")?
-                .to_str()
-                .context("Cannot convert current directory to str")?,
-            self.dry_run,
-            true,
-        )?;
+        let current_dir =
+            std::env::current_dir().context("Current working directory is invalid")?;
+        let path = current_dir
+            .to_str()
+            .context("Cannot convert current directory to str")?;
+        config_file.remove_directory(path, self.dry_run, true)?;
+        show_config_removed(path, self.dry_run);
         Ok(())
     }
 }
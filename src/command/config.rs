@@ -4,7 +4,7 @@ use anyhow::{Context, Result};
 use colored::Colorize;
 use structopt::{clap::AppSettings, StructOpt};
 
-use crate::config_file::ConfigFile;
+use cargo_trim::ConfigFile;
 
 #[derive(Debug, StructOpt)]
 #[structopt(
@@ -20,6 +20,8 @@ pub(crate) struct Config {
         help = "Query about ignored file name data"
     )]
     ignore: bool,
+    #[structopt(long = "keep", short = "k", help = "Query about keep list data")]
+    keep: bool,
     #[structopt(long = "location", short = "l", help = "Return config file location")]
     location: bool,
     #[structopt(long = "print", short = "p", help = "Display config file content")]
@@ -27,17 +29,24 @@ pub(crate) struct Config {
 }
 
 impl Config {
+    // path is debug formatted so it is printed within quotes
+    #[allow(clippy::unnecessary_debug_formatting)]
     pub(super) fn run(&self, config_file: &ConfigFile, config_file_location: &Path) -> Result<()> {
         if self.directory {
             let read_directory = config_file.directory();
             for name in read_directory {
-                println!("{}", name);
+                println!("{name}");
             }
         }
         if self.ignore {
             let read_ignore_file_name = config_file.ignore_file_name();
             for name in read_ignore_file_name {
-                println!("{}", name);
+                println!("{name}");
+            }
+        }
+        if self.keep {
+            for name in config_file.keep() {
+                println!("{name}");
             }
         }
         if self.location {
@@ -50,7 +59,7 @@ impl Config {
         if self.print {
             let content = toml::to_string_pretty(config_file)
                 .context("Failed to convert struct to pretty toml")?;
-            println!("{}", content);
+            println!("{content}");
         }
         Ok(())
     }
@@ -1,16 +1,24 @@
-use std::{io::Write, path::Path};
+use std::{io::Write, path::Path, str::FromStr, time::Duration};
 
 use anyhow::{Context, Result};
 use colored::Colorize;
 use structopt::{clap::AppSettings, StructOpt};
 
+use cargo_trim::{
+    utils::get_size, CrateDetail, CrateKind, CrateList, CrateSelector, DirPath, GitDir, Journal,
+    Progress,
+};
+
 use crate::{
-    crate_detail::CrateDetail,
-    dir_path::DirPath,
-    git_dir::GitDir,
-    list_crate::CrateList,
-    utils::{convert_pretty, get_size, print_dash, query_print, show_top_number_crates},
+    output::{
+        convert_age, convert_mb, print_dash, query_full_width, show_dry_run_removed,
+        show_kept_crate, show_query, show_removal_result, show_removal_summary,
+        show_selector_expansion, show_top_number_crates,
+    },
+    progress_bar::ProgressBar,
 };
+
+use super::parse::parse_duration;
 #[derive(Debug, StructOpt)]
 #[structopt(about="Perform operation only to git related cache file", settings=&[
     AppSettings::ArgRequiredElseHelp,
@@ -39,6 +47,14 @@ pub(crate) struct Git {
         help = "Clean git crates which is both old and orphan"
     )]
     old_orphan: bool,
+    #[structopt(
+        long = "older-than",
+        help = "Clean git crates which are not used for provided duration such as 30d, 12h or \
+                2w",
+        value_name = "duration",
+        parse(try_from_str = parse_duration)
+    )]
+    older_than: Option<Duration>,
     #[structopt(
         long = "orphan",
         short = "x",
@@ -56,10 +72,12 @@ pub(crate) struct Git {
     #[structopt(
         long = "remove",
         short = "r",
-        help = "Remove provided crates from git",
-        value_name = "crate"
+        help = "Remove provided crates from git. Value can be crate name, full name or glob pattern such \
+                as serde_*",
+        value_name = "crate",
+        parse(try_from_str = CrateSelector::from_str)
     )]
-    remove: Option<Vec<String>>,
+    remove: Option<Vec<CrateSelector>>,
     #[structopt(
         long = "top",
         short = "t",
@@ -70,46 +88,87 @@ pub(crate) struct Git {
 }
 
 impl Git {
+    #[allow(clippy::too_many_arguments)]
     pub(super) fn run(
         &self,
         dir_path: &DirPath,
         crate_list: &CrateList,
         crate_detail: &CrateDetail,
         git_crates_location: &GitDir,
+        journal: &Journal,
         directory_is_empty: bool,
+        json_format: bool,
     ) -> Result<()> {
         let dry_run = self.dry_run;
 
         if self.light_cleanup {
-            let light_cleanup_success = light_cleanup_git(dir_path.checkout_dir(), dry_run);
+            journal.set_reason("light");
+            let light_cleanup_success =
+                light_cleanup_git(git_crates_location, dir_path.checkout_dir(), dry_run);
             if !light_cleanup_success {
                 println!("Failed to delete some folder during light cleanup");
             }
         }
 
         if let Some(number) = self.top {
-            top_crates_git(crate_detail, number);
+            top_crates_git(crate_detail, number, json_format);
         }
 
         if self.query {
-            let final_size = query_size_git(dir_path, crate_list, crate_detail);
-            query_print("Total size", &convert_pretty(final_size));
+            let final_size = query_size_git(dir_path, crate_list, crate_detail, json_format);
+            show_query("Total size", "total", None, final_size, json_format);
         }
 
         if self.old {
-            let (sized_cleaned, total_crate_removed) =
-                old_clean_git(git_crates_location, crate_list, crate_detail, dry_run);
-            println!(
-                "{}",
-                format!(
+            journal.set_reason("old");
+            let (sized_cleaned, total_crate_removed) = old_clean_git(
+                git_crates_location,
+                crate_list,
+                crate_detail,
+                dry_run,
+                json_format,
+            );
+            show_removal_summary(
+                "old",
+                &format!(
                     "{} old crates removed which had occupied {:.3} MB",
-                    total_crate_removed, sized_cleaned
-                )
-                .color("blue")
+                    total_crate_removed,
+                    convert_mb(sized_cleaned)
+                ),
+                total_crate_removed,
+                sized_cleaned,
+                dry_run,
+                json_format,
+            );
+        }
+
+        if let Some(duration) = self.older_than {
+            journal.set_reason("older-than");
+            let (sized_cleaned, total_crate_removed) = older_than_clean_git(
+                git_crates_location,
+                crate_list,
+                crate_detail,
+                duration,
+                dry_run,
+                json_format,
+            );
+            show_removal_summary(
+                "older_than",
+                &format!(
+                    "{} crates not used for {} removed which had occupied {:.3} MB",
+                    total_crate_removed,
+                    convert_age(duration),
+                    convert_mb(sized_cleaned)
+                ),
+                total_crate_removed,
+                sized_cleaned,
+                dry_run,
+                json_format,
             );
         }
 
         if self.old_orphan {
+            journal.set_reason("old-orphan");
             if directory_is_empty {
                 let warning_text = "WARNING: You have not initialized any directory as rust \
                                     project directory. This command will clean all old crates \
@@ -132,20 +191,30 @@ impl Git {
                     return Ok(());
                 }
             }
-            let (sized_cleaned, total_crate_removed) =
-                old_orphan_clean_git(git_crates_location, crate_list, crate_detail, dry_run);
+            let (sized_cleaned, total_crate_removed) = old_orphan_clean_git(
+                git_crates_location,
+                crate_list,
+                crate_detail,
+                dry_run,
+                json_format,
+            );
 
-            println!(
-                "{}",
-                format!(
+            show_removal_summary(
+                "old_orphan",
+                &format!(
                     "{} crates which are both old and orphan crate removed which had {:.3} MB",
-                    total_crate_removed, sized_cleaned
-                )
-                .color("blue")
+                    total_crate_removed,
+                    convert_mb(sized_cleaned)
+                ),
+                total_crate_removed,
+                sized_cleaned,
+                dry_run,
+                json_format,
             );
         }
 
         if self.orphan {
+            journal.set_reason("orphan");
             if directory_is_empty {
                 let warning_text = "WARNING: You have not initialized any directory as rust \
                                     project directory. This command will clean all crates since \
@@ -168,39 +237,60 @@ impl Git {
                     return Ok(());
                 }
             }
-            let (sized_cleaned, total_crate_removed) =
-                orphan_clean_git(git_crates_location, crate_list, crate_detail, dry_run);
+            let (sized_cleaned, total_crate_removed) = orphan_clean_git(
+                git_crates_location,
+                crate_list,
+                crate_detail,
+                dry_run,
+                json_format,
+            );
 
-            println!(
-                "{}",
-                format!(
+            show_removal_summary(
+                "orphan",
+                &format!(
                     "{} orphan crates removed which had occupied {:.3} MB",
-                    total_crate_removed, sized_cleaned
-                )
-                .color("blue")
+                    total_crate_removed,
+                    convert_mb(sized_cleaned)
+                ),
+                total_crate_removed,
+                sized_cleaned,
+                dry_run,
+                json_format,
             );
         }
 
         if self.all {
-            let (sized_cleaned, total_crate_removed) =
-                all_clean_git(git_crates_location, crate_list, crate_detail, dry_run);
-            println!(
-                "{}",
-                format!(
+            journal.set_reason("all");
+            let (sized_cleaned, total_crate_removed) = all_clean_git(
+                git_crates_location,
+                crate_list,
+                crate_detail,
+                dry_run,
+                json_format,
+            );
+            show_removal_summary(
+                "all",
+                &format!(
                     "Total size of  {} crates removed :- {:.3} MB",
-                    total_crate_removed, sized_cleaned
-                )
-                .color("blue")
+                    total_crate_removed,
+                    convert_mb(sized_cleaned)
+                ),
+                total_crate_removed,
+                sized_cleaned,
+                dry_run,
+                json_format,
             );
         }
 
         if let Some(crates) = &self.remove {
+            journal.set_reason("remove");
             remove_crates(
                 crates,
                 crate_list,
                 git_crates_location,
                 crate_detail,
                 dry_run,
+                json_format,
             );
         }
 
@@ -209,45 +299,75 @@ impl Git {
 }
 
 // Perform light cleanup of git and return if light clean was success or not
-pub(super) fn light_cleanup_git(checkout_dir: &Path, dry_run: bool) -> bool {
-    // delete checkout dir
-    crate::utils::delete_folder(checkout_dir, dry_run).is_ok()
+pub(super) fn light_cleanup_git(
+    git_crates_location: &GitDir,
+    checkout_dir: &Path,
+    dry_run: bool,
+) -> bool {
+    if dry_run {
+        show_dry_run_removed(checkout_dir);
+    }
+    // delete checkout dir except checkout of crates present in keep list
+    git_crates_location.remove_all_checkouts(dry_run).is_ok()
 }
 
 // Show top git crates
-pub(super) fn top_crates_git(crate_detail: &CrateDetail, number: usize) {
-    show_top_number_crates(crate_detail.git_crates_archive(), "git_archive", number);
-    show_top_number_crates(crate_detail.git_crates_source(), "git_source", number);
+pub(super) fn top_crates_git(crate_detail: &CrateDetail, number: usize, json_format: bool) {
+    show_top_number_crates(
+        crate_detail.git_crates_archive(),
+        "git_archive",
+        number,
+        json_format,
+    );
+    show_top_number_crates(
+        crate_detail.git_crates_source(),
+        "git_source",
+        number,
+        json_format,
+    );
 }
 
+// Query size of git
 pub(super) fn query_size_git(
     dir_path: &DirPath,
     crate_list: &CrateList,
     crate_detail: &CrateDetail,
+    json_format: bool,
 ) -> u64 {
     let git_dir_size = get_size(dir_path.git_dir()).unwrap_or(0_u64);
-    query_print(
+    show_query(
         &format!(
             "Total size of {} .cargo/git crates:",
             crate_list.installed_git().len()
         ),
-        &convert_pretty(git_dir_size),
+        "git",
+        Some(crate_list.installed_git().len()),
+        git_dir_size,
+        json_format,
     );
-    query_print(
+    show_query(
         &format!(
             "   \u{251c} Size of {} .cargo/git/checkout folder",
             crate_detail.git_crates_archive().len()
         ),
-        &convert_pretty(get_size(dir_path.checkout_dir()).unwrap_or(0_u64)),
+        "git/checkouts",
+        Some(crate_detail.git_crates_archive().len()),
+        get_size(dir_path.checkout_dir()).unwrap_or(0_u64),
+        json_format,
     );
-    query_print(
+    show_query(
         &format!(
             "   \u{2514} Size of {} .cargo/git/db folder",
             crate_detail.git_crates_source().len()
         ),
-        &convert_pretty(get_size(dir_path.checkout_dir()).unwrap_or(0_u64)),
+        "git/db",
+        Some(crate_detail.git_crates_source().len()),
+        get_size(dir_path.db_dir()).unwrap_or(0_u64),
+        json_format,
     );
-    print_dash(crate::utils::query_full_width());
+    if !json_format {
+        print_dash(query_full_width());
+    }
     git_dir_size
 }
 
@@ -257,10 +377,14 @@ pub(super) fn old_clean_git(
     crate_list: &CrateList,
     crate_detail: &CrateDetail,
     dry_run: bool,
-) -> (f64, usize) {
-    (
-        git_crates_location.remove_crate_list(crate_detail, crate_list.old_git(), dry_run),
-        crate_list.old_registry().len(),
+    json_format: bool,
+) -> (u64, usize) {
+    remove_crate_list(
+        git_crates_location,
+        crate_detail,
+        crate_list.old_git(),
+        dry_run,
+        json_format,
     )
 }
 
@@ -270,14 +394,34 @@ pub(super) fn old_orphan_clean_git(
     crate_list: &CrateList,
     crate_detail: &CrateDetail,
     dry_run: bool,
-) -> (f64, usize) {
-    (
-        git_crates_location.remove_crate_list(
-            crate_detail,
-            &crate_list.list_old_orphan_git(),
-            dry_run,
-        ),
-        crate_list.list_old_orphan_git().len(),
+    json_format: bool,
+) -> (u64, usize) {
+    remove_orphan_crate_list(
+        git_crates_location,
+        crate_list,
+        crate_detail,
+        &crate_list.list_old_orphan_git(),
+        dry_run,
+        json_format,
+    )
+}
+
+// perform older than clean on git crates
+pub(super) fn older_than_clean_git(
+    git_crates_location: &GitDir,
+    crate_list: &CrateList,
+    crate_detail: &CrateDetail,
+    duration: Duration,
+    dry_run: bool,
+    json_format: bool,
+) -> (u64, usize) {
+    let older_than_git = crate_list.list_older_than_git(crate_detail, duration);
+    remove_crate_list(
+        git_crates_location,
+        crate_detail,
+        &older_than_git,
+        dry_run,
+        json_format,
     )
 }
 
@@ -287,10 +431,15 @@ pub(super) fn orphan_clean_git(
     crate_list: &CrateList,
     crate_detail: &CrateDetail,
     dry_run: bool,
-) -> (f64, usize) {
-    (
-        git_crates_location.remove_crate_list(crate_detail, crate_list.orphan_git(), dry_run),
-        crate_list.orphan_git().len(),
+    json_format: bool,
+) -> (u64, usize) {
+    remove_orphan_crate_list(
+        git_crates_location,
+        crate_list,
+        crate_detail,
+        crate_list.orphan_git(),
+        dry_run,
+        json_format,
     )
 }
 
@@ -300,30 +449,140 @@ pub(super) fn all_clean_git(
     crate_list: &CrateList,
     crate_detail: &CrateDetail,
     dry_run: bool,
-) -> (f64, usize) {
-    (
-        git_crates_location.remove_crate_list(crate_detail, crate_list.installed_git(), dry_run),
-        crate_list.installed_git().len(),
+    json_format: bool,
+) -> (u64, usize) {
+    remove_crate_list(
+        git_crates_location,
+        crate_detail,
+        crate_list.installed_git(),
+        dry_run,
+        json_format,
     )
 }
 
-// Remove certain git crates
+// Remove list of crates and return size cleaned along with number of crates
+// removed. Crates present in keep list are not removed
+fn remove_crate_list(
+    git_crates_location: &GitDir,
+    crate_detail: &CrateDetail,
+    list: &[String],
+    dry_run: bool,
+    json_format: bool,
+) -> (u64, usize) {
+    let mut size_cleaned = 0;
+    let mut total_crate_removed = 0;
+    let progress = ProgressBar::new();
+    progress.start("Removing git crates", list.len());
+    for crate_name in list {
+        if git_crates_location.is_kept(crate_name) {
+            progress.suspend(|| show_kept_crate(crate_name, "git", json_format));
+            progress.advance(crate_name, 0);
+            continue;
+        }
+        let result = git_crates_location.remove_crate(crate_name, dry_run);
+        progress.suspend(|| {
+            show_removal_result(crate_name, "git", &result, dry_run, json_format);
+        });
+        // crate which failed to be removed is not counted
+        if result.is_err() {
+            progress.advance(crate_name, 0);
+            continue;
+        }
+        let crate_bytes = crate_detail.find_bytes(crate_name, CrateKind::Git);
+        size_cleaned += crate_bytes;
+        total_crate_removed += 1;
+        progress.advance(crate_name, crate_bytes);
+    }
+    progress.finish();
+    (size_cleaned, total_crate_removed)
+}
+
+// Remove orphan crates only from repository in which they are orphan and return
+// size cleaned along with number of crates removed. Crates present in keep list
+// are not removed
+fn remove_orphan_crate_list(
+    git_crates_location: &GitDir,
+    crate_list: &CrateList,
+    crate_detail: &CrateDetail,
+    list: &[String],
+    dry_run: bool,
+    json_format: bool,
+) -> (u64, usize) {
+    let mut size_cleaned = 0;
+    let mut total_crate_removed = 0;
+    let progress = ProgressBar::new();
+    progress.start("Removing git crates", list.len());
+    for crate_name in list {
+        if git_crates_location.is_kept(crate_name) {
+            progress.suspend(|| show_kept_crate(crate_name, "git", json_format));
+            progress.advance(crate_name, 0);
+            continue;
+        }
+        let result = git_crates_location.remove_crate_from_repository(
+            crate_name,
+            crate_list.orphan_git_dirs(crate_name),
+            dry_run,
+        );
+        progress.suspend(|| {
+            show_removal_result(crate_name, "git", &result, dry_run, json_format);
+        });
+        // crate which failed to be removed is not counted
+        if result.is_err() {
+            progress.advance(crate_name, 0);
+            continue;
+        }
+        let crate_bytes = crate_detail.find_bytes(crate_name, CrateKind::Git);
+        size_cleaned += crate_bytes;
+        total_crate_removed += 1;
+        progress.advance(crate_name, crate_bytes);
+    }
+    progress.finish();
+    (size_cleaned, total_crate_removed)
+}
+
+// Remove git crates matching provided selectors. Dry run list crates matched by
+// each selector
 fn remove_crates(
-    crates: &[String],
+    selectors: &[CrateSelector],
     crate_list: &CrateList,
     git_crates_location: &GitDir,
     crate_detail: &CrateDetail,
     dry_run: bool,
+    json_format: bool,
 ) {
-    let mut size_cleaned = 0.0;
+    let mut crates = Vec::new();
+    for selector in selectors {
+        let matched = selector.expand_git(crate_list.installed_git());
+        if dry_run {
+            show_selector_expansion(selector.value(), &matched, json_format);
+        }
+        for crate_name in matched {
+            if !crates.contains(&crate_name) {
+                crates.push(crate_name);
+            }
+        }
+    }
+    let mut size_cleaned = 0;
+    let mut total_crate_removed = 0;
     for crate_name in crates {
-        if crate_list.installed_git().contains(crate_name) {
-            git_crates_location.remove_crate(crate_name, dry_run);
-            size_cleaned += crate_detail.find_size_git_all(crate_name);
+        if git_crates_location.is_kept(crate_name) {
+            show_kept_crate(crate_name, "git", json_format);
+            continue;
+        }
+        let result = git_crates_location.remove_crate(crate_name, dry_run);
+        show_removal_result(crate_name, "git", &result, dry_run, json_format);
+        // crate which failed to be removed is not counted
+        if result.is_ok() {
+            size_cleaned += crate_detail.find_bytes(crate_name, CrateKind::Git);
+            total_crate_removed += 1;
         }
     }
-    println!(
-        "{}",
-        format!("Total size removed :- {:.3} MB", size_cleaned).color("blue")
+    show_removal_summary(
+        "remove",
+        &format!("Total size removed :- {:.3} MB", convert_mb(size_cleaned)),
+        total_crate_removed,
+        size_cleaned,
+        dry_run,
+        json_format,
     );
 }
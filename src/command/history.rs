@@ -0,0 +1,56 @@
+use anyhow::Result;
+use structopt::StructOpt;
+
+use cargo_trim::{utils::crate_matches, Journal, JournalRun};
+
+use crate::output::{show_history_entry, show_history_run};
+
+#[derive(Debug, StructOpt)]
+#[structopt(about = "Show crates removed by previous runs")]
+pub(crate) struct History {
+    #[structopt(
+        long = "crate",
+        short = "c",
+        help = "Show every removal of crates matching provided crate name, full name or glob pattern",
+        value_name = "crate"
+    )]
+    crate_name: Option<String>,
+    #[structopt(
+        long = "limit",
+        short = "l",
+        help = "Only show provided number of latest runs",
+        value_name = "number"
+    )]
+    limit: Option<usize>,
+}
+
+impl History {
+    pub(super) fn run(&self, journal: &Journal, json_format: bool) -> Result<()> {
+        let runs = journal.list_runs()?;
+        let skip = self
+            .limit
+            .map_or(0, |limit| runs.len().saturating_sub(limit));
+        let runs = &runs[skip..];
+        if let Some(crate_name) = &self.crate_name {
+            let entries = runs
+                .iter()
+                .flat_map(JournalRun::entries)
+                .filter(|entry| crate_matches(crate_name, entry.crate_name()))
+                .collect::<Vec<_>>();
+            if entries.is_empty() && !json_format {
+                println!("No removal of {crate_name:?} present in history");
+            }
+            for entry in entries {
+                show_history_entry(entry, json_format);
+            }
+        } else {
+            if runs.is_empty() && !json_format {
+                println!("History is empty");
+            }
+            for run in runs {
+                show_history_run(run, json_format);
+            }
+        }
+        Ok(())
+    }
+}
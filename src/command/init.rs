@@ -1,7 +1,9 @@
 use anyhow::{Context, Result};
 use structopt::StructOpt;
 
-use crate::config_file::ConfigFile;
+use cargo_trim::ConfigFile;
+
+use crate::output::show_config_added;
 
 #[derive(Debug, StructOpt)]
 #[structopt(about = "Initialize current working directory as cargo trim directory")]
@@ -16,14 +18,13 @@ pub(crate) struct Init {
 
 impl Init {
     pub(super) fn run(&self, config_file: &mut ConfigFile) -> Result<()> {
-        config_file.add_directory(
-            std::env::current_dir()
-                .context("Current working directory is invalid")?
-                .to_str()
-                .context("failed to convert current directory to str")?,
-            self.dry_run,
-            true,
-        )?;
+        let current_dir =
+            std::env::current_dir().context("Current working directory is invalid")?;
+        let path = current_dir
+            .to_str()
+            .context("failed to convert current directory to str")?;
+        config_file.add_directory(path, self.dry_run, true)?;
+        show_config_added(path, self.dry_run);
         Ok(())
     }
 }
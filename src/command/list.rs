@@ -1,21 +1,53 @@
+use std::{collections::BTreeMap, time::Duration};
+
 use colored::Colorize;
 use structopt::{clap::AppSettings, StructOpt};
 
-use crate::{crate_detail::CrateDetail, list_crate::CrateList};
+use cargo_trim::{CrateDetail, CrateKind, CrateList, OldMode, RegistrySource};
+
+use crate::output::{convert_age, print_dash, print_json, CrateRecord};
+
+use super::parse::{parse_duration, parse_keep_versions};
 
 #[derive(Debug, StructOpt)]
 #[structopt(about = "List out crates", settings=&[AppSettings::ArgRequiredElseHelp])]
 pub(crate) struct List {
     #[structopt(long = "all", short = "a", help = "List out all installed crate")]
     all: bool,
-    #[structopt(long = "old", short = "o", help = "List out old crates")]
-    old: bool,
+    #[structopt(
+        long = "keep-versions",
+        help = "Number of newest versions per crate and registry which are not listed as old \
+                [default: value in config file or 1]",
+        value_name = "number",
+        parse(try_from_str = parse_keep_versions)
+    )]
+    keep_versions: Option<usize>,
+    // short flag is separate from long option so it can be bundled with other flags
+    #[structopt(short = "o", help = "List out old crates using latest mode")]
+    old_flag: bool,
+    #[structopt(
+        long = "old",
+        help = "List out old crates classified by provided mode [default: latest]",
+        value_name = "mode",
+        possible_values = &["latest", "semver"],
+        min_values = 0,
+        require_equals = true
+    )]
+    #[allow(clippy::option_option)]
+    old: Option<Option<OldMode>>,
     #[structopt(
         long = "old-orphan",
         short = "z",
         help = "List out crates which are both old and orphan"
     )]
     old_orphan: bool,
+    #[structopt(
+        long = "older-than",
+        help = "List out crates which are not used for provided duration such as 30d, 12h or 2w",
+        value_name = "duration",
+        parse(try_from_str = parse_duration)
+    )]
+    older_than: Option<Duration>,
     #[structopt(long = "orphan", short = "x", help = "List out orphan crates")]
     orphan: bool,
     #[structopt(long = "used", short = "u", help = "List out used crates")]
@@ -23,65 +55,154 @@ pub(crate) struct List {
 }
 
 impl List {
+    // return number of newest versions which overrides config file value
+    pub(super) fn keep_versions(&self) -> Option<usize> {
+        self.keep_versions
+    }
+
+    // return mode used for classifying crates as old if it is provided
+    pub(super) fn old_mode(&self) -> Option<OldMode> {
+        self.old.flatten()
+    }
+
     pub(super) fn run(
         &self,
         crate_detail: &CrateDetail,
         crate_list: &CrateList,
+        registry_source: &RegistrySource,
         directory_is_empty: bool,
+        json_format: bool,
     ) {
         if self.all {
-            list_all(crate_detail, crate_list);
+            list_all(crate_detail, crate_list, registry_source, json_format);
         }
-        if self.old {
-            list_old(crate_detail, crate_list);
+        if self.old_flag || self.old.is_some() {
+            list_old(crate_detail, crate_list, registry_source, json_format);
         }
         if self.old_orphan {
-            list_old_orphan(crate_detail, crate_list, directory_is_empty);
+            list_old_orphan(
+                crate_detail,
+                crate_list,
+                registry_source,
+                directory_is_empty,
+                json_format,
+            );
+        }
+        if let Some(duration) = self.older_than {
+            list_older_than(
+                crate_detail,
+                crate_list,
+                registry_source,
+                duration,
+                json_format,
+            );
         }
         if self.orphan {
-            list_orphan(crate_detail, crate_list, directory_is_empty);
+            list_orphan(
+                crate_detail,
+                crate_list,
+                registry_source,
+                directory_is_empty,
+                json_format,
+            );
         }
         if self.used {
-            list_used(crate_detail, crate_list, directory_is_empty);
+            list_used(
+                crate_detail,
+                crate_list,
+                registry_source,
+                directory_is_empty,
+                json_format,
+            );
         }
     }
 }
 
-fn list_all(crate_detail: &CrateDetail, crate_list: &CrateList) {
+fn list_all(
+    crate_detail: &CrateDetail,
+    crate_list: &CrateList,
+    registry_source: &RegistrySource,
+    json_format: bool,
+) {
     crate_list_type(
         crate_detail,
+        crate_list,
+        registry_source,
         crate_list.installed_registry(),
+        CrateKind::Registry,
         "REGISTRY INSTALLED CRATE",
+        "installed",
+        json_format,
     );
     crate_list_type(
         crate_detail,
+        crate_list,
+        registry_source,
         crate_list.installed_git(),
+        CrateKind::Git,
         "GIT INSTALLED CRATE",
+        "installed",
+        json_format,
     );
 }
 
-fn list_old(crate_detail: &CrateDetail, crate_list: &CrateList) {
+fn list_old(
+    crate_detail: &CrateDetail,
+    crate_list: &CrateList,
+    registry_source: &RegistrySource,
+    json_format: bool,
+) {
     crate_list_type(
         crate_detail,
+        crate_list,
+        registry_source,
         crate_list.old_registry(),
+        CrateKind::Registry,
         "REGISTRY OLD CRATE",
+        "old",
+        json_format,
+    );
+    crate_list_type(
+        crate_detail,
+        crate_list,
+        registry_source,
+        crate_list.old_git(),
+        CrateKind::Git,
+        "GIT OLD CRATE",
+        "old",
+        json_format,
     );
-    crate_list_type(crate_detail, crate_list.old_git(), "GIT OLD CRATE");
 }
 
-fn list_old_orphan(crate_detail: &CrateDetail, crate_list: &CrateList, directory_is_empty: bool) {
+fn list_old_orphan(
+    crate_detail: &CrateDetail,
+    crate_list: &CrateList,
+    registry_source: &RegistrySource,
+    directory_is_empty: bool,
+    json_format: bool,
+) {
     crate_list_type(
         crate_detail,
+        crate_list,
+        registry_source,
         &crate_list.list_old_orphan_registry(),
+        CrateKind::Registry,
         "REGISTRY OLD+ORPHAN CRATE",
+        "old_orphan",
+        json_format,
     );
     crate_list_type(
         crate_detail,
+        crate_list,
+        registry_source,
         &crate_list.list_old_orphan_git(),
+        CrateKind::Git,
         "GIT OLD+ORPHAN CRATE",
+        "old_orphan",
+        json_format,
     );
     // print waning if no directory present in config file
-    if directory_is_empty {
+    if directory_is_empty && !json_format {
         let warning_text = "WARNING: You have not initialized any directory as rust project \
                             directory. This will list all old crates as old orphan crates even if \
                             they are not orphan crates. Run command 'cargo trim init' to \
@@ -91,15 +212,64 @@ fn list_old_orphan(crate_detail: &CrateDetail, crate_list: &CrateList, directory
     }
 }
 
-fn list_orphan(crate_detail: &CrateDetail, crate_list: &CrateList, directory_is_empty: bool) {
+fn list_older_than(
+    crate_detail: &CrateDetail,
+    crate_list: &CrateList,
+    registry_source: &RegistrySource,
+    duration: Duration,
+    json_format: bool,
+) {
+    crate_list_type(
+        crate_detail,
+        crate_list,
+        registry_source,
+        &crate_list.list_older_than_registry(crate_detail, duration),
+        CrateKind::Registry,
+        "REGISTRY OLDER THAN CRATE",
+        "older_than",
+        json_format,
+    );
+    crate_list_type(
+        crate_detail,
+        crate_list,
+        registry_source,
+        &crate_list.list_older_than_git(crate_detail, duration),
+        CrateKind::Git,
+        "GIT OLDER THAN CRATE",
+        "older_than",
+        json_format,
+    );
+}
+
+fn list_orphan(
+    crate_detail: &CrateDetail,
+    crate_list: &CrateList,
+    registry_source: &RegistrySource,
+    directory_is_empty: bool,
+    json_format: bool,
+) {
     crate_list_type(
         crate_detail,
+        crate_list,
+        registry_source,
         crate_list.orphan_registry(),
+        CrateKind::Registry,
         "REGISTRY ORPHAN CRATE",
+        "orphan",
+        json_format,
+    );
+    crate_list_type(
+        crate_detail,
+        crate_list,
+        registry_source,
+        crate_list.orphan_git(),
+        CrateKind::Git,
+        "GIT ORPHAN CRATE",
+        "orphan",
+        json_format,
     );
-    crate_list_type(crate_detail, crate_list.orphan_git(), "GIT ORPHAN CRATE");
     // print warning if directory config is empty
-    if directory_is_empty {
+    if directory_is_empty && !json_format {
         let warning_text = "WARNING: You have not initialized any directory as rust project \
                             directory. This will list all crates as orphan crate. Run command \
                             'cargo trim init' to initialize current directory as rust project \
@@ -109,15 +279,35 @@ fn list_orphan(crate_detail: &CrateDetail, crate_list: &CrateList, directory_is_
     }
 }
 
-fn list_used(crate_detail: &CrateDetail, crate_list: &CrateList, directory_is_empty: bool) {
+fn list_used(
+    crate_detail: &CrateDetail,
+    crate_list: &CrateList,
+    registry_source: &RegistrySource,
+    directory_is_empty: bool,
+    json_format: bool,
+) {
     crate_list_type(
         crate_detail,
+        crate_list,
+        registry_source,
         crate_list.used_registry(),
+        CrateKind::Registry,
         "REGISTRY USED CRATE",
+        "used",
+        json_format,
+    );
+    crate_list_type(
+        crate_detail,
+        crate_list,
+        registry_source,
+        crate_list.used_git(),
+        CrateKind::Git,
+        "GIT USED CRATE",
+        "used",
+        json_format,
     );
-    crate_list_type(crate_detail, crate_list.used_git(), "GIT USED CRATE");
     // print warning if directory config is empty
-    if directory_is_empty {
+    if directory_is_empty && !json_format {
         let warning_text = "WARNING: You have not initialized any directory as rust project \
                             directory. This will list no crates as used crate. Run command 'cargo \
                             trim init' to initialize current directory as rust project directory \
@@ -126,32 +316,140 @@ fn list_used(crate_detail: &CrateDetail, crate_list: &CrateList, directory_is_em
     }
 }
 
-// list certain crate type to terminal
-fn crate_list_type(crate_detail: &CrateDetail, crate_type: &[String], title: &str) {
+// list certain crate type to terminal. Registry crates are grouped by registry
+// which contains crate
+#[allow(clippy::too_many_arguments)]
+fn crate_list_type(
+    crate_detail: &CrateDetail,
+    crate_list: &CrateList,
+    registry_source: &RegistrySource,
+    crate_type: &[String],
+    kind: CrateKind,
+    title: &str,
+    list_name: &str,
+    json_format: bool,
+) {
+    let crate_group = group_by_registry(crate_detail, crate_list, crate_type, kind, list_name);
+    if json_format {
+        for (registry, crates) in &crate_group {
+            for crate_name in crates {
+                print_json(&CrateRecord::new(
+                    list_name,
+                    crate_name,
+                    kind,
+                    registry.as_deref(),
+                    registry_source,
+                    crate_detail,
+                    crate_list,
+                ));
+            }
+        }
+        return;
+    }
     let first_path_len = 40;
     let second_path_len = 10;
+    let third_path_len = 12;
     let second_path_precision = 3;
-    let dash_len = first_path_len + second_path_len + 3;
-    crate::utils::show_title(title, first_path_len, second_path_len, dash_len);
+    let dash_len = first_path_len + second_path_len + third_path_len + 4;
+    print_dash(dash_len);
+    println!(
+        "|{:^first_width$}|{:^second_width$}|{:^third_width$}|",
+        title.bold(),
+        "SIZE(MB)".bold(),
+        "AGE".bold(),
+        first_width = first_path_len,
+        second_width = second_path_len,
+        third_width = third_path_len
+    );
+    print_dash(dash_len);
 
-    let mut total_size = 0.0;
-    for crates in crate_type {
-        let size = crate_detail.find(crates, title);
-        total_size += size;
+    for (registry, crates) in &crate_group {
+        if kind == CrateKind::Registry {
+            let registry_name = registry.as_ref().map_or_else(
+                || "UNKNOWN REGISTRY".to_string(),
+                |registry| registry_source.display_name(registry),
+            );
+            println!(
+                "|{:^width$}|",
+                registry_name.color("cyan"),
+                width = dash_len - 2
+            );
+        }
+        for crate_name in crates {
+            let size = crate_detail.find(crate_name, kind);
+            let age = crate_detail
+                .find_age(crate_name, kind)
+                .map_or_else(|| "-".to_string(), convert_age);
+            println!(
+                "|{crate_name:^first_path_len$}|{size:^second_path_len$.second_path_precision$}|\
+                 {age:^third_path_len$}|"
+            );
+        }
+    }
+    if crate_type.is_empty() {
         println!(
-            "|{:^first_width$}|{:^second_width$.precision$}|",
-            crates,
-            size,
+            "|{:^first_width$}|{:^second_width$}|{:^third_width$}|",
+            "NONE".color("red"),
+            "0.000".color("red"),
+            "-".color("red"),
             first_width = first_path_len,
             second_width = second_path_len,
-            precision = second_path_precision
+            third_width = third_path_len
         );
     }
-    crate::utils::show_total_count(
-        crate_type,
-        total_size,
-        first_path_len,
-        second_path_len,
-        dash_len,
+    let total_size = crate_type
+        .iter()
+        .map(|crate_name| crate_detail.find(crate_name, kind))
+        .fold(0.0, |total, size| total + size);
+    print_dash(dash_len);
+    println!(
+        "|{:^first_width$}|{:^second_width$}|",
+        format!("Total no of crates:- {}", crate_type.len()).color("blue"),
+        format!("{total_size:.3}").color("blue"),
+        first_width = first_path_len,
+        second_width = second_path_len + third_path_len + 1,
     );
+    print_dash(dash_len);
+}
+
+// group crates by registry folder which contains crate. Used and orphan crates
+// are grouped by registry in which they are used or orphan. Git crates and
+// registry crates which are not present in any registry are grouped without
+// registry
+fn group_by_registry<'a>(
+    crate_detail: &CrateDetail,
+    crate_list: &CrateList,
+    crate_type: &'a [String],
+    kind: CrateKind,
+    list_name: &str,
+) -> Vec<(Option<String>, Vec<&'a String>)> {
+    if kind == CrateKind::Git {
+        return vec![(None, crate_type.iter().collect())];
+    }
+    let mut registry_group: BTreeMap<String, Vec<&String>> = BTreeMap::new();
+    let mut unknown_registry = Vec::new();
+    for crate_name in crate_type {
+        let registries = match list_name {
+            "used" => crate_list.used_registry_dirs(crate_name),
+            "orphan" | "old_orphan" => crate_list.orphan_registry_dirs(crate_name),
+            _ => crate_detail.find_registries(crate_name),
+        };
+        if registries.is_empty() {
+            unknown_registry.push(crate_name);
+        }
+        for registry in registries {
+            registry_group
+                .entry(registry.clone())
+                .or_default()
+                .push(crate_name);
+        }
+    }
+    let mut crate_group = registry_group
+        .into_iter()
+        .map(|(registry, crates)| (Some(registry), crates))
+        .collect::<Vec<_>>();
+    if !unknown_registry.is_empty() {
+        crate_group.push((None, unknown_registry));
+    }
+    crate_group
 }
@@ -2,30 +2,47 @@ use std::{
     fs,
     io::Write,
     path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
 };
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use colored::Colorize;
 use structopt::{clap::AppSettings, StructOpt};
 
+use cargo_trim::{
+    git_repo::{expire_reflog, gc, pack_refs, PackageCacheLock},
+    utils::{delete_index_cache, get_size, list_registry_index},
+    ConfigFile, CrateDetail, CrateKind, CrateList, CrateSelector, DirPath, GitDir, Journal,
+    OldMode, Progress, RegistryDir, RegistrySource, TargetArtifact, TargetDir, Trash,
+};
+
 use crate::{
-    config_file::ConfigFile,
-    crate_detail::CrateDetail,
-    dir_path,
-    dir_path::DirPath,
-    git_dir::GitDir,
-    list_crate::CrateList,
-    registry_dir::RegistryDir,
-    utils::{convert_pretty, delete_folder, get_size, print_dash, query_print},
+    output::{
+        convert_age, convert_mb, convert_pretty, print_dash, query_full_width, show_config_added,
+        show_config_set, show_kept_crate, show_query, show_removal_result, show_removal_summary,
+        show_removed_crate, show_selector_expansion, show_target_query, show_top_number_crates,
+    },
+    progress_bar::ProgressBar,
 };
 
+use self::parse::{parse_duration, parse_keep_versions, parse_size};
+
+mod bin;
 mod clear;
 mod config;
 mod git;
+mod history;
 mod init;
 mod list;
+mod parse;
 mod registry;
+mod restore;
+mod select;
 mod set;
+mod target;
+mod trash;
 mod unset;
 
 #[derive(Debug, StructOpt)]
@@ -38,6 +55,12 @@ enum SubCommand {
     List(list::List),
     Git(git::Git),
     Registry(registry::Registry),
+    Select(select::Select),
+    Restore(restore::Restore),
+    Target(target::Target),
+    Bin(bin::Bin),
+    Trash(trash::Trash),
+    History(history::History),
 }
 
 #[derive(Debug, StructOpt)]
@@ -50,6 +73,14 @@ enum SubCommand {
 pub(crate) struct Command {
     #[structopt(long = "all", short = "a", help = "Clean up all registry & git crates")]
     all: bool,
+    #[structopt(
+        long = "cargo-home",
+        help = "Path of cargo home directory to use instead of $CARGO_HOME or $HOME/.cargo",
+        value_name = "path",
+        parse(from_os_str),
+        global = true
+    )]
+    cargo_home: Option<PathBuf>,
     #[structopt(
         long = "directory",
         short = "d",
@@ -64,6 +95,15 @@ pub(crate) struct Command {
         help = "Run command in dry run mode to see what would be done"
     )]
     dry_run: bool,
+    #[structopt(
+        long = "format",
+        help = "Output format of list, query, top and removal summary",
+        possible_values = &["text", "json"],
+        default_value = "text",
+        value_name = "format",
+        global = true
+    )]
+    format: String,
     #[structopt(
         long="gc",
         short="g",
@@ -79,6 +119,31 @@ pub(crate) struct Command {
         hidden = true
     )]
     ignore: Option<Vec<String>>,
+    #[structopt(
+        long = "incremental",
+        help = "Remove incremental compilation cache present in target directory of projects. \
+                Provide all to remove whole cache or newest to only keep newest session of each \
+                crate",
+        possible_values = &["all", "newest"],
+        value_name = "mode"
+    )]
+    incremental: Option<String>,
+    #[structopt(
+        long = "jobs",
+        short = "j",
+        help = "Number of parallel jobs used for scanning crates [default: number of logical cpus]",
+        value_name = "number",
+        global = true
+    )]
+    jobs: Option<usize>,
+    #[structopt(
+        long = "keep-versions",
+        help = "Number of newest versions per crate and registry which are not classified as old \
+                [default: value in config file or 1]",
+        value_name = "number",
+        parse(try_from_str = parse_keep_versions)
+    )]
+    keep_versions: Option<usize>,
     #[structopt(
         long = "light",
         short = "l",
@@ -86,14 +151,42 @@ pub(crate) struct Command {
                 and registry archive for future compilation without internet requirement"
     )]
     light_cleanup: bool,
-    #[structopt(long = "old", short = "o", help = "Clean old cache crates")]
-    old: bool,
+    #[structopt(
+        long = "max-size",
+        help = "Remove least recently used crates until cargo home fits under provided size such \
+                as 5GB. Crates used by projects are removed last",
+        value_name = "size",
+        parse(try_from_str = parse_size)
+    )]
+    max_size: Option<u64>,
+    // short flag is separate from long option so it can be bundled with other flags
+    #[structopt(short = "o", help = "Clean old cache crates using latest mode")]
+    old_flag: bool,
+    #[structopt(
+        long = "old",
+        help = "Clean old cache crates. Latest mode classifies version as old if newer version is \
+                present whereas semver mode only classifies version as old if newer semver \
+                compatible version is present [default: latest]",
+        value_name = "mode",
+        possible_values = &["latest", "semver"],
+        min_values = 0,
+        require_equals = true
+    )]
+    #[allow(clippy::option_option)]
+    old: Option<Option<OldMode>>,
     #[structopt(
         long = "old-orphan",
         short = "z",
         help = "Clean crates which is both old and orphan"
     )]
     old_orphan: bool,
+    #[structopt(
+        long = "older-than",
+        help = "Clean crates which are not used for provided duration such as 30d, 12h or 2w",
+        value_name = "duration",
+        parse(try_from_str = parse_duration)
+    )]
+    older_than: Option<Duration>,
     #[structopt(
         long = "orphan",
         short = "x",
@@ -108,13 +201,25 @@ pub(crate) struct Command {
         help = "Return size of different .cargo/cache folders"
     )]
     query: bool,
+    #[structopt(
+        long = "registry",
+        help = "Only list, clean and query crates of provided registry. Value can be registry \
+                name present in $CARGO_HOME/config.toml such as crates-io, index url or \
+                registry folder name",
+        value_name = "registry",
+        global = true
+    )]
+    registry: Option<String>,
     #[structopt(
         long = "remove",
         short = "r",
-        help = "Remove provided crates from registry or git",
-        value_name = "crate"
+        help = "Remove provided crates from registry or git. Value can be crate name, full name or \
+                glob pattern optionally followed by @ and version requirement such as tokio@<1.20 \
+                or windows-*@0.3x",
+        value_name = "crate",
+        parse(try_from_str = CrateSelector::from_str)
     )]
-    remove: Option<Vec<String>>,
+    remove: Option<Vec<CrateSelector>>,
     #[structopt(
         long = "scan-hidden-folder",
         help = " Whether to scan hidden folder for current command",
@@ -138,6 +243,13 @@ pub(crate) struct Command {
         value_name = "number"
     )]
     top: Option<usize>,
+    #[structopt(
+        long = "trash",
+        help = "Move removed crates to trash instead of deleting them so they can be restored \
+                with cargo trim restore",
+        global = true
+    )]
+    trash: bool,
     #[structopt(
         long = "update",
         short = "u",
@@ -156,76 +268,154 @@ pub(crate) struct Command {
     ], value_name="folder")]
     wipe: Option<Vec<String>>,
     #[structopt(subcommand)]
-    sub_command: Option<SubCommand>,
+    subcommand: Option<SubCommand>,
 }
 
 impl Command {
     pub(crate) fn run(&self) -> Result<()> {
         let dry_run = self.dry_run;
+        let json_format = self.format == "json";
 
         // List out all required path
-        let dir_path = dir_path::DirPath::new()?;
+        let dir_path = DirPath::new(self.cargo_home.as_deref())?;
+
+        // all removal made during run is recorded in journal
+        let journal = Arc::new(Journal::new(dir_path.journal_file()));
+
+        // removed crates and folders are moved to trash which can be restored later
+        let trash = Arc::new(Trash::new(dir_path.trash_dir()));
+        // restore, trash and history subcommand only read trash or journal so they
+        // are run before installed crates are scanned
+        match &self.subcommand {
+            Some(SubCommand::Restore(restore)) => return restore.run(&trash, json_format),
+            Some(SubCommand::Trash(trash_command)) => {
+                return trash_command.run(&trash, json_format)
+            }
+            Some(SubCommand::History(history)) => return history.run(&journal, json_format),
+            _ => {}
+        }
 
         // Read config file data
         let mut config_file = ConfigFile::init(dir_path.config_file())?;
+        // provided keep versions only override config file value for current command
+        let keep_versions = match &self.subcommand {
+            Some(SubCommand::List(list)) => list.keep_versions().or(self.keep_versions),
+            _ => self.keep_versions,
+        };
+
+        // Read registries name and url
+        let mut registry_source = RegistrySource::new(dir_path.home_dir())?;
 
         // create new CrateDetail struct
         let mut crate_detail = CrateDetail::default();
+        if let Some(jobs) = self.jobs {
+            crate_detail.set_jobs(jobs)?;
+        }
+        crate_detail.set_progress(Arc::new(ProgressBar::new()));
+        // provided old mode of subcommand override old mode of main command
+        let old_mode = match &self.subcommand {
+            Some(SubCommand::List(list)) => list.old_mode(),
+            Some(SubCommand::Registry(registry)) => registry.old_mode(),
+            _ => None,
+        };
+        if let Some(old_mode) = old_mode.or(self.old.flatten()) {
+            crate_detail.set_old_mode(old_mode);
+        }
+
+        // only scan registry folders which matches with registry filter
+        let registry_filter = if let Some(registry) = &self.registry {
+            registry_source.set_filter(registry);
+            let registry_dirs = registry_source.list_registry_dir(&dir_path)?;
+            if registry_dirs.is_empty() {
+                bail!("registry {:?} is not present in cargo home", registry);
+            }
+            crate_detail.set_registry_filter(registry_dirs.clone());
+            Some(registry_dirs)
+        } else {
+            None
+        };
 
         // List out crates
         let crate_list =
-            crate::list_crate::CrateList::create_list(&dir_path, &config_file, &mut crate_detail)?;
+            CrateList::create_list(&dir_path, &config_file, &mut crate_detail, keep_versions)?;
 
         if let Some(directories) = &self.directory {
             for directory in directories {
                 config_file.add_directory(directory, dry_run, false)?;
+                show_config_added(directory, dry_run);
             }
         }
         if let Some(ignore_file_names) = &self.ignore {
             for file in ignore_file_names {
                 config_file.add_ignore_file_name(file, dry_run, false)?;
+                show_config_added(file, dry_run);
             }
         }
         if let Some(scan_hidden_folder) = &self.scan_hidden_folder {
-            match scan_hidden_folder.as_str() {
-                "true" => config_file.set_scan_hidden_folder(true, dry_run, false)?,
-                "false" => config_file.set_scan_hidden_folder(false, dry_run, false)?,
-                _ => (),
-            }
+            let value = scan_hidden_folder == "true";
+            config_file.set_scan_hidden_folder(value, dry_run, false)?;
+            show_config_set("scan_hidden_folder", value, dry_run);
         }
         if let Some(scan_target_folder) = &self.scan_target_folder {
-            match scan_target_folder.as_str() {
-                "true" => config_file.set_scan_target_folder(true, dry_run, false)?,
-                "false" => config_file.set_scan_target_folder(false, dry_run, false)?,
-                _ => (),
-            }
+            let value = scan_target_folder == "true";
+            config_file.set_scan_target_folder(value, dry_run, false)?;
+            show_config_set("scan_target_folder", value, dry_run);
         }
 
         if let Some(val) = &self.git_compress {
-            git_compress(
-                val,
-                dir_path.index_dir(),
-                dir_path.checkout_dir(),
-                dir_path.db_dir(),
-                dry_run,
-            )?;
+            git_compress(val, &dir_path, dry_run)?;
+        }
+        let mut registry_crates_location = RegistryDir::new(
+            dir_path.cache_dir(),
+            dir_path.src_dir(),
+            dir_path.index_dir(),
+            crate_list.installed_registry(),
+        )?;
+        if let Some(registry_dirs) = registry_filter {
+            registry_crates_location.set_registry_filter(registry_dirs);
         }
+
+        let mut git_crates_location = GitDir::new(dir_path.checkout_dir(), dir_path.db_dir());
+
+        if self.trash {
+            registry_crates_location.set_trash(Arc::clone(&trash));
+            git_crates_location.set_trash(Arc::clone(&trash));
+        }
+        registry_crates_location.set_journal(Arc::clone(&journal));
+        git_crates_location.set_journal(Arc::clone(&journal));
+        // crates present in keep list are never removed
+        registry_crates_location.set_keep(config_file.keep());
+        git_crates_location.set_keep(config_file.keep());
+
         if self.light_cleanup {
+            journal.set_reason("light");
             light_cleanup(
-                dir_path.checkout_dir(),
-                dir_path.src_dir(),
-                dir_path.index_dir(),
+                &registry_crates_location,
+                &git_crates_location,
+                &dir_path,
+                self.trash.then_some(trash.as_ref()),
+                &journal,
                 dry_run,
             );
         }
         if let Some(folders) = &self.wipe {
+            journal.set_reason("wipe");
             for folder in folders {
-                wipe_directory(folder, &dir_path, dry_run);
+                wipe_directory(
+                    folder,
+                    &registry_crates_location,
+                    &git_crates_location,
+                    &dir_path,
+                    config_file.keep().is_empty(),
+                    self.trash.then_some(trash.as_ref()),
+                    &journal,
+                    dry_run,
+                );
             }
         }
 
         if let Some(number) = self.top {
-            top_crates(&crate_detail, number);
+            top_crates(&crate_detail, number, json_format);
         }
 
         if self.update {
@@ -234,30 +424,43 @@ impl Command {
         }
 
         if self.query {
-            query_size(&dir_path, &crate_list, &crate_detail);
+            query_size(
+                &dir_path,
+                &config_file,
+                &crate_list,
+                &crate_detail,
+                &registry_source,
+                json_format,
+            );
         }
 
-        let mut registry_crates_location = crate::registry_dir::RegistryDir::new(
-            dir_path.cache_dir(),
-            dir_path.src_dir(),
-            dir_path.index_dir(),
-            crate_list.installed_registry(),
-        )?;
-
-        let git_crates_location =
-            crate::git_dir::GitDir::new(dir_path.checkout_dir(), dir_path.db_dir());
-
-        if self.old {
+        if self.old_flag || self.old.is_some() {
+            journal.set_reason("old");
             old_clean(
                 &crate_list,
                 &mut registry_crates_location,
                 &git_crates_location,
                 &crate_detail,
                 dry_run,
+                json_format,
+            );
+        }
+
+        if let Some(duration) = self.older_than {
+            journal.set_reason("older-than");
+            older_than_clean(
+                &crate_list,
+                &mut registry_crates_location,
+                &git_crates_location,
+                &crate_detail,
+                duration,
+                dry_run,
+                json_format,
             );
         }
 
         if self.old_orphan {
+            journal.set_reason("old-orphan");
             old_orphan_clean(
                 &crate_list,
                 &mut registry_crates_location,
@@ -265,10 +468,12 @@ impl Command {
                 &crate_detail,
                 config_file.directory().is_empty(),
                 dry_run,
+                json_format,
             )?;
         }
 
         if self.orphan {
+            journal.set_reason("orphan");
             orphan_clean(
                 &crate_list,
                 &mut registry_crates_location,
@@ -276,20 +481,38 @@ impl Command {
                 &crate_detail,
                 config_file.directory().is_empty(),
                 dry_run,
+                json_format,
             )?;
         }
 
         if self.all {
+            journal.set_reason("all");
             remove_all(
                 &crate_list,
                 &mut registry_crates_location,
                 &git_crates_location,
                 &crate_detail,
                 dry_run,
+                json_format,
+            );
+        }
+
+        if let Some(max_size) = self.max_size {
+            journal.set_reason("max-size");
+            max_size_clean(
+                &dir_path,
+                &crate_list,
+                &mut registry_crates_location,
+                &git_crates_location,
+                &crate_detail,
+                max_size,
+                dry_run,
+                json_format,
             );
         }
 
         if let Some(crates) = &self.remove {
+            journal.set_reason("remove");
             remove_crates(
                 crates,
                 &crate_list,
@@ -297,10 +520,24 @@ impl Command {
                 &git_crates_location,
                 &crate_detail,
                 dry_run,
+                json_format,
             );
         }
 
-        if let Some(sub_command) = &self.sub_command {
+        if let Some(mode) = &self.incremental {
+            journal.set_reason("incremental");
+            let target_dirs = TargetDir::list(&dir_path, &config_file)?;
+            target::incremental_clean(
+                &target_dirs,
+                mode,
+                self.trash.then_some(trash.as_ref()),
+                &journal,
+                dry_run,
+                json_format,
+            );
+        }
+
+        if let Some(sub_command) = &self.subcommand {
             match &sub_command {
                 SubCommand::Init(init) => init.run(&mut config_file)?,
                 SubCommand::Clear(clear) => clear.run(&mut config_file)?,
@@ -308,7 +545,9 @@ impl Command {
                 SubCommand::List(list) => list.run(
                     &crate_detail,
                     &crate_list,
+                    &registry_source,
                     config_file.directory().is_empty(),
+                    json_format,
                 ),
                 SubCommand::Set(set) => set.run(&mut config_file)?,
                 SubCommand::Unset(unset) => unset.run(&mut config_file)?,
@@ -317,15 +556,46 @@ impl Command {
                     &crate_list,
                     &crate_detail,
                     &git_crates_location,
+                    &journal,
                     config_file.directory().is_empty(),
+                    json_format,
                 )?,
                 SubCommand::Registry(registry) => registry.run(
                     &dir_path,
                     &crate_list,
                     &crate_detail,
+                    &registry_source,
                     &mut registry_crates_location,
+                    self.trash.then_some(trash.as_ref()),
+                    &journal,
                     config_file.directory().is_empty(),
+                    json_format,
+                )?,
+                SubCommand::Select(select) => {
+                    journal.set_reason("select");
+                    select.run(
+                        &crate_list,
+                        &crate_detail,
+                        &mut registry_crates_location,
+                        &git_crates_location,
+                        json_format,
+                    )?;
+                }
+                SubCommand::Target(target) => target.run(
+                    &dir_path,
+                    &config_file,
+                    self.trash.then_some(trash.as_ref()),
+                    &journal,
+                    json_format,
+                )?,
+                SubCommand::Bin(bin) => bin.run(
+                    &dir_path,
+                    self.trash.then_some(trash.as_ref()),
+                    &journal,
+                    json_format,
                 )?,
+                // already run before crates are scanned
+                SubCommand::Restore(_) | SubCommand::Trash(_) | SubCommand::History(_) => {}
             }
         }
 
@@ -334,27 +604,30 @@ impl Command {
 }
 
 // Git compress git files according to provided value if option
-fn git_compress(
-    value: &str,
-    index_dir: &Path,
-    checkout_dir: &Path,
-    db_dir: &Path,
-    dry_run: bool,
-) -> Result<()> {
-    if (value == "index" || value == "all") && index_dir.exists() {
-        for entry in fs::read_dir(index_dir).context("failed to read registry index folder")? {
-            let repo_path = entry?.path();
+fn git_compress(value: &str, dir_path: &DirPath, dry_run: bool) -> Result<()> {
+    let index_dir = dir_path.index_dir();
+    let checkout_dir = dir_path.checkout_dir();
+    let db_dir = dir_path.db_dir();
+    // list of repo along with message shown before compressing repo
+    let mut repo_list = Vec::new();
+    if value == "index" || value == "all" {
+        let (git_index, sparse_index) = list_registry_index(index_dir)?;
+        for repo_path in git_index {
             let file_name = repo_path
                 .file_name()
                 .context("Failed to get a file name / folder name")?;
-            if !dry_run {
-                println!(
-                    "{}",
-                    format!("Compressing {} registry index", file_name.to_str().unwrap())
-                        .color("blue")
-                );
-            }
-            run_git_compress_commands(&repo_path, dry_run);
+            let message = format!("Compressing {} registry index", file_name.to_str().unwrap());
+            repo_list.push((message, repo_path));
+        }
+        // sparse registry index is not a git repo so it cannot be git compressed
+        for repo_path in sparse_index {
+            let file_name = repo_path
+                .file_name()
+                .context("Failed to get a file name / folder name")?;
+            println!(
+                "Skipping {} sparse registry index",
+                file_name.to_str().unwrap()
+            );
         }
     }
     // if git is provided it git compress all git folders
@@ -365,127 +638,166 @@ fn git_compress(
                 for rev in fs::read_dir(repo_path)
                     .context("failed to read checkout directory sub directory")?
                 {
-                    let rev_path = rev?.path();
-                    if !dry_run {
-                        println!("{}", "Compressing git checkout".color("blue"));
-                    }
-                    run_git_compress_commands(&rev_path, dry_run);
+                    repo_list.push(("Compressing git checkout".to_string(), rev?.path()));
                 }
             }
         }
         if (value == "git" || value == "git-db") && db_dir.exists() {
             for entry in fs::read_dir(db_dir).context("failed to read db dir")? {
-                let repo_path = entry?.path();
-                if !dry_run {
-                    println!("{}", "Compressing git db".color("blue"));
-                }
-                run_git_compress_commands(&repo_path, dry_run);
+                repo_list.push(("Compressing git db".to_string(), entry?.path()));
             }
         }
     }
+    // cargo do not update any git repository while package cache is locked
+    let lock = if dry_run || repo_list.is_empty() {
+        None
+    } else {
+        Some(PackageCacheLock::acquire(dir_path.home_dir())?)
+    };
+    let progress = ProgressBar::new();
+    progress.start("Git compressing", repo_list.len());
+    for (message, repo_path) in &repo_list {
+        let size_before = get_size(repo_path).unwrap_or(0);
+        progress.suspend(|| {
+            if !dry_run {
+                println!("{}", message.color("blue"));
+            }
+            run_git_compress_commands(repo_path, lock.as_ref());
+        });
+        let size_after = get_size(repo_path).unwrap_or(size_before);
+        let item = repo_path.file_name().and_then(|name| name.to_str());
+        progress.advance(
+            item.unwrap_or_default(),
+            size_before.saturating_sub(size_after),
+        );
+    }
+    progress.finish();
     println!("{}", "Git compress task completed".color("blue"));
     Ok(())
 }
 
-// run combination of commands which git compress a index of registry
-fn run_git_compress_commands(repo_path: &Path, dry_run: bool) {
-    if dry_run {
-        println!(
-            "{} git compressing {:?}",
-            "Dry run:".color("yellow"),
-            repo_path
-        );
-    } else {
+// run combination of commands which git compress a index of registry. Lock is
+// not provided for dry run
+// path is debug formatted so it is printed within quotes
+#[allow(clippy::unnecessary_debug_formatting)]
+fn run_git_compress_commands(repo_path: &Path, lock: Option<&PackageCacheLock>) {
+    if let Some(lock) = lock {
         // Remove history of all checkout which will help in remove dangling commits
-        if let Err(e) = std::process::Command::new("git")
-            .arg("reflog")
-            .arg("expire")
-            .arg("--expire=now")
-            .arg("--all")
-            .current_dir(repo_path)
-            .output()
-        {
+        if let Err(e) = expire_reflog(repo_path, lock) {
             eprintln!(
                 "{}",
-                format!("  \u{2514} git reflog failed to execute due to error {}", e).color("red")
+                format!("  \u{2514} git reflog failed to execute due to error {e:#}").color("red")
             );
-        } else {
-            println!("{:70}.......Step 1/3", "  \u{251c} Completed git reflog");
+            return;
         }
+        println!("{:70}.......Step 1/3", "  \u{251c} Completed git reflog");
 
         // pack refs of branches/tags etc into one file know as pack-refs file for
         // effective repo access
-        if let Err(e) = std::process::Command::new("git")
-            .arg("pack-refs")
-            .arg("--all")
-            .arg("--prune")
-            .current_dir(repo_path)
-            .output()
-        {
+        if let Err(e) = pack_refs(repo_path, lock) {
             eprintln!(
                 "{}",
-                format!(
-                    "  \u{2514} git pack-refs failed to execute due to error {}",
-                    e
-                )
-                .color("red")
-            );
-        } else {
-            println!(
-                "{:70}.......Step 2/3",
-                "  \u{251c} Packed refs and tags successfully"
+                format!("  \u{2514} git pack-refs failed to execute due to error {e:#}")
+                    .color("red")
             );
+            return;
         }
+        println!(
+            "{:70}.......Step 2/3",
+            "  \u{251c} Packed refs and tags successfully"
+        );
 
         // cleanup unnecessary file and optimize a local repo
-        if let Err(e) = std::process::Command::new("git")
-            .arg("gc")
-            .arg("--aggressive")
-            .arg("--prune=now")
-            .current_dir(repo_path)
-            .output()
-        {
+        if let Err(e) = gc(repo_path, lock) {
             eprintln!(
                 "{}",
-                format!("  \u{2514} git gc failed to execute due to error {}", e).color("red")
-            );
-        } else {
-            println!(
-                "{:70}.......Step 3/3",
-                "  \u{2514} Cleaned up unnecessary files and optimize a files"
+                format!("  \u{2514} git gc failed to execute due to error {e:#}").color("red")
             );
+            return;
         }
+        println!(
+            "{:70}.......Step 3/3",
+            "  \u{2514} Cleaned up unnecessary files and optimize a files"
+        );
+    } else {
+        println!(
+            "{} git compressing {:?}",
+            "Dry run:".color("yellow"),
+            repo_path
+        );
     }
 }
 // light cleanup registry directory
-fn light_cleanup(checkout_dir: &Path, src_dir: &Path, index_dir: &Path, dry_run: bool) {
+fn light_cleanup(
+    registry_crates_location: &RegistryDir,
+    git_crates_location: &GitDir,
+    dir_path: &DirPath,
+    trash: Option<&Trash>,
+    journal: &Journal,
+    dry_run: bool,
+) {
     let mut light_cleanup_success = true;
     // light cleanup registry
-    light_cleanup_success =
-        registry::light_cleanup_registry(src_dir, index_dir, dry_run) && light_cleanup_success;
+    light_cleanup_success = registry::light_cleanup_registry(
+        registry_crates_location,
+        dir_path.src_dir(),
+        dir_path.index_dir(),
+        trash,
+        journal,
+        dry_run,
+    ) && light_cleanup_success;
     // light cleanup git
-    light_cleanup_success = git::light_cleanup_git(checkout_dir, dry_run) && light_cleanup_success;
+    light_cleanup_success =
+        git::light_cleanup_git(git_crates_location, dir_path.checkout_dir(), dry_run)
+            && light_cleanup_success;
     if !light_cleanup_success {
         println!("Failed to delete some folder during light cleanup");
     }
 }
 
-// wipe certain directory
-fn wipe_directory(folder: &str, dir_path: &DirPath, dry_run: bool) {
+// wipe certain directory. Crates present in keep list are left in place so git
+// and registry folder is only removed as whole when keep list is empty
+#[allow(clippy::too_many_arguments)]
+fn wipe_directory(
+    folder: &str,
+    registry_crates_location: &RegistryDir,
+    git_crates_location: &GitDir,
+    dir_path: &DirPath,
+    keep_is_empty: bool,
+    trash: Option<&Trash>,
+    journal: &Journal,
+    dry_run: bool,
+) {
     let has_failed = match folder {
-        "git" => delete_folder(dir_path.git_dir(), dry_run),
-        "checkouts" => delete_folder(dir_path.checkout_dir(), dry_run),
-        "db" => delete_folder(dir_path.db_dir(), dry_run),
-        "registry" => delete_folder(dir_path.registry_dir(), dry_run),
-        "cache" => delete_folder(dir_path.cache_dir(), dry_run),
-        "index" => delete_folder(dir_path.index_dir(), dry_run),
-        "index-cache" => crate::utils::delete_index_cache(dir_path.index_dir(), dry_run),
-        "src" => delete_folder(dir_path.src_dir(), dry_run),
+        "git" if keep_is_empty => journal.remove_folder(folder, dir_path.git_dir(), trash, dry_run),
+        "git" => git_crates_location
+            .remove_all_checkouts(dry_run)
+            .and(git_crates_location.remove_all_db(dry_run)),
+        "checkouts" => git_crates_location.remove_all_checkouts(dry_run),
+        "db" => git_crates_location.remove_all_db(dry_run),
+        "registry" if keep_is_empty => {
+            journal.remove_folder(folder, dir_path.registry_dir(), trash, dry_run)
+        }
+        "registry" => registry_crates_location
+            .remove_all_cache(dry_run)
+            .and(registry_crates_location.remove_all_src(dry_run))
+            .and(journal.remove_folder("index", dir_path.index_dir(), trash, dry_run)),
+        "cache" => registry_crates_location.remove_all_cache(dry_run),
+        "index" => journal.remove_folder(folder, dir_path.index_dir(), trash, dry_run),
+        "index-cache" => delete_index_cache(dir_path.index_dir(), trash, Some(journal), dry_run),
+        "src" => registry_crates_location.remove_all_src(dry_run),
         _ => Ok(()),
     }
     .is_err();
     if has_failed {
-        println!("Failed to remove {:?} directory", folder);
+        println!("Failed to remove {folder:?} directory");
+    } else if dry_run {
+        println!(
+            "{} {} {:?} directory",
+            "Dry run:".color("yellow"),
+            "Removed".color("red"),
+            folder
+        );
     } else {
         println!("{} {:?} directory", "Removed".color("red"), folder);
     }
@@ -494,6 +806,8 @@ fn wipe_directory(folder: &str, dir_path: &DirPath, dry_run: bool) {
 // Update cargo toml
 
 // Update cargo lock
+// path is debug formatted so it is printed within quotes
+#[allow(clippy::unnecessary_debug_formatting)]
 fn update_cargo_toml(cargo_toml_location: &[PathBuf], dry_run: bool) -> Result<()> {
     for location in cargo_toml_location {
         let mut cargo_lock = location.clone();
@@ -516,7 +830,7 @@ fn update_cargo_toml(cargo_toml_location: &[PathBuf], dry_run: bool) -> Result<(
                 );
             } else {
                 let message = format!("Updating {}", cargo_lock.to_str().unwrap().color("blue"));
-                println!("{}", message);
+                println!("{message}");
                 std::process::Command::new("cargo")
                     .arg("update")
                     .current_dir(location)
@@ -532,29 +846,84 @@ fn update_cargo_toml(cargo_toml_location: &[PathBuf], dry_run: bool) -> Result<(
 }
 
 // show top n crates
-fn top_crates(crate_detail: &CrateDetail, number: usize) {
-    crate::utils::show_top_number_crates(crate_detail.bin(), "bin", number);
-    registry::top_crates_registry(crate_detail, number);
-    git::top_crates_git(crate_detail, number);
+fn top_crates(crate_detail: &CrateDetail, number: usize, json_format: bool) {
+    show_top_number_crates(crate_detail.bin(), "bin", number, json_format);
+    registry::top_crates_registry(crate_detail, number, json_format);
+    git::top_crates_git(crate_detail, number, json_format);
 }
 
 // query size of directory of cargo home folder provide some valuable size
 // information
-fn query_size(dir_path: &DirPath, crate_list: &CrateList, crate_detail: &CrateDetail) {
+fn query_size(
+    dir_path: &DirPath,
+    config_file: &ConfigFile,
+    crate_list: &CrateList,
+    crate_detail: &CrateDetail,
+    registry_source: &RegistrySource,
+    json_format: bool,
+) {
     let mut final_size = 0_u64;
     let bin_dir_size = get_size(dir_path.bin_dir()).unwrap_or(0_u64);
     final_size += bin_dir_size;
-    query_print(
+    show_query(
         &format!(
             "Total size of {} .cargo/bin binary:",
             crate_list.installed_bin().len()
         ),
-        &convert_pretty(bin_dir_size),
+        "bin",
+        Some(crate_list.installed_bin().len()),
+        bin_dir_size,
+        json_format,
+    );
+    if !json_format {
+        print_dash(query_full_width());
+    }
+    final_size += registry::query_size_registry(
+        dir_path,
+        crate_list,
+        crate_detail,
+        registry_source,
+        json_format,
+    );
+    final_size += git::query_size_git(dir_path, crate_list, crate_detail, json_format);
+    show_query("Total size", "total", None, final_size, json_format);
+    // target directories are present outside of cargo home so they are not part of
+    // total size
+    let target_dirs = TargetDir::list(dir_path, config_file).unwrap_or_default();
+    let incremental_dirs = target_dirs
+        .iter()
+        .filter_map(|target_dir| target_dir.list_incremental_dirs().ok())
+        .flatten()
+        .collect::<Vec<_>>();
+    if !json_format {
+        print_dash(query_full_width());
+    }
+    show_query(
+        &format!(
+            "Total size of {} incremental cache folders:",
+            incremental_dirs.len()
+        ),
+        "incremental",
+        Some(incremental_dirs.len()),
+        incremental_dirs.iter().map(TargetArtifact::size).sum(),
+        json_format,
+    );
+    if target_dirs.is_empty() {
+        return;
+    }
+    if !json_format {
+        print_dash(query_full_width());
+    }
+    for target_dir in &target_dirs {
+        show_target_query(target_dir, json_format);
+    }
+    show_query(
+        &format!("Total size of {} target directories:", target_dirs.len()),
+        "targets",
+        Some(target_dirs.len()),
+        target_dirs.iter().map(TargetDir::size).sum(),
+        json_format,
     );
-    print_dash(crate::utils::query_full_width());
-    final_size += registry::query_size_registry(dir_path, crate_list, crate_detail);
-    final_size += git::query_size_git(dir_path, crate_list, crate_detail);
-    query_print("Total size", &convert_pretty(final_size));
 }
 
 // Clean old crates
@@ -564,19 +933,75 @@ fn old_clean(
     git_crates_location: &GitDir,
     crate_detail: &CrateDetail,
     dry_run: bool,
+    json_format: bool,
 ) {
-    let (registry_sized_cleaned, total_registry_crate_removed) =
-        registry::old_clean_registry(registry_crates_location, crate_list, crate_detail, dry_run);
-    let (git_sized_cleaned, total_git_crate_removed) =
-        git::old_clean_git(git_crates_location, crate_list, crate_detail, dry_run);
-    println!(
-        "{}",
-        format!(
+    let (registry_sized_cleaned, total_registry_crate_removed) = registry::old_clean_registry(
+        registry_crates_location,
+        crate_list,
+        crate_detail,
+        dry_run,
+        json_format,
+    );
+    let (git_sized_cleaned, total_git_crate_removed) = git::old_clean_git(
+        git_crates_location,
+        crate_list,
+        crate_detail,
+        dry_run,
+        json_format,
+    );
+    show_removal_summary(
+        "old",
+        &format!(
             "{} old crates removed which had occupied {:.3} MB",
             total_git_crate_removed + total_registry_crate_removed,
-            git_sized_cleaned + registry_sized_cleaned
-        )
-        .color("blue")
+            convert_mb(git_sized_cleaned + registry_sized_cleaned)
+        ),
+        total_git_crate_removed + total_registry_crate_removed,
+        git_sized_cleaned + registry_sized_cleaned,
+        dry_run,
+        json_format,
+    );
+}
+
+// Clean crates which are not used for provided duration
+fn older_than_clean(
+    crate_list: &CrateList,
+    registry_crates_location: &mut RegistryDir,
+    git_crates_location: &GitDir,
+    crate_detail: &CrateDetail,
+    duration: Duration,
+    dry_run: bool,
+    json_format: bool,
+) {
+    let (registry_sized_cleaned, total_registry_crate_removed) =
+        registry::older_than_clean_registry(
+            registry_crates_location,
+            crate_list,
+            crate_detail,
+            duration,
+            dry_run,
+            json_format,
+        );
+    let (git_sized_cleaned, total_git_crate_removed) = git::older_than_clean_git(
+        git_crates_location,
+        crate_list,
+        crate_detail,
+        duration,
+        dry_run,
+        json_format,
+    );
+    show_removal_summary(
+        "older_than",
+        &format!(
+            "{} crates not used for {} removed which had occupied {:.3} MB",
+            total_git_crate_removed + total_registry_crate_removed,
+            convert_age(duration),
+            convert_mb(git_sized_cleaned + registry_sized_cleaned)
+        ),
+        total_git_crate_removed + total_registry_crate_removed,
+        git_sized_cleaned + registry_sized_cleaned,
+        dry_run,
+        json_format,
     );
 }
 
@@ -588,6 +1013,7 @@ fn old_orphan_clean(
     crate_detail: &CrateDetail,
     directory_is_empty: bool,
     dry_run: bool,
+    json_format: bool,
 ) -> Result<()> {
     if directory_is_empty {
         let warning_text = "WARNING: You have not initialized any directory as rust project \
@@ -616,18 +1042,27 @@ fn old_orphan_clean(
             crate_list,
             crate_detail,
             dry_run,
+            json_format,
         );
-    let (git_sized_cleaned, total_git_crate_removed) =
-        git::old_orphan_clean_git(git_crates_location, crate_list, crate_detail, dry_run);
+    let (git_sized_cleaned, total_git_crate_removed) = git::old_orphan_clean_git(
+        git_crates_location,
+        crate_list,
+        crate_detail,
+        dry_run,
+        json_format,
+    );
 
-    println!(
-        "{}",
-        format!(
+    show_removal_summary(
+        "old_orphan",
+        &format!(
             "{} crates which are both old and orphan crate removed which had {:.3} MB",
             total_git_crate_removed + total_registry_crate_removed,
-            git_sized_cleaned + registry_sized_cleaned
-        )
-        .color("blue")
+            convert_mb(git_sized_cleaned + registry_sized_cleaned)
+        ),
+        total_git_crate_removed + total_registry_crate_removed,
+        git_sized_cleaned + registry_sized_cleaned,
+        dry_run,
+        json_format,
     );
     Ok(())
 }
@@ -640,6 +1075,7 @@ fn orphan_clean(
     crate_detail: &CrateDetail,
     directory_is_empty: bool,
     dry_run: bool,
+    json_format: bool,
 ) -> Result<()> {
     if directory_is_empty {
         let warning_text = "WARNING: You have not initialized any directory as rust project \
@@ -667,18 +1103,27 @@ fn orphan_clean(
         crate_list,
         crate_detail,
         dry_run,
+        json_format,
+    );
+    let (git_sized_cleaned, total_git_crate_removed) = git::orphan_clean_git(
+        git_crates_location,
+        crate_list,
+        crate_detail,
+        dry_run,
+        json_format,
     );
-    let (git_sized_cleaned, total_git_crate_removed) =
-        git::orphan_clean_git(git_crates_location, crate_list, crate_detail, dry_run);
 
-    println!(
-        "{}",
-        format!(
+    show_removal_summary(
+        "orphan",
+        &format!(
             "{} orphan crates removed which had occupied {:.3} MB",
             total_git_crate_removed + total_registry_crate_removed,
-            git_sized_cleaned + registry_sized_cleaned
-        )
-        .color("blue")
+            convert_mb(git_sized_cleaned + registry_sized_cleaned)
+        ),
+        total_git_crate_removed + total_registry_crate_removed,
+        git_sized_cleaned + registry_sized_cleaned,
+        dry_run,
+        json_format,
     );
     Ok(())
 }
@@ -690,46 +1135,178 @@ fn remove_all(
     git_crates_location: &GitDir,
     crate_detail: &CrateDetail,
     dry_run: bool,
+    json_format: bool,
 ) {
-    let (registry_sized_cleaned, total_registry_crate_removed) =
-        registry::all_clean_registry(registry_crates_location, crate_list, crate_detail, dry_run);
-    let (git_sized_cleaned, total_git_crate_removed) =
-        git::all_clean_git(git_crates_location, crate_list, crate_detail, dry_run);
+    let (registry_sized_cleaned, total_registry_crate_removed) = registry::all_clean_registry(
+        registry_crates_location,
+        crate_list,
+        crate_detail,
+        dry_run,
+        json_format,
+    );
+    let (git_sized_cleaned, total_git_crate_removed) = git::all_clean_git(
+        git_crates_location,
+        crate_list,
+        crate_detail,
+        dry_run,
+        json_format,
+    );
 
-    println!(
-        "{}",
-        format!(
+    show_removal_summary(
+        "all",
+        &format!(
             "Total size of  {} crates removed :- {:.3} MB",
             total_git_crate_removed + total_registry_crate_removed,
-            git_sized_cleaned + registry_sized_cleaned
-        )
-        .color("blue")
+            convert_mb(git_sized_cleaned + registry_sized_cleaned)
+        ),
+        total_git_crate_removed + total_registry_crate_removed,
+        git_sized_cleaned + registry_sized_cleaned,
+        dry_run,
+        json_format,
     );
 }
 
-// Remove certain crates
-fn remove_crates(
-    crates: &[String],
+// remove least recently used crates until cargo home size fits under max size
+#[allow(clippy::too_many_arguments)]
+fn max_size_clean(
+    dir_path: &DirPath,
     crate_list: &CrateList,
     registry_crates_location: &mut RegistryDir,
     git_crates_location: &GitDir,
     crate_detail: &CrateDetail,
+    max_size: u64,
     dry_run: bool,
+    json_format: bool,
 ) {
-    let mut size_cleaned = 0.0;
-    for crate_name in crates {
-        if crate_list.installed_registry().contains(crate_name) {
-            registry_crates_location.remove_crate(crate_name, dry_run);
-            size_cleaned += crate_detail.find_size_registry_all(crate_name);
+    let mut cargo_home_size = get_size(dir_path.home_dir()).unwrap_or(0_u64);
+    let mut size_cleaned = 0;
+    let mut total_crate_removed = 0;
+    let eviction_order = crate_list.list_eviction_order(crate_detail);
+    let progress = ProgressBar::new();
+    progress.start("Removing least recently used crates", eviction_order.len());
+    for (crate_name, kind) in eviction_order {
+        if cargo_home_size <= max_size {
+            break;
         }
+        let is_kept = match kind {
+            CrateKind::Registry => registry_crates_location.is_kept(&crate_name),
+            CrateKind::Git => git_crates_location.is_kept(&crate_name),
+        };
+        if is_kept {
+            progress.suspend(|| show_kept_crate(&crate_name, kind.as_str(), json_format));
+            progress.advance(&crate_name, 0);
+            continue;
+        }
+        let is_success = match kind {
+            CrateKind::Registry => registry_crates_location
+                .remove_crate(&crate_name, dry_run)
+                .is_ok(),
+            CrateKind::Git => git_crates_location
+                .remove_crate(&crate_name, dry_run)
+                .is_ok(),
+        };
+        progress.suspend(|| {
+            show_removed_crate(&crate_name, kind.as_str(), is_success, dry_run, json_format);
+        });
+        // failed removal do not free any space so eviction continues with next crate
+        if !is_success {
+            progress.advance(&crate_name, 0);
+            continue;
+        }
+        let crate_bytes = crate_detail.find_bytes(&crate_name, kind);
+        size_cleaned += crate_bytes;
+        cargo_home_size = cargo_home_size.saturating_sub(crate_bytes);
+        total_crate_removed += 1;
+        progress.advance(&crate_name, crate_bytes);
+    }
+    progress.finish();
+    show_removal_summary(
+        "max_size",
+        &format!(
+            "{} least recently used crates removed which had occupied {:.3} MB",
+            total_crate_removed,
+            convert_mb(size_cleaned)
+        ),
+        total_crate_removed,
+        size_cleaned,
+        dry_run,
+        json_format,
+    );
+    if cargo_home_size > max_size {
+        println!(
+            "{}",
+            format!(
+                "Cargo home size {} is still above {} after removing all crates",
+                convert_pretty(cargo_home_size),
+                convert_pretty(max_size)
+            )
+            .color("yellow")
+        );
+    }
+}
 
-        if crate_list.installed_git().contains(crate_name) {
-            git_crates_location.remove_crate(crate_name, dry_run);
-            size_cleaned += crate_detail.find_size_git_all(crate_name);
+// Remove registry and git crates matching provided selectors. Dry run list
+// crates matched by each selector
+fn remove_crates(
+    selectors: &[CrateSelector],
+    crate_list: &CrateList,
+    registry_crates_location: &mut RegistryDir,
+    git_crates_location: &GitDir,
+    crate_detail: &CrateDetail,
+    dry_run: bool,
+    json_format: bool,
+) {
+    let mut crates = Vec::new();
+    for selector in selectors {
+        let registry_matched = selector.expand_registry(crate_list.installed_registry());
+        let git_matched = selector.expand_git(crate_list.installed_git());
+        if dry_run {
+            let matched = registry_matched
+                .iter()
+                .chain(git_matched.iter())
+                .copied()
+                .collect::<Vec<_>>();
+            show_selector_expansion(selector.value(), &matched, json_format);
+        }
+        let registry_matched = registry_matched
+            .into_iter()
+            .map(|name| (name, CrateKind::Registry));
+        let git_matched = git_matched.into_iter().map(|name| (name, CrateKind::Git));
+        for matched in registry_matched.chain(git_matched) {
+            if !crates.contains(&matched) {
+                crates.push(matched);
+            }
         }
     }
-    println!(
-        "{}",
-        format!("Total size removed :- {:.3} MB", size_cleaned).color("blue")
+    let mut size_cleaned = 0;
+    let mut total_crate_removed = 0;
+    for (crate_name, kind) in crates {
+        let result = if kind == CrateKind::Registry {
+            if registry_crates_location.is_kept(crate_name) {
+                show_kept_crate(crate_name, "registry", json_format);
+                continue;
+            }
+            registry_crates_location.remove_crate(crate_name, dry_run)
+        } else {
+            if git_crates_location.is_kept(crate_name) {
+                show_kept_crate(crate_name, "git", json_format);
+                continue;
+            }
+            git_crates_location.remove_crate(crate_name, dry_run)
+        };
+        show_removal_result(crate_name, kind.as_str(), &result, dry_run, json_format);
+        // crate which failed to be removed is not counted
+        if result.is_ok() {
+            size_cleaned += crate_detail.find_bytes(crate_name, kind);
+            total_crate_removed += 1;
+        }
+    }
+    show_removal_summary(
+        "remove",
+        &format!("Total size removed :- {:.3} MB", convert_mb(size_cleaned)),
+        total_crate_removed,
+        size_cleaned,
+        dry_run,
+        json_format,
     );
 }
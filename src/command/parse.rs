@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+
+// parse duration from string such as 30d, 12h or 2w
+pub(super) fn parse_duration(value: &str) -> Result<Duration> {
+    let value = value.trim();
+    let unit_position = value
+        .find(|c: char| !c.is_ascii_digit())
+        .context("duration is missing unit. Valid units are s, m, h, d and w")?;
+    let (number, unit) = value.split_at(unit_position);
+    let number = number
+        .parse::<u64>()
+        .with_context(|| format!("invalid duration number {value:?}"))?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => bail!(
+            "invalid duration unit {:?}. Valid units are s, m, h, d and w",
+            unit
+        ),
+    };
+    let seconds = number
+        .checked_mul(seconds)
+        .with_context(|| format!("duration {value:?} is too large"))?;
+    Ok(Duration::from_secs(seconds))
+}
+
+// parse size in bytes from string such as 500MB, 5GB or 2GiB
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+pub(super) fn parse_size(value: &str) -> Result<u64> {
+    let value = value.trim();
+    let unit_position = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(unit_position);
+    let number = number
+        .parse::<f64>()
+        .with_context(|| format!("invalid size number {value:?}"))?;
+    let factor: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "kb" => 1000,
+        "mb" => 1000_u64.pow(2),
+        "gb" => 1000_u64.pow(3),
+        "tb" => 1000_u64.pow(4),
+        "kib" => 1024,
+        "mib" => 1024_u64.pow(2),
+        "gib" => 1024_u64.pow(3),
+        "tib" => 1024_u64.pow(4),
+        _ => bail!(
+            "invalid size unit {:?}. Valid units are B, kB, MB, GB, TB, KiB, MiB, GiB and TiB",
+            unit
+        ),
+    };
+    Ok((number * factor as f64) as u64)
+}
+
+// parse number of newest versions to keep per crate. At least single version
+// needs to be kept
+pub(super) fn parse_keep_versions(value: &str) -> Result<usize> {
+    let keep_versions = value
+        .parse::<usize>()
+        .with_context(|| format!("invalid number of versions {value:?}"))?;
+    if keep_versions == 0 {
+        bail!("number of versions to keep should be at least 1");
+    }
+    Ok(keep_versions)
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{parse_duration, parse_size};
+
+    const MINUTE: u64 = 60;
+    const HOUR: u64 = 60 * MINUTE;
+    const DAY: u64 = 24 * HOUR;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("45s").unwrap(), Duration::from_secs(45));
+        assert_eq!(
+            parse_duration("10m").unwrap(),
+            Duration::from_secs(10 * MINUTE)
+        );
+        assert_eq!(
+            parse_duration("12h").unwrap(),
+            Duration::from_secs(12 * HOUR)
+        );
+        assert_eq!(
+            parse_duration("30d").unwrap(),
+            Duration::from_secs(30 * DAY)
+        );
+        assert_eq!(parse_duration("2w").unwrap(), Duration::from_secs(14 * DAY));
+        assert!(parse_duration("30").is_err());
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("3y").is_err());
+        assert!(parse_duration("99999999999999999w").is_err());
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("100").unwrap(), 100);
+        assert_eq!(parse_size("12B").unwrap(), 12);
+        assert_eq!(parse_size("500MB").unwrap(), 500_000_000);
+        assert_eq!(parse_size("5GB").unwrap(), 5_000_000_000);
+        assert_eq!(parse_size("1.5 gb").unwrap(), 1_500_000_000);
+        assert_eq!(parse_size("2GiB").unwrap(), 2_147_483_648);
+        assert!(parse_size("GB").is_err());
+        assert!(parse_size("5PB").is_err());
+    }
+}
@@ -1,17 +1,31 @@
-use std::{io::Write, path::Path};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
 
 use anyhow::{Context, Result};
 use colored::Colorize;
 use structopt::{clap::AppSettings, StructOpt};
 
+use cargo_trim::{
+    utils::{delete_index_cache, get_size, list_registry_index},
+    CrateDetail, CrateKind, CrateList, CrateSelector, DirPath, Journal, OldMode, Progress,
+    RegistryDir, RegistrySource, Trash,
+};
+
 use crate::{
-    crate_detail::CrateDetail,
-    dir_path::DirPath,
-    list_crate::CrateList,
-    registry_dir::RegistryDir,
-    utils::{convert_pretty, get_size, print_dash, query_print, show_top_number_crates},
+    output::{
+        convert_age, convert_mb, print_dash, query_full_width, show_dry_run_removed,
+        show_kept_crate, show_query, show_registry_query, show_removal_result,
+        show_removal_summary, show_selector_expansion, show_top_number_crates,
+    },
+    progress_bar::ProgressBar,
 };
 
+use super::parse::parse_duration;
+
 #[derive(Debug, StructOpt)]
 #[structopt(about="Perform operation only to registry related cache file", settings=&[
     AppSettings::ArgRequiredElseHelp,
@@ -32,14 +46,36 @@ pub(crate) struct Registry {
                 future compilation"
     )]
     light_cleanup: bool,
-    #[structopt(long = "old", short = "o", help = "Clean old registry cache crates")]
-    old: bool,
+    // short flag is separate from long option so it can be bundled with other flags
+    #[structopt(
+        short = "o",
+        help = "Clean old registry cache crates using latest mode"
+    )]
+    old_flag: bool,
+    #[structopt(
+        long = "old",
+        help = "Clean old registry cache crates classified by provided mode [default: latest]",
+        value_name = "mode",
+        possible_values = &["latest", "semver"],
+        min_values = 0,
+        require_equals = true
+    )]
+    #[allow(clippy::option_option)]
+    old: Option<Option<OldMode>>,
     #[structopt(
         long = "old-orphan",
         short = "z",
         help = "Clean registry crates which is both old and orphan"
     )]
     old_orphan: bool,
+    #[structopt(
+        long = "older-than",
+        help = "Clean registry crates which are not used for provided duration such as 30d, 12h or \
+                2w",
+        value_name = "duration",
+        parse(try_from_str = parse_duration)
+    )]
+    older_than: Option<Duration>,
     #[structopt(
         long = "orphan",
         short = "x",
@@ -57,10 +93,13 @@ pub(crate) struct Registry {
     #[structopt(
         long = "remove",
         short = "r",
-        help = "Remove provided crates from registry",
-        value_name = "crate"
+        help = "Remove provided crates from registry. Value can be crate name, full name or glob \
+                pattern optionally followed by @ and version requirement such as tokio@<1.20 or \
+                windows-*@0.3x",
+        value_name = "crate",
+        parse(try_from_str = CrateSelector::from_str)
     )]
-    remove: Option<Vec<String>>,
+    remove: Option<Vec<CrateSelector>>,
     #[structopt(
         long = "top",
         short = "t",
@@ -71,44 +110,103 @@ pub(crate) struct Registry {
 }
 
 impl Registry {
+    // return mode used for classifying crates as old if it is provided
+    pub(super) fn old_mode(&self) -> Option<OldMode> {
+        self.old.flatten()
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub(super) fn run(
         &self,
         dir_path: &DirPath,
         crate_list: &CrateList,
         crate_detail: &CrateDetail,
+        registry_source: &RegistrySource,
         registry_crates_location: &mut RegistryDir,
+        trash: Option<&Trash>,
+        journal: &Journal,
         directory_is_empty: bool,
+        json_format: bool,
     ) -> Result<()> {
         let dry_run = self.dry_run;
         if self.light_cleanup {
-            let light_cleanup_success =
-                light_cleanup_registry(dir_path.src_dir(), dir_path.index_dir(), dry_run);
+            journal.set_reason("light");
+            let light_cleanup_success = light_cleanup_registry(
+                registry_crates_location,
+                dir_path.src_dir(),
+                dir_path.index_dir(),
+                trash,
+                journal,
+                dry_run,
+            );
             if !light_cleanup_success {
                 println!("Failed to delete some folder during light cleanup");
             }
         }
         if let Some(number) = self.top {
-            top_crates_registry(crate_detail, number);
+            top_crates_registry(crate_detail, number, json_format);
         }
         if self.query {
-            let final_size = query_size_registry(dir_path, crate_list, crate_detail);
-            query_print("Total size", &convert_pretty(final_size));
+            let final_size = query_size_registry(
+                dir_path,
+                crate_list,
+                crate_detail,
+                registry_source,
+                json_format,
+            );
+            show_query("Total size", "total", None, final_size, json_format);
         }
 
-        if self.old {
-            let (sized_cleaned, total_crate_removed) =
-                old_clean_registry(registry_crates_location, crate_list, crate_detail, dry_run);
-            println!(
-                "{}",
-                format!(
+        if self.old_flag || self.old.is_some() {
+            journal.set_reason("old");
+            let (sized_cleaned, total_crate_removed) = old_clean_registry(
+                registry_crates_location,
+                crate_list,
+                crate_detail,
+                dry_run,
+                json_format,
+            );
+            show_removal_summary(
+                "old",
+                &format!(
                     "{} old crates removed which had occupied {:.3} MB",
-                    total_crate_removed, sized_cleaned
-                )
-                .color("blue")
+                    total_crate_removed,
+                    convert_mb(sized_cleaned)
+                ),
+                total_crate_removed,
+                sized_cleaned,
+                dry_run,
+                json_format,
+            );
+        }
+
+        if let Some(duration) = self.older_than {
+            journal.set_reason("older-than");
+            let (sized_cleaned, total_crate_removed) = older_than_clean_registry(
+                registry_crates_location,
+                crate_list,
+                crate_detail,
+                duration,
+                dry_run,
+                json_format,
+            );
+            show_removal_summary(
+                "older_than",
+                &format!(
+                    "{} crates not used for {} removed which had occupied {:.3} MB",
+                    total_crate_removed,
+                    convert_age(duration),
+                    convert_mb(sized_cleaned)
+                ),
+                total_crate_removed,
+                sized_cleaned,
+                dry_run,
+                json_format,
             );
         }
 
         if self.old_orphan {
+            journal.set_reason("old-orphan");
             if directory_is_empty {
                 let warning_text = "WARNING: You have not initialized any directory as rust \
                                     project directory. This command will clean all old crates \
@@ -136,19 +234,25 @@ impl Registry {
                 crate_list,
                 crate_detail,
                 dry_run,
+                json_format,
             );
 
-            println!(
-                "{}",
-                format!(
+            show_removal_summary(
+                "old_orphan",
+                &format!(
                     "{} crates which are both old and orphan crate removed which had {:.3} MB",
-                    total_crate_removed, sized_cleaned
-                )
-                .color("blue")
+                    total_crate_removed,
+                    convert_mb(sized_cleaned)
+                ),
+                total_crate_removed,
+                sized_cleaned,
+                dry_run,
+                json_format,
             );
         }
 
         if self.orphan {
+            journal.set_reason("orphan");
             if directory_is_empty {
                 let warning_text = "WARNING: You have not initialized any directory as rust \
                                     project directory. This command will clean all crates since \
@@ -171,39 +275,60 @@ impl Registry {
                     return Ok(());
                 }
             }
-            let (sized_cleaned, total_crate_removed) =
-                orphan_clean_registry(registry_crates_location, crate_list, crate_detail, dry_run);
+            let (sized_cleaned, total_crate_removed) = orphan_clean_registry(
+                registry_crates_location,
+                crate_list,
+                crate_detail,
+                dry_run,
+                json_format,
+            );
 
-            println!(
-                "{}",
-                format!(
+            show_removal_summary(
+                "orphan",
+                &format!(
                     "{} orphan crates removed which had occupied {:.3} MB",
-                    total_crate_removed, sized_cleaned
-                )
-                .color("blue")
+                    total_crate_removed,
+                    convert_mb(sized_cleaned)
+                ),
+                total_crate_removed,
+                sized_cleaned,
+                dry_run,
+                json_format,
             );
         }
 
         if self.all {
-            let (sized_cleaned, total_crate_removed) =
-                all_clean_registry(registry_crates_location, crate_list, crate_detail, dry_run);
-            println!(
-                "{}",
-                format!(
+            journal.set_reason("all");
+            let (sized_cleaned, total_crate_removed) = all_clean_registry(
+                registry_crates_location,
+                crate_list,
+                crate_detail,
+                dry_run,
+                json_format,
+            );
+            show_removal_summary(
+                "all",
+                &format!(
                     "Total size of  {} crates removed :- {:.3} MB",
-                    total_crate_removed, sized_cleaned
-                )
-                .color("blue")
+                    total_crate_removed,
+                    convert_mb(sized_cleaned)
+                ),
+                total_crate_removed,
+                sized_cleaned,
+                dry_run,
+                json_format,
             );
         }
 
         if let Some(crates) = &self.remove {
+            journal.set_reason("remove");
             remove_crates(
                 crates,
                 crate_list,
                 registry_crates_location,
                 crate_detail,
                 dry_run,
+                json_format,
             );
         }
 
@@ -213,64 +338,205 @@ impl Registry {
 
 // Perform light cleanup of registry and return if light clean was success or
 // not
-pub(super) fn light_cleanup_registry(src_dir: &Path, index_dir: &Path, dry_run: bool) -> bool {
+pub(super) fn light_cleanup_registry(
+    registry_crates_location: &RegistryDir,
+    src_dir: &Path,
+    index_dir: &Path,
+    trash: Option<&Trash>,
+    journal: &Journal,
+    dry_run: bool,
+) -> bool {
     let mut light_cleanup_success = true;
-    // delete src dir
+    if dry_run {
+        show_dry_run_removed(src_dir);
+        show_dry_run_removed(&index_dir.join("*").join(".cache"));
+    }
+    // delete src dir except source of crates present in keep list
     light_cleanup_success =
-        crate::utils::delete_folder(src_dir, dry_run).is_ok() && light_cleanup_success;
+        registry_crates_location.remove_all_src(dry_run).is_ok() && light_cleanup_success;
     // Delete out .cache folder also
-    light_cleanup_success =
-        crate::utils::delete_index_cache(index_dir, dry_run).is_ok() && light_cleanup_success;
+    light_cleanup_success = delete_index_cache(index_dir, trash, Some(journal), dry_run).is_ok()
+        && light_cleanup_success;
     light_cleanup_success
 }
 
 // Show top registry crates
-pub(super) fn top_crates_registry(crate_detail: &CrateDetail, number: usize) {
+pub(super) fn top_crates_registry(crate_detail: &CrateDetail, number: usize, json_format: bool) {
     show_top_number_crates(
         crate_detail.registry_crates_archive(),
         "registry_archive",
         number,
+        json_format,
     );
     show_top_number_crates(
         crate_detail.registry_crates_source(),
         "registry_source",
         number,
+        json_format,
     );
 }
 
-// Query size of registry
+// Query size of registry. If registry filter is provided only matching
+// registry folders are queried
 pub(super) fn query_size_registry(
     dir_path: &DirPath,
     crate_list: &CrateList,
     crate_detail: &CrateDetail,
+    registry_source: &RegistrySource,
+    json_format: bool,
 ) -> u64 {
-    let registry_dir_size = get_size(dir_path.registry_dir()).unwrap_or(0);
-    query_print(
+    let is_filtered = registry_source.filter().is_some();
+    let registry_dirs = registry_source
+        .list_registry_dir(dir_path)
+        .unwrap_or_default();
+    let folder_size = |folder: &Path| -> u64 {
+        if is_filtered {
+            registry_dirs
+                .iter()
+                .map(|registry_dir| get_size(&folder.join(registry_dir)).unwrap_or(0_u64))
+                .sum()
+        } else {
+            get_size(folder).unwrap_or(0_u64)
+        }
+    };
+    let cache_dir_size = folder_size(dir_path.cache_dir());
+    let index_dir_size = folder_size(dir_path.index_dir());
+    let src_dir_size = folder_size(dir_path.src_dir());
+    let registry_dir_size = if is_filtered {
+        cache_dir_size + index_dir_size + src_dir_size
+    } else {
+        get_size(dir_path.registry_dir()).unwrap_or(0)
+    };
+    show_query(
         &format!(
             "Total size of {} .cargo/registry crates:",
             crate_list.installed_registry().len()
         ),
-        &convert_pretty(registry_dir_size),
+        "registry",
+        Some(crate_list.installed_registry().len()),
+        registry_dir_size,
+        json_format,
     );
-    query_print(
+    show_query(
         &format!(
             "   \u{251c} Size of {} .cargo/registry/cache folder",
             crate_detail.registry_crates_archive().len()
         ),
-        &convert_pretty(get_size(dir_path.cache_dir()).unwrap_or(0_u64)),
+        "registry/cache",
+        Some(crate_detail.registry_crates_archive().len()),
+        cache_dir_size,
+        json_format,
     );
-    query_print(
+    show_query(
         "   \u{251c} Size of .cargo/registry/index folder",
-        &convert_pretty(get_size(dir_path.index_dir()).unwrap_or(0_u64)),
+        "registry/index",
+        None,
+        index_dir_size,
+        json_format,
+    );
+    let is_queried = |index: &PathBuf| {
+        index
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| registry_dirs.iter().any(|dir| dir == name))
+    };
+    let (mut git_index, mut sparse_index) =
+        list_registry_index(dir_path.index_dir()).unwrap_or_default();
+    git_index.retain(is_queried);
+    sparse_index.retain(is_queried);
+    show_query(
+        &format!(
+            "   \u{2502}  \u{251c} Size of {} git registry index",
+            git_index.len()
+        ),
+        "registry/index/git",
+        Some(git_index.len()),
+        git_index
+            .iter()
+            .map(|index| get_size(index).unwrap_or(0_u64))
+            .sum(),
+        json_format,
+    );
+    show_query(
+        &format!(
+            "   \u{2502}  \u{2514} Size of {} sparse registry index",
+            sparse_index.len()
+        ),
+        "registry/index/sparse",
+        Some(sparse_index.len()),
+        sparse_index
+            .iter()
+            .map(|index| get_size(index).unwrap_or(0_u64))
+            .sum(),
+        json_format,
     );
-    query_print(
+    show_query(
         &format!(
             "   \u{2514} Size of {} .cargo/registry/src folder",
             crate_detail.registry_crates_source().len()
         ),
-        &convert_pretty(get_size(dir_path.src_dir()).unwrap_or(0_u64)),
+        "registry/src",
+        Some(crate_detail.registry_crates_source().len()),
+        src_dir_size,
+        json_format,
     );
-    print_dash(crate::utils::query_full_width());
+    if !json_format {
+        print_dash(query_full_width());
+    }
+    // show size of each registry separately
+    for registry_dir in &registry_dirs {
+        let cache_size = get_size(&dir_path.cache_dir().join(registry_dir)).unwrap_or(0_u64);
+        let index_size = get_size(&dir_path.index_dir().join(registry_dir)).unwrap_or(0_u64);
+        let src_size = get_size(&dir_path.src_dir().join(registry_dir)).unwrap_or(0_u64);
+        let crate_count = crate_list
+            .installed_registry()
+            .iter()
+            .filter(|crate_name| {
+                crate_detail
+                    .find_registries(crate_name)
+                    .contains(registry_dir)
+            })
+            .count();
+        show_registry_query(
+            &format!(
+                "Size of {} crates of {}:",
+                crate_count,
+                registry_source.display_name(registry_dir)
+            ),
+            "registry",
+            registry_dir,
+            Some(crate_count),
+            cache_size + index_size + src_size,
+            json_format,
+        );
+        show_registry_query(
+            "   \u{251c} Size of cache folder",
+            "registry/cache",
+            registry_dir,
+            None,
+            cache_size,
+            json_format,
+        );
+        show_registry_query(
+            "   \u{251c} Size of index folder",
+            "registry/index",
+            registry_dir,
+            None,
+            index_size,
+            json_format,
+        );
+        show_registry_query(
+            "   \u{2514} Size of src folder",
+            "registry/src",
+            registry_dir,
+            None,
+            src_size,
+            json_format,
+        );
+    }
+    if !json_format && !registry_dirs.is_empty() {
+        print_dash(query_full_width());
+    }
     registry_dir_size
 }
 
@@ -280,14 +546,14 @@ pub(super) fn old_clean_registry(
     crate_list: &CrateList,
     crate_detail: &CrateDetail,
     dry_run: bool,
-) -> (f64, usize) {
-    (
-        registry_crates_location.remove_crate_list(
-            crate_detail,
-            crate_list.old_registry(),
-            dry_run,
-        ),
-        crate_list.old_registry().len(),
+    json_format: bool,
+) -> (u64, usize) {
+    remove_crate_list(
+        registry_crates_location,
+        crate_detail,
+        crate_list.old_registry(),
+        dry_run,
+        json_format,
     )
 }
 
@@ -297,14 +563,34 @@ pub(super) fn old_orphan_clean_registry(
     crate_list: &CrateList,
     crate_detail: &CrateDetail,
     dry_run: bool,
-) -> (f64, usize) {
-    (
-        registry_crates_location.remove_crate_list(
-            crate_detail,
-            &crate_list.list_old_orphan_registry(),
-            dry_run,
-        ),
-        crate_list.list_old_orphan_registry().len(),
+    json_format: bool,
+) -> (u64, usize) {
+    remove_orphan_crate_list(
+        registry_crates_location,
+        crate_list,
+        crate_detail,
+        &crate_list.list_old_orphan_registry(),
+        dry_run,
+        json_format,
+    )
+}
+
+// perform older than clean on registry crates
+pub(super) fn older_than_clean_registry(
+    registry_crates_location: &mut RegistryDir,
+    crate_list: &CrateList,
+    crate_detail: &CrateDetail,
+    duration: Duration,
+    dry_run: bool,
+    json_format: bool,
+) -> (u64, usize) {
+    let older_than_registry = crate_list.list_older_than_registry(crate_detail, duration);
+    remove_crate_list(
+        registry_crates_location,
+        crate_detail,
+        &older_than_registry,
+        dry_run,
+        json_format,
     )
 }
 
@@ -314,14 +600,15 @@ pub(super) fn orphan_clean_registry(
     crate_list: &CrateList,
     crate_detail: &CrateDetail,
     dry_run: bool,
-) -> (f64, usize) {
-    (
-        registry_crates_location.remove_crate_list(
-            crate_detail,
-            crate_list.orphan_registry(),
-            dry_run,
-        ),
-        crate_list.orphan_registry().len(),
+    json_format: bool,
+) -> (u64, usize) {
+    remove_orphan_crate_list(
+        registry_crates_location,
+        crate_list,
+        crate_detail,
+        crate_list.orphan_registry(),
+        dry_run,
+        json_format,
     )
 }
 
@@ -331,34 +618,143 @@ pub(super) fn all_clean_registry(
     crate_list: &CrateList,
     crate_detail: &CrateDetail,
     dry_run: bool,
-) -> (f64, usize) {
-    (
-        registry_crates_location.remove_crate_list(
-            crate_detail,
-            crate_list.installed_registry(),
-            dry_run,
-        ),
-        crate_list.installed_registry().len(),
+    json_format: bool,
+) -> (u64, usize) {
+    remove_crate_list(
+        registry_crates_location,
+        crate_detail,
+        crate_list.installed_registry(),
+        dry_run,
+        json_format,
     )
 }
 
-// Remove certain registry crates
+// Remove list of crates and return size cleaned along with number of crates
+// removed. Crates present in keep list are not removed
+fn remove_crate_list(
+    registry_crates_location: &mut RegistryDir,
+    crate_detail: &CrateDetail,
+    list: &[String],
+    dry_run: bool,
+    json_format: bool,
+) -> (u64, usize) {
+    let mut size_cleaned = 0;
+    let mut total_crate_removed = 0;
+    let progress = ProgressBar::new();
+    progress.start("Removing registry crates", list.len());
+    for crate_name in list {
+        if registry_crates_location.is_kept(crate_name) {
+            progress.suspend(|| show_kept_crate(crate_name, "registry", json_format));
+            progress.advance(crate_name, 0);
+            continue;
+        }
+        let result = registry_crates_location.remove_crate(crate_name, dry_run);
+        progress.suspend(|| {
+            show_removal_result(crate_name, "registry", &result, dry_run, json_format);
+        });
+        // crate which failed to be removed is not counted
+        if result.is_err() {
+            progress.advance(crate_name, 0);
+            continue;
+        }
+        let crate_bytes = crate_detail.find_bytes(crate_name, CrateKind::Registry);
+        size_cleaned += crate_bytes;
+        total_crate_removed += 1;
+        progress.advance(crate_name, crate_bytes);
+    }
+    progress.finish();
+    (size_cleaned, total_crate_removed)
+}
+
+// Remove orphan crates only from registry in which they are orphan and return
+// size cleaned along with number of crates removed. Crates present in keep list
+// are not removed
+fn remove_orphan_crate_list(
+    registry_crates_location: &mut RegistryDir,
+    crate_list: &CrateList,
+    crate_detail: &CrateDetail,
+    list: &[String],
+    dry_run: bool,
+    json_format: bool,
+) -> (u64, usize) {
+    let mut size_cleaned = 0;
+    let mut total_crate_removed = 0;
+    let progress = ProgressBar::new();
+    progress.start("Removing registry crates", list.len());
+    for crate_name in list {
+        if registry_crates_location.is_kept(crate_name) {
+            progress.suspend(|| show_kept_crate(crate_name, "registry", json_format));
+            progress.advance(crate_name, 0);
+            continue;
+        }
+        let orphan_registry_dirs = crate_list.orphan_registry_dirs(crate_name);
+        let result = registry_crates_location.remove_crate_from_registry(
+            crate_name,
+            orphan_registry_dirs,
+            dry_run,
+        );
+        progress.suspend(|| {
+            show_removal_result(crate_name, "registry", &result, dry_run, json_format);
+        });
+        // crate which failed to be removed is not counted
+        if result.is_err() {
+            progress.advance(crate_name, 0);
+            continue;
+        }
+        // crate is only removed from registry in which it is orphan so size of
+        // other registries is not cleaned
+        let crate_bytes = crate_detail.find_bytes_in_registries(crate_name, orphan_registry_dirs);
+        size_cleaned += crate_bytes;
+        total_crate_removed += 1;
+        progress.advance(crate_name, crate_bytes);
+    }
+    progress.finish();
+    (size_cleaned, total_crate_removed)
+}
+
+// Remove registry crates matching provided selectors. Dry run list crates
+// matched by each selector
 fn remove_crates(
-    crates: &[String],
+    selectors: &[CrateSelector],
     crate_list: &CrateList,
     registry_crates_location: &mut RegistryDir,
     crate_detail: &CrateDetail,
     dry_run: bool,
+    json_format: bool,
 ) {
-    let mut size_cleaned = 0.0;
+    let mut crates = Vec::new();
+    for selector in selectors {
+        let matched = selector.expand_registry(crate_list.installed_registry());
+        if dry_run {
+            show_selector_expansion(selector.value(), &matched, json_format);
+        }
+        for crate_name in matched {
+            if !crates.contains(&crate_name) {
+                crates.push(crate_name);
+            }
+        }
+    }
+    let mut size_cleaned = 0;
+    let mut total_crate_removed = 0;
     for crate_name in crates {
-        if crate_list.installed_registry().contains(crate_name) {
-            registry_crates_location.remove_crate(crate_name, dry_run);
-            size_cleaned += crate_detail.find_size_registry_all(crate_name);
+        if registry_crates_location.is_kept(crate_name) {
+            show_kept_crate(crate_name, "registry", json_format);
+            continue;
+        }
+        let result = registry_crates_location.remove_crate(crate_name, dry_run);
+        show_removal_result(crate_name, "registry", &result, dry_run, json_format);
+        // crate which failed to be removed is not counted
+        if result.is_ok() {
+            size_cleaned += crate_detail.find_bytes(crate_name, CrateKind::Registry);
+            total_crate_removed += 1;
         }
     }
-    println!(
-        "{}",
-        format!("Total size removed :- {:.3} MB", size_cleaned).color("blue")
+    show_removal_summary(
+        "remove",
+        &format!("Total size removed :- {:.3} MB", convert_mb(size_cleaned)),
+        total_crate_removed,
+        size_cleaned,
+        dry_run,
+        json_format,
     );
 }
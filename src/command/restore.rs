@@ -0,0 +1,54 @@
+use anyhow::Result;
+use structopt::{clap::AppSettings, StructOpt};
+
+use cargo_trim::Trash;
+
+use crate::output::show_restored_crate;
+
+#[derive(Debug, StructOpt)]
+#[structopt(about = "Restore crates moved to trash", settings=&[AppSettings::ArgRequiredElseHelp])]
+pub(crate) struct Restore {
+    #[structopt(
+        help = "Crate to restore from latest trash batch which contains crate",
+        value_name = "crate",
+        required_unless = "last"
+    )]
+    crate_name: Option<String>,
+    #[structopt(
+        long = "dry-run",
+        short = "n",
+        help = "Run command in dry run mode to see what would be done"
+    )]
+    dry_run: bool,
+    #[structopt(
+        long = "last",
+        help = "Restore all crates removed by last run which used trash",
+        conflicts_with = "crate-name"
+    )]
+    last: bool,
+}
+
+impl Restore {
+    pub(super) fn run(&self, trash: &Trash, json_format: bool) -> Result<()> {
+        // last batch is restored when crate name is not provided
+        let crate_name = if self.last {
+            None
+        } else {
+            self.crate_name.as_deref()
+        };
+        let (restored, skipped) = trash.restore(crate_name, self.dry_run)?;
+        if restored.is_empty() && skipped.is_empty() && !json_format {
+            match crate_name {
+                Some(crate_name) => println!("{crate_name:?} is not present in trash"),
+                None => println!("Trash is empty"),
+            }
+        }
+        for entry in &restored {
+            show_restored_crate(entry, true, self.dry_run, json_format);
+        }
+        for entry in &skipped {
+            show_restored_crate(entry, false, self.dry_run, json_format);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,215 @@
+use std::io::{self, IsTerminal};
+
+use anyhow::{bail, Context, Result};
+use dialoguer::{theme::ColorfulTheme, Confirm, Input, MultiSelect, Select as SelectPrompt};
+use structopt::StructOpt;
+
+use cargo_trim::{CrateDetail, CrateKind, CrateList, GitDir, RegistryDir};
+
+use crate::output::{
+    convert_age, convert_mb, show_removal_error, show_removal_summary, show_removed_crate,
+};
+
+// keys which can be used to sort crates
+const SORT_KEYS: [&str; 3] = ["size", "name", "age"];
+
+#[derive(Debug, StructOpt)]
+#[structopt(about = "Interactively select crates to remove")]
+pub(crate) struct Select {
+    #[structopt(
+        long = "dry-run",
+        short = "n",
+        help = "Run command in dry run mode to see what would be done"
+    )]
+    dry_run: bool,
+    #[structopt(
+        long = "filter",
+        short = "f",
+        help = "Only show crates whose name contains provided value. Prompted if not provided",
+        value_name = "pattern"
+    )]
+    filter: Option<String>,
+    #[structopt(
+        long = "sort",
+        short = "s",
+        help = "Sort crates by provided key. Prompted if not provided",
+        possible_values = &SORT_KEYS,
+        value_name = "key"
+    )]
+    sort: Option<String>,
+}
+
+// crate which can be selected along with its kind
+struct SelectEntry<'a> {
+    name: &'a str,
+    kind: CrateKind,
+    size: f64,
+    label: String,
+}
+
+impl Select {
+    pub(super) fn run(
+        &self,
+        crate_list: &CrateList,
+        crate_detail: &CrateDetail,
+        registry_crates_location: &mut RegistryDir,
+        git_crates_location: &GitDir,
+        json_format: bool,
+    ) -> Result<()> {
+        if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+            bail!("select subcommand requires interactive terminal");
+        }
+        let theme = ColorfulTheme::default();
+        let filter = match &self.filter {
+            Some(filter) => filter.clone(),
+            None => Input::<String>::with_theme(&theme)
+                .with_prompt("Filter crates by name (leave empty to show all crates)")
+                .allow_empty(true)
+                .interact_text()
+                .context("failed to read filter")?,
+        };
+        let sort = if let Some(sort) = &self.sort {
+            sort.clone()
+        } else {
+            let position = SelectPrompt::with_theme(&theme)
+                .with_prompt("Sort crates by")
+                .items(&SORT_KEYS)
+                .default(0)
+                .interact()
+                .context("failed to read sort key")?;
+            SORT_KEYS[position].to_string()
+        };
+
+        let mut entries = list_entries(crate_list, crate_detail, &filter, &sort);
+        // crates present in keep list cannot be selected
+        entries.retain(|entry| {
+            if entry.kind == CrateKind::Registry {
+                !registry_crates_location.is_kept(entry.name)
+            } else {
+                !git_crates_location.is_kept(entry.name)
+            }
+        });
+        if entries.is_empty() {
+            println!("No crates matched filter {filter:?}");
+            return Ok(());
+        }
+        let labels = entries
+            .iter()
+            .map(|entry| entry.label.as_str())
+            .collect::<Vec<_>>();
+        let selected = MultiSelect::with_theme(&theme)
+            .with_prompt("Select crates to remove (space to select, enter to continue)")
+            .items(&labels)
+            .max_length(20)
+            .interact()
+            .context("failed to read selected crates")?;
+        if selected.is_empty() {
+            println!("No crates selected");
+            return Ok(());
+        }
+        let selected_size = selected
+            .iter()
+            .fold(0.0, |size, position| size + entries[*position].size);
+        let confirm = Confirm::with_theme(&theme)
+            .with_prompt(format!(
+                "Remove {} crates of size {:.3} MB?",
+                selected.len(),
+                selected_size
+            ))
+            .default(false)
+            .interact()
+            .context("failed to read confirmation")?;
+        if !confirm {
+            println!("No crates removed");
+            return Ok(());
+        }
+
+        let mut size_cleaned = 0;
+        let mut total_crate_removed = 0;
+        for position in &selected {
+            let entry = &entries[*position];
+            let result = if entry.kind == CrateKind::Registry {
+                registry_crates_location.remove_crate(entry.name, self.dry_run)
+            } else {
+                git_crates_location.remove_crate(entry.name, self.dry_run)
+            };
+            show_removed_crate(
+                entry.name,
+                entry.kind.as_str(),
+                result.is_ok(),
+                self.dry_run,
+                json_format,
+            );
+            // only crates which are removed successfully are counted
+            match result {
+                Ok(()) => {
+                    size_cleaned += crate_detail.find_bytes(entry.name, entry.kind);
+                    total_crate_removed += 1;
+                }
+                Err(e) => show_removal_error(&e),
+            }
+        }
+        show_removal_summary(
+            "select",
+            &format!("Total size removed :- {:.3} MB", convert_mb(size_cleaned)),
+            total_crate_removed,
+            size_cleaned,
+            self.dry_run,
+            json_format,
+        );
+        Ok(())
+    }
+}
+
+// list registry and git crates matching filter sorted by provided key. Crates are
+// sorted from largest size or oldest age
+fn list_entries<'a>(
+    crate_list: &'a CrateList,
+    crate_detail: &CrateDetail,
+    filter: &str,
+    sort: &str,
+) -> Vec<SelectEntry<'a>> {
+    let registry_crates = crate_list
+        .installed_registry()
+        .iter()
+        .map(|crate_name| (crate_name, CrateKind::Registry));
+    let git_crates = crate_list
+        .installed_git()
+        .iter()
+        .map(|crate_name| (crate_name, CrateKind::Git));
+    let mut entries = registry_crates
+        .chain(git_crates)
+        .filter(|(crate_name, _)| crate_name.contains(filter))
+        .map(|(crate_name, kind)| {
+            let size = crate_detail.find(crate_name, kind);
+            let age = crate_detail
+                .find_age(crate_name, kind)
+                .map_or_else(|| "-".to_string(), convert_age);
+            let label = format!(
+                "{:40} {:8} {:>10.3} MB {:>12} {}",
+                crate_name,
+                kind.as_str(),
+                size,
+                age,
+                crate_list.classification(crate_name, kind).join(",")
+            );
+            SelectEntry {
+                name: crate_name,
+                kind,
+                size,
+                label,
+            }
+        })
+        .collect::<Vec<_>>();
+    match sort {
+        "size" => entries.sort_by(|a, b| b.size.total_cmp(&a.size).then(a.name.cmp(b.name))),
+        "age" => entries.sort_by_key(|entry| {
+            (
+                crate_detail.find_last_used(entry.name, entry.kind),
+                entry.name,
+            )
+        }),
+        _ => entries.sort_by(|a, b| a.name.cmp(b.name).then(a.kind.cmp(&b.kind))),
+    }
+    entries
+}
@@ -1,7 +1,12 @@
 use anyhow::Result;
 use structopt::{clap::AppSettings, StructOpt};
 
-use crate::config_file::ConfigFile;
+use cargo_trim::ConfigFile;
+
+use crate::output::{show_config_added, show_config_set, show_config_set_value};
+
+use super::parse::parse_keep_versions;
+
 #[derive(Debug, StructOpt)]
 #[structopt(about = "Set config file values", settings=&[AppSettings::ArgRequiredElseHelp])]
 pub(crate) struct Set {
@@ -25,6 +30,21 @@ pub(crate) struct Set {
         value_name = "file"
     )]
     ignore: Option<Vec<String>>,
+    #[structopt(
+        long = "keep",
+        short = "k",
+        help = "Add crate name, name-version or glob pattern such as *-sys to keep list in \
+                configuration file. Crates present in keep list are never cleaned",
+        value_name = "crate"
+    )]
+    keep: Option<Vec<String>>,
+    #[structopt(
+        long = "keep-versions",
+        help = "Set number of newest versions per crate which are not classified as old",
+        value_name = "number",
+        parse(try_from_str = parse_keep_versions)
+    )]
+    keep_versions: Option<usize>,
     #[structopt(long = "scan-hidden-folder", help = "Set scan hidden folder as true")]
     scan_hidden_folder: bool,
     #[structopt(long = "scan-target-folder", help = "Set scan hidden folder as true")]
@@ -39,18 +59,32 @@ impl Set {
                 let path_separator = std::path::MAIN_SEPARATOR;
                 let path = directory.trim_end_matches(path_separator);
                 config_file.add_directory(path, dry_run, true)?;
+                show_config_added(path, dry_run);
             }
         }
         if let Some(files) = &self.ignore {
             for file in files {
                 config_file.add_ignore_file_name(file, dry_run, true)?;
+                show_config_added(file, dry_run);
             }
         }
+        if let Some(crates) = &self.keep {
+            for crate_name in crates {
+                config_file.add_keep(crate_name, dry_run, true)?;
+                show_config_added(crate_name, dry_run);
+            }
+        }
+        if let Some(keep_versions) = self.keep_versions {
+            config_file.set_keep_versions(Some(keep_versions), dry_run, true)?;
+            show_config_set_value("keep_versions", keep_versions, dry_run);
+        }
         if self.scan_hidden_folder {
             config_file.set_scan_hidden_folder(true, dry_run, true)?;
+            show_config_set("scan_hidden_folder", true, dry_run);
         }
         if self.scan_target_folder {
             config_file.set_scan_target_folder(true, dry_run, true)?;
+            show_config_set("scan_target_folder", true, dry_run);
         }
 
         Ok(())
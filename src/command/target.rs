@@ -0,0 +1,355 @@
+use std::{path::PathBuf, time::Duration};
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+use structopt::StructOpt;
+
+use cargo_trim::{
+    utils::list_installed_toolchains, ConfigFile, DirPath, Journal, TargetDir, Trash,
+};
+
+use crate::output::{
+    convert_age, convert_pretty, print_dash, print_json, show_removal_summary, show_removed_crate,
+    TargetRecord,
+};
+
+use super::parse::parse_duration;
+
+#[derive(Debug, StructOpt)]
+#[structopt(about = "Query and clean build artifacts present in target directory of projects")]
+pub(crate) struct Target {
+    #[structopt(
+        long = "dry-run",
+        short = "n",
+        help = "Run command in dry run mode to see what would be done"
+    )]
+    dry_run: bool,
+    #[structopt(
+        long = "inactive",
+        help = "Remove whole target directory of projects which are not built for provided \
+                duration such as 30d, 12h or 2w",
+        value_name = "duration",
+        parse(try_from_str = parse_duration)
+    )]
+    inactive: Option<Duration>,
+    #[structopt(
+        long = "incremental",
+        help = "Remove incremental compilation cache of target directory. Provide all to remove \
+                whole cache or newest to only keep newest session of each crate",
+        possible_values = &["all", "newest"],
+        value_name = "mode"
+    )]
+    incremental: Option<String>,
+    #[structopt(
+        long = "older-than",
+        help = "Remove build artifacts of target directory which are not used for provided \
+                duration such as 30d, 12h or 2w",
+        value_name = "duration",
+        parse(try_from_str = parse_duration)
+    )]
+    older_than: Option<Duration>,
+    #[structopt(
+        long = "stale-toolchains",
+        help = "Remove build artifacts of target directory which are built by toolchain which is \
+                no longer installed in rustup home"
+    )]
+    stale_toolchains: bool,
+}
+
+impl Target {
+    pub(super) fn run(
+        &self,
+        dir_path: &DirPath,
+        config_file: &ConfigFile,
+        trash: Option<&Trash>,
+        journal: &Journal,
+        json_format: bool,
+    ) -> Result<()> {
+        let dry_run = self.dry_run;
+        let target_dirs = TargetDir::list(dir_path, config_file)?;
+        if self.inactive.is_none()
+            && self.incremental.is_none()
+            && self.older_than.is_none()
+            && !self.stale_toolchains
+        {
+            show_target_dirs(&target_dirs, json_format);
+            // print warning if directory config is empty
+            if config_file.directory().is_empty() && !json_format {
+                let warning_text = "WARNING: You have not initialized any directory as rust \
+                                    project directory. Run command 'cargo trim init' to \
+                                    initialize current directory as rust project directory or \
+                                    pass cargo trim -d <directory> for setting rust project \
+                                    directory";
+                println!("{}", warning_text.color("yellow"));
+            }
+            return Ok(());
+        }
+
+        let mut removed_target_dirs = Vec::new();
+        let mut removed_artifacts = Vec::new();
+        let mut total_removed = 0;
+        let mut size_cleaned = 0;
+        if let Some(duration) = self.inactive {
+            journal.set_reason("inactive-target");
+            for target_dir in target_dirs.iter().filter(|dir| dir.is_inactive(duration)) {
+                let is_success = target_dir.remove(trash, Some(journal), dry_run).is_ok();
+                show_removed_crate(
+                    &target_dir.path().to_string_lossy(),
+                    "target",
+                    is_success,
+                    dry_run,
+                    json_format,
+                );
+                if is_success {
+                    removed_target_dirs.push(target_dir.path());
+                    total_removed += 1;
+                    size_cleaned += target_dir.size();
+                }
+            }
+        }
+        if let Some(mode) = &self.incremental {
+            journal.set_reason("incremental");
+            let remaining_target_dirs = target_dirs
+                .iter()
+                .filter(|target_dir| !removed_target_dirs.contains(&target_dir.path()))
+                .collect::<Vec<_>>();
+            let (removed, size, removed_paths) = remove_incremental(
+                &remaining_target_dirs,
+                mode,
+                trash,
+                journal,
+                dry_run,
+                json_format,
+            );
+            total_removed += removed;
+            size_cleaned += size;
+            removed_artifacts.extend(removed_paths);
+        }
+        if self.stale_toolchains {
+            let installed_toolchains = list_installed_toolchains(dir_path.rustup_dir())?;
+            // without any installed toolchain all artifacts would be treated as stale
+            if installed_toolchains.is_empty() {
+                bail!(
+                    "no installed toolchain found in rustup home {:?}",
+                    dir_path.rustup_dir()
+                );
+            }
+            journal.set_reason("stale-toolchain");
+            for target_dir in &target_dirs {
+                if removed_target_dirs.contains(&target_dir.path()) {
+                    continue;
+                }
+                for artifact in target_dir.list_stale_toolchain_artifacts(&installed_toolchains)? {
+                    let is_success = artifact.remove(trash, Some(journal), dry_run).is_ok();
+                    show_removed_crate(
+                        &artifact.path().to_string_lossy(),
+                        "target",
+                        is_success,
+                        dry_run,
+                        json_format,
+                    );
+                    if is_success {
+                        total_removed += 1;
+                        size_cleaned += artifact.size();
+                        removed_artifacts.push(artifact.path().to_path_buf());
+                    }
+                }
+            }
+        }
+        if let Some(duration) = self.older_than {
+            journal.set_reason("stale-artifact");
+            for target_dir in &target_dirs {
+                // artifacts of already removed target directory are not listed again
+                if removed_target_dirs.contains(&target_dir.path()) {
+                    continue;
+                }
+                for artifact in target_dir.list_artifacts_older_than(duration)? {
+                    // artifact can already be removed in dry run as stale toolchain artifact
+                    // or incremental cache
+                    if removed_artifacts
+                        .iter()
+                        .any(|path| artifact.path().starts_with(path))
+                    {
+                        continue;
+                    }
+                    let is_success = artifact.remove(trash, Some(journal), dry_run).is_ok();
+                    show_removed_crate(
+                        &artifact.path().to_string_lossy(),
+                        "target",
+                        is_success,
+                        dry_run,
+                        json_format,
+                    );
+                    if is_success {
+                        total_removed += 1;
+                        size_cleaned += artifact.size();
+                    }
+                }
+            }
+        }
+        show_removal_summary(
+            "target",
+            &format!(
+                "{} target directories and artifacts removed which had occupied {}",
+                total_removed,
+                convert_pretty(size_cleaned)
+            ),
+            total_removed,
+            size_cleaned,
+            dry_run,
+            json_format,
+        );
+        Ok(())
+    }
+}
+
+// remove incremental compilation cache of target directories and show summary
+// of removal
+pub(super) fn incremental_clean(
+    target_dirs: &[TargetDir],
+    mode: &str,
+    trash: Option<&Trash>,
+    journal: &Journal,
+    dry_run: bool,
+    json_format: bool,
+) {
+    let target_dirs = target_dirs.iter().collect::<Vec<_>>();
+    let (total_removed, size_cleaned, _) =
+        remove_incremental(&target_dirs, mode, trash, journal, dry_run, json_format);
+    show_removal_summary(
+        "incremental",
+        &format!(
+            "{} incremental caches removed which had occupied {}",
+            total_removed,
+            convert_pretty(size_cleaned)
+        ),
+        total_removed,
+        size_cleaned,
+        dry_run,
+        json_format,
+    );
+}
+
+// remove incremental compilation cache of target directories. All mode removes
+// whole incremental folder of every profile whereas newest mode only keeps newest
+// session of each crate. Return number of removed folders, their size and paths
+// path is debug formatted so it is printed within quotes
+#[allow(clippy::unnecessary_debug_formatting)]
+fn remove_incremental(
+    target_dirs: &[&TargetDir],
+    mode: &str,
+    trash: Option<&Trash>,
+    journal: &Journal,
+    dry_run: bool,
+    json_format: bool,
+) -> (usize, u64, Vec<PathBuf>) {
+    let mut total_removed = 0;
+    let mut size_cleaned = 0;
+    let mut removed_paths = Vec::new();
+    for target_dir in target_dirs {
+        let incremental = if mode == "newest" {
+            target_dir.list_old_incremental_sessions()
+        } else {
+            target_dir.list_incremental_dirs()
+        };
+        let Ok(incremental) = incremental else {
+            println!(
+                "Failed to read incremental cache of {:?}",
+                target_dir.path()
+            );
+            continue;
+        };
+        for artifact in incremental {
+            let is_success = artifact.remove(trash, Some(journal), dry_run).is_ok();
+            show_removed_crate(
+                &artifact.path().to_string_lossy(),
+                "incremental",
+                is_success,
+                dry_run,
+                json_format,
+            );
+            if is_success {
+                total_removed += 1;
+                size_cleaned += artifact.size();
+                removed_paths.push(artifact.path().to_path_buf());
+            }
+        }
+    }
+    (total_removed, size_cleaned, removed_paths)
+}
+
+// show target directories along with their size and age
+fn show_target_dirs(target_dirs: &[TargetDir], json_format: bool) {
+    if json_format {
+        for target_dir in target_dirs {
+            print_json(&TargetRecord::new(target_dir));
+        }
+        return;
+    }
+    let first_path_len = 60;
+    let second_path_len = 10;
+    let third_path_len = 12;
+    let second_path_precision = 3;
+    let dash_len = first_path_len + second_path_len + third_path_len + 4;
+    print_dash(dash_len);
+    println!(
+        "|{:^first_width$}|{:^second_width$}|{:^third_width$}|",
+        "TARGET DIRECTORY".bold(),
+        "SIZE(MB)".bold(),
+        "AGE".bold(),
+        first_width = first_path_len,
+        second_width = second_path_len,
+        third_width = third_path_len
+    );
+    print_dash(dash_len);
+    for target_dir in target_dirs {
+        let age = target_dir.last_used().map_or_else(
+            || "-".to_string(),
+            |last_used| convert_age(last_used.elapsed().unwrap_or_default()),
+        );
+        #[allow(clippy::cast_precision_loss)]
+        let size = target_dir.size() as f64 / 1000_f64.powi(2);
+        println!(
+            "|{:^first_width$}|{:^second_width$.precision$}|{:^third_width$}|",
+            target_dir.path().display(),
+            size,
+            age,
+            first_width = first_path_len,
+            second_width = second_path_len,
+            third_width = third_path_len,
+            precision = second_path_precision
+        );
+        for project in target_dir.projects() {
+            // target directory present inside project is not shown again
+            if target_dir.path().parent() != Some(project.as_path()) {
+                println!(
+                    "|{:^width$}|",
+                    format!("used by {}", project.display()).color("cyan"),
+                    width = dash_len - 2
+                );
+            }
+        }
+    }
+    if target_dirs.is_empty() {
+        println!(
+            "|{:^first_width$}|{:^second_width$}|{:^third_width$}|",
+            "NONE".color("red"),
+            "0.000".color("red"),
+            "-".color("red"),
+            first_width = first_path_len,
+            second_width = second_path_len,
+            third_width = third_path_len
+        );
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let total_size = target_dirs.iter().map(TargetDir::size).sum::<u64>() as f64 / 1000_f64.powi(2);
+    print_dash(dash_len);
+    println!(
+        "|{:^first_width$}|{:^second_width$}|",
+        format!("Total no of target directories:- {}", target_dirs.len()).color("blue"),
+        format!("{total_size:.3}").color("blue"),
+        first_width = first_path_len,
+        second_width = second_path_len + third_path_len + 1,
+    );
+    print_dash(dash_len);
+}
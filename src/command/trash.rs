@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use structopt::{clap::AppSettings, StructOpt};
+
+use cargo_trim::Trash as TrashDir;
+
+use crate::output::{convert_pretty, show_removal_summary, show_trash_batch};
+
+use super::parse::parse_duration;
+
+#[derive(Debug, StructOpt)]
+#[structopt(about = "Manage crates moved to trash", settings=&[AppSettings::ArgRequiredElseHelp])]
+pub(crate) enum Trash {
+    #[structopt(about = "List batches of crates present in trash")]
+    List,
+    #[structopt(about = "Permanently remove batches of crates present in trash")]
+    Purge(Purge),
+}
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Purge {
+    #[structopt(
+        long = "dry-run",
+        short = "n",
+        help = "Run command in dry run mode to see what would be done"
+    )]
+    dry_run: bool,
+    #[structopt(
+        long = "older-than",
+        help = "Only remove batches which were moved to trash before provided duration such as \
+                30d, 12h or 2w",
+        value_name = "duration",
+        parse(try_from_str = parse_duration)
+    )]
+    older_than: Option<Duration>,
+}
+
+impl Trash {
+    pub(super) fn run(&self, trash: &TrashDir, json_format: bool) -> Result<()> {
+        match self {
+            Self::List => {
+                let batches = trash.list_batches()?;
+                if batches.is_empty() && !json_format {
+                    println!("Trash is empty");
+                }
+                for batch in &batches {
+                    show_trash_batch(batch, false, false, json_format);
+                }
+            }
+            Self::Purge(purge) => {
+                let batches = trash.purge(purge.older_than, purge.dry_run)?;
+                let mut crates_removed = 0;
+                let mut size_cleaned = 0;
+                for batch in &batches {
+                    show_trash_batch(batch, true, purge.dry_run, json_format);
+                    crates_removed += batch.entries().len();
+                    size_cleaned += batch.size();
+                }
+                show_removal_summary(
+                    "trash purge",
+                    &format!(
+                        "Total size removed from trash :- {}",
+                        convert_pretty(size_cleaned)
+                    ),
+                    crates_removed,
+                    size_cleaned,
+                    purge.dry_run,
+                    json_format,
+                );
+            }
+        }
+        Ok(())
+    }
+}
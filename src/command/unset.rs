@@ -1,7 +1,10 @@
 use anyhow::Result;
 use structopt::{clap::AppSettings, StructOpt};
 
-use crate::config_file::ConfigFile;
+use cargo_trim::ConfigFile;
+
+use crate::output::{show_config_removed, show_config_set, show_config_set_value};
+
 #[derive(Debug, StructOpt)]
 #[structopt(about = "Unset values from config file", settings=&[AppSettings::ArgRequiredElseHelp])]
 pub(crate) struct Unset {
@@ -24,6 +27,18 @@ pub(crate) struct Unset {
         value_name = "file"
     )]
     ignore: Option<Vec<String>>,
+    #[structopt(
+        long = "keep",
+        short = "k",
+        help = "Crate name or pattern to be removed from keep list in config file",
+        value_name = "crate"
+    )]
+    keep: Option<Vec<String>>,
+    #[structopt(
+        long = "keep-versions",
+        help = "Reset number of newest versions per crate which are not classified as old to 1"
+    )]
+    keep_versions: bool,
     #[structopt(long = "scan-hidden-folder", help = "Set scan hidden folder as false")]
     scan_hidden_folder: bool,
     #[structopt(long = "scan-target-folder", help = "Set scan hidden folder as false")]
@@ -38,18 +53,32 @@ impl Unset {
                 let path_separator = std::path::MAIN_SEPARATOR;
                 let path = directory.trim_end_matches(path_separator);
                 config_file.remove_directory(path, dry_run, true)?;
+                show_config_removed(path, dry_run);
             }
         }
         if let Some(files) = &self.ignore {
             for file in files {
                 config_file.remove_ignore_file_name(file, dry_run, true)?;
+                show_config_removed(file, dry_run);
+            }
+        }
+        if let Some(crates) = &self.keep {
+            for crate_name in crates {
+                config_file.remove_keep(crate_name, dry_run, true)?;
+                show_config_removed(crate_name, dry_run);
             }
         }
+        if self.keep_versions {
+            config_file.set_keep_versions(None, dry_run, true)?;
+            show_config_set_value("keep_versions", 1, dry_run);
+        }
         if self.scan_hidden_folder {
             config_file.set_scan_hidden_folder(false, dry_run, true)?;
+            show_config_set("scan_hidden_folder", false, dry_run);
         }
         if self.scan_target_folder {
             config_file.set_scan_target_folder(false, dry_run, true)?;
+            show_config_set("scan_target_folder", false, dry_run);
         }
 
         Ok(())
@@ -7,14 +7,13 @@ use std::{
 };
 
 use anyhow::{Context, Result};
-use colored::Colorize;
 use serde::{Deserialize, Serialize};
 
 use crate::list_crate::CargoTomlLocation;
 
-// Stores config file information
+/// Stores config file information
 #[derive(Serialize, Deserialize, Default)]
-pub(crate) struct ConfigFile {
+pub struct ConfigFile {
     #[serde(default)]
     directory: Vec<String>,
     #[serde(default)]
@@ -23,13 +22,17 @@ pub(crate) struct ConfigFile {
     scan_hidden_folder: bool,
     #[serde(default)]
     scan_target_folder: bool,
+    #[serde(default)]
+    keep: Vec<String>,
+    #[serde(default)]
+    keep_versions: Option<usize>,
     #[serde(skip)]
-    config_file: PathBuf,
+    path: PathBuf,
 }
 
 impl ConfigFile {
-    // Perform initial config file actions
-    pub(crate) fn init(config_file: &Path) -> Result<Self> {
+    /// Perform initial config file actions
+    pub fn init(config_file: &Path) -> Result<Self> {
         let mut buffer = String::new();
         let mut file = fs::File::open(config_file).context("failed to open config file")?;
         file.read_to_string(&mut buffer)
@@ -42,165 +45,191 @@ impl ConfigFile {
         }
         let mut deserialize_config: Self =
             toml::from_str(&buffer).context("failed to convert string to Config")?;
-        deserialize_config.config_file = config_file.to_path_buf();
+        deserialize_config.path = config_file.to_path_buf();
         Ok(deserialize_config)
     }
 
-    // return vector of directory value in config file
-    pub(crate) fn directory(&self) -> &Vec<String> {
+    /// return vector of directory value in config file
+    pub fn directory(&self) -> &Vec<String> {
         &self.directory
     }
 
-    // return vector of ignore file name value in config file
-    pub(crate) fn ignore_file_name(&self) -> &Vec<String> {
+    /// return vector of ignore file name value in config file
+    pub fn ignore_file_name(&self) -> &Vec<String> {
         &self.ignore_file_name
     }
 
-    // scan hidden folder
-    pub(crate) fn scan_hidden_folder(&self) -> bool {
+    /// return vector of crate name, full name or glob pattern of crates which are
+    /// never cleaned
+    pub fn keep(&self) -> &Vec<String> {
+        &self.keep
+    }
+
+    /// return number of newest versions per crate which are not classified as old
+    pub fn keep_versions(&self) -> usize {
+        self.keep_versions.unwrap_or(1)
+    }
+
+    /// scan hidden folder
+    pub fn scan_hidden_folder(&self) -> bool {
         self.scan_hidden_folder
     }
 
-    // scan target folder
-    pub(crate) fn scan_target_folder(&self) -> bool {
+    /// scan target folder
+    pub fn scan_target_folder(&self) -> bool {
         self.scan_target_folder
     }
 
-    // Set scan hidden folder to value
-    pub(crate) fn set_scan_hidden_folder(
+    /// Set scan hidden folder to value
+    pub fn set_scan_hidden_folder(
         &mut self,
         value: bool,
         dry_run: bool,
         save_to_config_file: bool,
     ) -> Result<()> {
-        if dry_run {
-            println!(
-                "{} Set scan_hidden_folder to {:?}",
-                "Dry run:".color("yellow"),
-                value
-            );
-        } else {
+        if !dry_run {
             self.scan_hidden_folder = value;
             if save_to_config_file {
                 self.save_to_config_file()?;
             }
-            println!("Set scan_hidden_folder to {:?}", value);
         }
         Ok(())
     }
 
-    // Set scan target folder to value
-    pub(crate) fn set_scan_target_folder(
+    /// Set scan target folder to value
+    pub fn set_scan_target_folder(
         &mut self,
         value: bool,
         dry_run: bool,
         save_to_config_file: bool,
     ) -> Result<()> {
-        if dry_run {
-            println!(
-                "{} Set scan_target_folder to {:?}",
-                "Dry run:".color("yellow"),
-                value
-            );
-        } else {
+        if !dry_run {
             self.scan_target_folder = value;
             if save_to_config_file {
                 self.save_to_config_file()?;
             }
-            println!("Set scan_target_folder to {:?}", value);
         }
         Ok(())
     }
 
-    // add directory
-    pub(crate) fn add_directory(
+    /// Set number of newest versions per crate which are not classified as old. If
+    /// value is not provided default of single latest version is used
+    pub fn set_keep_versions(
+        &mut self,
+        value: Option<usize>,
+        dry_run: bool,
+        save_to_config_file: bool,
+    ) -> Result<()> {
+        if !dry_run {
+            self.keep_versions = value;
+            if save_to_config_file {
+                self.save_to_config_file()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// add directory
+    pub fn add_directory(
         &mut self,
         path: &str,
         dry_run: bool,
         save_to_config_file: bool,
     ) -> Result<()> {
-        if dry_run {
-            println!("{} Added {:?}", "Dry run:".color("yellow"), path);
-        } else {
+        if !dry_run {
             self.directory.push(path.to_string());
             if save_to_config_file {
                 self.save_to_config_file()?;
             }
-            println!("{} {:?}", "Added".color("red"), path);
         }
         Ok(())
     }
 
-    // add ignore file name
-    pub(crate) fn add_ignore_file_name(
+    /// add ignore file name
+    pub fn add_ignore_file_name(
         &mut self,
         file_name: &str,
         dry_run: bool,
         save_to_config_file: bool,
     ) -> Result<()> {
-        if dry_run {
-            println!("{} Added {:?}", "Dry run:".color("yellow"), file_name);
-        } else {
+        if !dry_run {
             self.ignore_file_name.push(file_name.to_string());
             if save_to_config_file {
                 self.save_to_config_file()?;
             }
-            println!("{} {:?}", "Added".color("red"), file_name);
         }
         Ok(())
     }
 
-    // remove directory
-    pub(crate) fn remove_directory(
+    /// add crate to keep list
+    pub fn add_keep(
+        &mut self,
+        value: &str,
+        dry_run: bool,
+        save_to_config_file: bool,
+    ) -> Result<()> {
+        if !dry_run {
+            if !self.keep.iter().any(|keep| keep == value) {
+                self.keep.push(value.to_string());
+            }
+            if save_to_config_file {
+                self.save_to_config_file()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// remove directory
+    pub fn remove_directory(
         &mut self,
         path: &str,
         dry_run: bool,
         save_to_config_file: bool,
     ) -> Result<()> {
-        if dry_run {
-            println!(
-                "{} {} {:?}",
-                "Dry run:".color("yellow"),
-                "Removed".color("red"),
-                path
-            );
-        } else {
+        if !dry_run {
             self.directory.retain(|data| data != path);
             if save_to_config_file {
                 self.save_to_config_file()?;
             }
-            println!("{} {:?}", "Removed".color("red"), path);
         }
         Ok(())
     }
 
-    // remove ignore file name
-    pub(crate) fn remove_ignore_file_name(
+    /// remove ignore file name
+    pub fn remove_ignore_file_name(
         &mut self,
         file_name: &str,
         dry_run: bool,
         save_to_config_file: bool,
     ) -> Result<()> {
-        if dry_run {
-            println!(
-                "{} {} {:?}",
-                "Dry run:".color("yellow"),
-                "Removed".color("red"),
-                file_name
-            );
-        } else {
+        if !dry_run {
             self.ignore_file_name.retain(|data| data != file_name);
             if save_to_config_file {
                 self.save_to_config_file()?;
             }
-            println!("{} {:?}", "Removed".color("red"), file_name);
         }
         Ok(())
     }
 
-    // List out cargo.toml file present directories by recursively analyze all
-    // folder present in directory
-    pub(crate) fn list_cargo_toml(&self, path: &Path) -> Result<CargoTomlLocation> {
+    /// remove crate from keep list
+    pub fn remove_keep(
+        &mut self,
+        value: &str,
+        dry_run: bool,
+        save_to_config_file: bool,
+    ) -> Result<()> {
+        if !dry_run {
+            self.keep.retain(|data| data != value);
+            if save_to_config_file {
+                self.save_to_config_file()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// List out cargo.toml file present directories by recursively analyze all
+    /// folder present in directory
+    pub fn list_cargo_toml(&self, path: &Path) -> Result<CargoTomlLocation> {
         let mut cargo_trim_list = CargoTomlLocation::new();
         if path.exists() {
             if path.is_dir() {
@@ -244,7 +273,7 @@ impl ConfigFile {
         let serialized =
             toml::to_string_pretty(&self).context("Config cannot to converted to pretty toml")?;
         buffer.push_str(&serialized);
-        fs::write(&self.config_file, buffer).context("Failed to write a value to config file")?;
+        fs::write(&self.path, buffer).context("Failed to write a value to config file")?;
         Ok(())
     }
 }
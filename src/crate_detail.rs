@@ -1,45 +1,112 @@
-use std::{collections::HashMap, fs, path::Path};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
+use rayon::{prelude::*, ThreadPool, ThreadPoolBuilder};
 
-use crate::utils::get_size;
+use crate::{
+    list_crate::{CrateKind, OldMode},
+    progress::Progress,
+    utils::{get_size, get_size_and_last_used},
+};
 
-// stores different crate size and name information
+/// stores different crate size and name information
 #[derive(Default)]
-pub(crate) struct CrateDetail {
+pub struct CrateDetail {
     bin: HashMap<String, u64>,
     git_crates_source: HashMap<String, u64>,
     registry_crates_source: HashMap<String, u64>,
     git_crates_archive: HashMap<String, u64>,
     registry_crates_archive: HashMap<String, u64>,
+    git_crates_last_used: HashMap<String, SystemTime>,
+    registry_crates_last_used: HashMap<String, SystemTime>,
+    registry_crates_registry_name: HashMap<String, Vec<String>>,
+    registry_crates_registry_size: HashMap<String, HashMap<String, u64>>,
+    git_crates_repository_dir: HashMap<String, Vec<String>>,
+    registry_filter: Option<Vec<String>>,
+    thread_pool: Option<ThreadPool>,
+    old_mode: OldMode,
+    progress: Option<Arc<dyn Progress>>,
 }
 
 impl CrateDetail {
-    // return bin crates size information
-    pub(crate) fn bin(&self) -> &HashMap<String, u64> {
+    /// return bin crates size information
+    pub fn bin(&self) -> &HashMap<String, u64> {
         &self.bin
     }
 
-    // return git crates source size information
-    pub(crate) fn git_crates_source(&self) -> &HashMap<String, u64> {
+    /// return git crates source size information
+    pub fn git_crates_source(&self) -> &HashMap<String, u64> {
         &self.git_crates_source
     }
 
-    // return registry crates source size information
-    pub(crate) fn registry_crates_source(&self) -> &HashMap<String, u64> {
+    /// return registry crates source size information
+    pub fn registry_crates_source(&self) -> &HashMap<String, u64> {
         &self.registry_crates_source
     }
 
-    // return git crates archive size information
-    pub(crate) fn git_crates_archive(&self) -> &HashMap<String, u64> {
+    /// return git crates archive size information
+    pub fn git_crates_archive(&self) -> &HashMap<String, u64> {
         &self.git_crates_archive
     }
 
-    // return registry crates archive size information
-    pub(crate) fn registry_crates_archive(&self) -> &HashMap<String, u64> {
+    /// return registry crates archive size information
+    pub fn registry_crates_archive(&self) -> &HashMap<String, u64> {
         &self.registry_crates_archive
     }
 
+    /// only scan registry crates present in provided registry folders
+    pub fn set_registry_filter(&mut self, registry_dirs: Vec<String>) {
+        self.registry_filter = Some(registry_dirs);
+    }
+
+    /// set number of parallel jobs used for scanning crates. Thread pool is built
+    /// once and reused for every scan. Number of logical cpu is used if it is not
+    /// set
+    pub fn set_jobs(&mut self, jobs: usize) -> Result<()> {
+        if jobs == 0 {
+            bail!("number of jobs should be at least 1");
+        }
+        let thread_pool = ThreadPoolBuilder::new()
+            .num_threads(jobs)
+            .build()
+            .context("failed to create thread pool")?;
+        self.thread_pool = Some(thread_pool);
+        Ok(())
+    }
+
+    /// set mode used for classifying registry crates as old
+    pub fn set_old_mode(&mut self, old_mode: OldMode) {
+        self.old_mode = old_mode;
+    }
+
+    /// return mode used for classifying registry crates as old
+    pub fn old_mode(&self) -> OldMode {
+        self.old_mode
+    }
+
+    /// report progress of scanning crates to provided progress
+    pub fn set_progress(&mut self, progress: Arc<dyn Progress>) {
+        self.progress = Some(progress);
+    }
+
+    /// check if registry filter is set
+    pub fn has_registry_filter(&self) -> bool {
+        self.registry_filter.is_some()
+    }
+
+    /// check if registry folder should be scanned
+    pub fn is_registry_scanned(&self, registry_dir: &str) -> bool {
+        self.registry_filter
+            .as_ref()
+            .is_none_or(|registry_dirs| registry_dirs.iter().any(|dir| dir == registry_dir))
+    }
+
     // add bin information to CrateDetail
     fn add_bin(&mut self, bin_name: String, size: u64) {
         self.bin.insert(bin_name, size);
@@ -65,6 +132,56 @@ impl CrateDetail {
         add_crate_to_hash_map(&mut self.registry_crates_archive, crate_name, size);
     }
 
+    // add git crate last used time information to CrateDetail
+    fn add_git_crate_last_used(&mut self, crate_name: String, last_used: SystemTime) {
+        add_last_used_to_hash_map(&mut self.git_crates_last_used, crate_name, last_used);
+    }
+
+    // add registry crate last used time information to CrateDetail
+    fn add_registry_crate_last_used(&mut self, crate_name: String, last_used: SystemTime) {
+        add_last_used_to_hash_map(&mut self.registry_crates_last_used, crate_name, last_used);
+    }
+
+    // add registry name of registry crate to CrateDetail
+    fn add_registry_crate_registry_name(&mut self, crate_name: String, registry_name: String) {
+        let registry_names = self
+            .registry_crates_registry_name
+            .entry(crate_name)
+            .or_default();
+        if !registry_names.contains(&registry_name) {
+            registry_names.push(registry_name);
+            registry_names.sort();
+        }
+    }
+
+    // add size of registry crate present in certain registry to CrateDetail
+    fn add_registry_crate_registry_size(
+        &mut self,
+        crate_name: String,
+        registry_name: String,
+        size: u64,
+    ) {
+        add_crate_to_hash_map(
+            self.registry_crates_registry_size
+                .entry(crate_name)
+                .or_default(),
+            registry_name,
+            size,
+        );
+    }
+
+    // add repository folder name of git crate to CrateDetail
+    fn add_git_crate_repository_dir(&mut self, crate_name: String, repository_dir: String) {
+        let repository_dirs = self
+            .git_crates_repository_dir
+            .entry(crate_name)
+            .or_default();
+        if !repository_dirs.contains(&repository_dir) {
+            repository_dirs.push(repository_dir);
+            repository_dirs.sort();
+        }
+    }
+
     // find size of certain git crate source in KB
     fn find_size_git_source(&self, crate_name: &str) -> f64 {
         get_hashmap_crate_size(&self.git_crates_source, crate_name)
@@ -86,29 +203,80 @@ impl CrateDetail {
         get_hashmap_crate_size(&self.registry_crates_archive, crate_name)
     }
 
-    // return certain git crate total size in KB
-    pub(crate) fn find_size_git_all(&self, crate_name: &str) -> f64 {
+    /// return certain git crate total size in KB
+    pub fn find_size_git_all(&self, crate_name: &str) -> f64 {
         self.find_size_git_archive(crate_name) + self.find_size_git_source(crate_name)
     }
 
-    // return certain registry crate total size in KB
-    pub(crate) fn find_size_registry_all(&self, crate_name: &str) -> f64 {
+    /// return certain registry crate total size in KB
+    pub fn find_size_registry_all(&self, crate_name: &str) -> f64 {
         self.find_size_registry_archive(crate_name) + self.find_size_registry_source(crate_name)
     }
 
-    // find crate size if location/title is given in KB
-    pub(crate) fn find(&self, crate_name: &str, location: &str) -> f64 {
-        if location.contains("REGISTRY") {
-            self.find_size_registry_all(crate_name)
-        } else if location.contains("GIT") {
-            self.find_size_git_all(crate_name)
-        } else {
-            0.0
+    /// find size of crate of provided kind in MB
+    pub fn find(&self, crate_name: &str, kind: CrateKind) -> f64 {
+        match kind {
+            CrateKind::Registry => self.find_size_registry_all(crate_name),
+            CrateKind::Git => self.find_size_git_all(crate_name),
         }
     }
 
-    // list installed bin
-    pub(crate) fn list_installed_bin(&mut self, bin_dir: &Path) -> Result<Vec<String>> {
+    /// find size of crate of provided kind in bytes
+    pub fn find_bytes(&self, crate_name: &str, kind: CrateKind) -> u64 {
+        let (source, archive) = match kind {
+            CrateKind::Registry => (&self.registry_crates_source, &self.registry_crates_archive),
+            CrateKind::Git => (&self.git_crates_source, &self.git_crates_archive),
+        };
+        source.get(crate_name).copied().unwrap_or(0) + archive.get(crate_name).copied().unwrap_or(0)
+    }
+
+    /// find name of registry directories which contains registry crate
+    pub fn find_registries(&self, crate_name: &str) -> &[String] {
+        self.registry_crates_registry_name
+            .get(crate_name)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// find size of registry crate in bytes which is present in provided registry
+    /// directories
+    pub fn find_bytes_in_registries(&self, crate_name: &str, registry_dirs: &[String]) -> u64 {
+        self.registry_crates_registry_size
+            .get(crate_name)
+            .map_or(0, |registry_size| {
+                registry_dirs
+                    .iter()
+                    .filter_map(|registry_dir| registry_size.get(registry_dir))
+                    .sum()
+            })
+    }
+
+    /// find repository folders present in git db or checkouts folder which contain
+    /// git crate
+    pub fn find_git_dirs(&self, crate_name: &str) -> &[String] {
+        self.git_crates_repository_dir
+            .get(crate_name)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// find last used time of crate of provided kind
+    pub fn find_last_used(&self, crate_name: &str, kind: CrateKind) -> Option<SystemTime> {
+        match kind {
+            CrateKind::Registry => self.registry_crates_last_used.get(crate_name).copied(),
+            CrateKind::Git => self.git_crates_last_used.get(crate_name).copied(),
+        }
+    }
+
+    /// find duration since crate of provided kind was last used
+    pub fn find_age(&self, crate_name: &str, kind: CrateKind) -> Option<Duration> {
+        self.find_last_used(crate_name, kind).map(|last_used| {
+            SystemTime::now()
+                .duration_since(last_used)
+                .unwrap_or_default()
+        })
+    }
+
+    /// list installed bin
+    pub fn list_installed_bin(&mut self, bin_dir: &Path) -> Result<Vec<String>> {
         let mut installed_bin = Vec::new();
         if bin_dir.exists() {
             for entry in fs::read_dir(bin_dir).context("failed to read bin directory")? {
@@ -126,105 +294,206 @@ impl CrateDetail {
         Ok(installed_bin)
     }
 
-    // list all installed registry crates
-    pub(crate) fn list_installed_crate_registry(
+    /// list all installed registry crates
+    pub fn list_installed_crate_registry(
         &mut self,
         src_dir: &Path,
         cache_dir: &Path,
     ) -> Result<Vec<String>> {
         let mut installed_crate_registry = Vec::new();
         // read src dir to get installed crate
+        let mut src_entries = Vec::new();
         if src_dir.exists() {
             for entry in fs::read_dir(src_dir).context("failed to read src directory")? {
                 let registry = entry?.path();
+                let registry_name = registry
+                    .file_name()
+                    .context("failed to get registry name from src directory")?
+                    .to_str()
+                    .unwrap()
+                    .to_string();
+                if !self.is_registry_scanned(&registry_name) {
+                    continue;
+                }
                 for entry in fs::read_dir(registry).context("failed to read registry folder")? {
-                    let entry = entry?.path();
-                    let crate_size =
-                        get_size(&entry).context("failed to get registry crate size")?;
-                    let file_name = entry
-                        .file_name()
-                        .context("failed to get file name form main entry")?;
-                    let crate_name = file_name.to_str().unwrap();
-                    self.add_registry_crate_source(crate_name.to_owned(), crate_size);
-                    installed_crate_registry.push(crate_name.to_owned());
+                    src_entries.push((registry_name.clone(), entry?.path()));
                 }
             }
         }
+        let src_details = self
+            .scan_entries("Scanning registry source", &src_entries)
+            .context("failed to scan registry crate source")?;
+        for ((registry_name, entry), (crate_size, last_used)) in src_entries.iter().zip(src_details)
+        {
+            let file_name = entry
+                .file_name()
+                .context("failed to get file name form main entry")?;
+            let crate_name = file_name.to_str().unwrap();
+            self.add_registry_crate_source(crate_name.to_owned(), crate_size);
+            self.add_registry_crate_last_used(crate_name.to_owned(), last_used);
+            self.add_registry_crate_registry_name(crate_name.to_owned(), registry_name.clone());
+            self.add_registry_crate_registry_size(
+                crate_name.to_owned(),
+                registry_name.clone(),
+                crate_size,
+            );
+            installed_crate_registry.push(crate_name.to_owned());
+        }
         // read cache dir to get installed crate
+        let mut cache_entries = Vec::new();
         if cache_dir.exists() {
             for entry in fs::read_dir(cache_dir).context("failed to read cache dir")? {
                 let registry = entry?.path();
+                let registry_name = registry
+                    .file_name()
+                    .context("failed to get registry name from cache directory")?
+                    .to_str()
+                    .unwrap()
+                    .to_string();
+                if !self.is_registry_scanned(&registry_name) {
+                    continue;
+                }
                 for entry in
                     fs::read_dir(registry).context("failed to read cache dir registry folder")?
                 {
-                    let entry = entry?.path();
-                    let file_name = entry
-                        .file_name()
-                        .context("failed to get file name from cache dir")?;
-                    let crate_size = get_size(&entry).context("failed to get size")?;
-                    let crate_name = file_name.to_str().unwrap();
-                    let split_name = crate_name.rsplitn(2, '.').collect::<Vec<&str>>();
-                    self.add_registry_crate_archive(split_name[1].to_owned(), crate_size);
-                    installed_crate_registry.push(split_name[1].to_owned());
+                    cache_entries.push((registry_name.clone(), entry?.path()));
                 }
             }
         }
+        let cache_details = self
+            .scan_entries("Scanning registry cache", &cache_entries)
+            .context("failed to scan registry crate archive")?;
+        for ((registry_name, entry), (crate_size, last_used)) in
+            cache_entries.iter().zip(cache_details)
+        {
+            let file_name = entry
+                .file_name()
+                .context("failed to get file name from cache dir")?;
+            let crate_name = file_name.to_str().unwrap();
+            let split_name = crate_name.rsplitn(2, '.').collect::<Vec<&str>>();
+            self.add_registry_crate_archive(split_name[1].to_owned(), crate_size);
+            self.add_registry_crate_last_used(split_name[1].to_owned(), last_used);
+            self.add_registry_crate_registry_name(split_name[1].to_owned(), registry_name.clone());
+            self.add_registry_crate_registry_size(
+                split_name[1].to_owned(),
+                registry_name.clone(),
+                crate_size,
+            );
+            installed_crate_registry.push(split_name[1].to_owned());
+        }
         installed_crate_registry.sort();
         installed_crate_registry.dedup();
         Ok(installed_crate_registry)
     }
 
-    // list all installed git crates
-    pub(crate) fn list_installed_crate_git(
+    /// list all installed git crates
+    pub fn list_installed_crate_git(
         &mut self,
         checkout_dir: &Path,
         db_dir: &Path,
     ) -> Result<Vec<String>> {
         let mut installed_crate_git = Vec::new();
+        // read checkout dir to list crate name in form of crate_name-rev_sha
+        let mut checkout_entries = Vec::new();
         if checkout_dir.exists() {
-            // read checkout dir to list crate name in form of crate_name-rev_sha
             for entry in fs::read_dir(checkout_dir).context("failed to read checkout directory")? {
                 let entry = entry?.path();
-                let path = entry.as_path();
-                let file_path = path
+                let file_name = entry
                     .file_name()
-                    .context("failed to obtain checkout directory sub folder file name")?;
+                    .context("failed to obtain checkout directory sub folder file name")?
+                    .to_str()
+                    .unwrap()
+                    .to_string();
                 for git_sha_entry in
-                    fs::read_dir(path).context("failed to read checkout dir sub folder")?
+                    fs::read_dir(&entry).context("failed to read checkout dir sub folder")?
                 {
-                    let git_sha_entry = git_sha_entry?.path();
-                    let crate_size =
-                        get_size(&git_sha_entry).context("failed to get folder size")?;
-                    let git_sha_file_name = git_sha_entry
-                        .file_name()
-                        .context("failed to get file name")?;
-                    let git_sha = git_sha_file_name.to_str().unwrap();
-                    let file_name = file_path.to_str().unwrap();
-                    let split_name = file_name.rsplitn(2, '-').collect::<Vec<&str>>();
-                    let full_name = format!("{}-{}", split_name[1], git_sha);
-                    self.add_git_crate_archive(full_name.clone(), crate_size);
-                    installed_crate_git.push(full_name);
+                    checkout_entries.push((file_name.clone(), git_sha_entry?.path()));
                 }
             }
         }
+        let checkout_details = self
+            .scan_entries("Scanning git checkouts", &checkout_entries)
+            .context("failed to scan git checkout folder")?;
+        for ((file_name, git_sha_entry), (crate_size, last_used)) in
+            checkout_entries.iter().zip(checkout_details)
+        {
+            let git_sha_file_name = git_sha_entry
+                .file_name()
+                .context("failed to get file name")?;
+            let git_sha = git_sha_file_name.to_str().unwrap();
+            let split_name = file_name.rsplitn(2, '-').collect::<Vec<&str>>();
+            let full_name = format!("{}-{}", split_name[1], git_sha);
+            self.add_git_crate_archive(full_name.clone(), crate_size);
+            self.add_git_crate_last_used(full_name.clone(), last_used);
+            self.add_git_crate_repository_dir(full_name.clone(), file_name.clone());
+            installed_crate_git.push(full_name);
+        }
         // read a database directory to list a git crate in form of crate_name-HEAD
+        let mut db_entries = Vec::new();
         if db_dir.exists() {
             for entry in fs::read_dir(db_dir).context("failed to read db dir")? {
                 let entry = entry?.path();
-                let crate_size =
-                    get_size(&entry).context("failed to get size of db dir folders")?;
-                let file_name = entry.file_name().context("failed to get file name")?;
-                let file_name = file_name.to_str().unwrap();
-                let split_name = file_name.rsplitn(2, '-').collect::<Vec<&str>>();
-                let full_name = format!("{}-HEAD", split_name[1]);
-                self.add_git_crate_source(full_name.clone(), crate_size);
-                installed_crate_git.push(full_name);
+                let file_name = entry
+                    .file_name()
+                    .context("failed to get file name")?
+                    .to_str()
+                    .unwrap()
+                    .to_string();
+                db_entries.push((file_name, entry));
             }
         }
+        let db_details = self
+            .scan_entries("Scanning git db", &db_entries)
+            .context("failed to scan git db folder")?;
+        for ((file_name, _), (crate_size, last_used)) in db_entries.iter().zip(db_details) {
+            let split_name = file_name.rsplitn(2, '-').collect::<Vec<&str>>();
+            let full_name = format!("{}-HEAD", split_name[1]);
+            self.add_git_crate_source(full_name.clone(), crate_size);
+            self.add_git_crate_last_used(full_name.clone(), last_used);
+            self.add_git_crate_repository_dir(full_name.clone(), file_name.clone());
+            installed_crate_git.push(full_name);
+        }
         installed_crate_git.sort();
         installed_crate_git.dedup();
         Ok(installed_crate_git)
     }
+
+    // compute size and last used time of entries in parallel using configured
+    // thread pool or global thread pool if number of jobs is not set. Result is
+    // returned in same order as entries
+    // path is debug formatted so it is printed within quotes
+    #[allow(clippy::unnecessary_debug_formatting)]
+    fn scan_entries(
+        &self,
+        title: &str,
+        entries: &[(String, PathBuf)],
+    ) -> Result<Vec<(u64, SystemTime)>> {
+        if let Some(progress) = &self.progress {
+            progress.start(title, entries.len());
+        }
+        let scan = || {
+            entries
+                .par_iter()
+                .map(|(_, entry)| {
+                    let (size, last_used) = get_size_and_last_used(entry)
+                        .with_context(|| format!("failed to scan {entry:?}"))?;
+                    if let Some(progress) = &self.progress {
+                        let item = entry.file_name().and_then(|name| name.to_str());
+                        progress.advance(item.unwrap_or_default(), size);
+                    }
+                    Ok((size, last_used))
+                })
+                .collect()
+        };
+        let details = match &self.thread_pool {
+            Some(thread_pool) => thread_pool.install(scan),
+            None => scan(),
+        };
+        if let Some(progress) = &self.progress {
+            progress.finish();
+        }
+        details
+    }
 }
 
 #[allow(clippy::cast_precision_loss)]
@@ -242,11 +511,27 @@ fn add_crate_to_hash_map(hashmap: &mut HashMap<String, u64>, crate_name: String,
     }
 }
 
+// add last used time to hashmap keeping latest time if crate is already present
+fn add_last_used_to_hash_map(
+    hashmap: &mut HashMap<String, SystemTime>,
+    crate_name: String,
+    last_used: SystemTime,
+) {
+    let crate_last_used = hashmap.entry(crate_name).or_insert(last_used);
+    if *crate_last_used < last_used {
+        *crate_last_used = last_used;
+    }
+}
+
 #[cfg(test)]
+#[allow(clippy::float_cmp)]
 mod test {
-    use std::collections::HashMap;
+    use std::{
+        collections::HashMap,
+        time::{Duration, SystemTime},
+    };
 
-    use super::{add_crate_to_hash_map, get_hashmap_crate_size};
+    use super::{add_crate_to_hash_map, add_last_used_to_hash_map, get_hashmap_crate_size};
     #[test]
     fn test_get_hashmap_crate_size() {
         let mut hashmap_content = HashMap::new();
@@ -277,4 +562,20 @@ mod test {
 
         assert_eq!(hashmap_content, another_hashmap);
     }
+
+    #[test]
+    fn test_add_last_used_to_hashmap() {
+        let old_time = SystemTime::UNIX_EPOCH;
+        let new_time = SystemTime::UNIX_EPOCH + Duration::from_secs(100);
+        let mut hashmap_content = HashMap::new();
+        add_last_used_to_hash_map(&mut hashmap_content, "sample_crate".to_string(), new_time);
+        add_last_used_to_hash_map(&mut hashmap_content, "sample_crate".to_string(), old_time);
+        add_last_used_to_hash_map(&mut hashmap_content, "sample_crate_2".to_string(), old_time);
+
+        let mut another_hashmap = HashMap::new();
+        another_hashmap.insert("sample_crate".to_string(), new_time);
+        another_hashmap.insert("sample_crate_2".to_string(), old_time);
+
+        assert_eq!(hashmap_content, another_hashmap);
+    }
 }
@@ -0,0 +1,169 @@
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+use semver::{Version, VersionReq};
+
+use crate::utils::{clear_version_value, crate_matches, glob_match};
+
+// Version part of selector. Value which is not valid semver requirement such as
+// 0.3x is matched as glob where x matches single digit
+#[derive(Debug, Clone)]
+enum VersionSelector {
+    Requirement(VersionReq),
+    Glob(String),
+}
+
+/// Selector used for selecting installed crates. Selector is crate name or glob
+/// pattern optionally followed by @ and version requirement such as tokio,
+/// serde_*, tokio@<1.20 or windows-*@0.3x
+#[derive(Debug, Clone)]
+pub struct CrateSelector {
+    value: String,
+    name: String,
+    version: Option<VersionSelector>,
+}
+
+impl FromStr for CrateSelector {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let (name, version) = match value.split_once('@') {
+            Some((name, version)) => (name, Some(version)),
+            None => (value, None),
+        };
+        if name.is_empty() {
+            bail!("crate name is not provided in selector {:?}", value);
+        }
+        let version = match version {
+            Some("") => bail!("version is not provided in selector {:?}", value),
+            Some(version) => Some(VersionReq::parse(version).map_or_else(
+                |_| VersionSelector::Glob(version.replace(['x', 'X'], "?")),
+                VersionSelector::Requirement,
+            )),
+            None => None,
+        };
+        Ok(Self {
+            value: value.to_string(),
+            name: name.to_string(),
+            version,
+        })
+    }
+}
+
+impl CrateSelector {
+    /// return selector as provided by user
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// check if installed registry crate full name such as tokio-1.20.0 matches
+    /// selector
+    pub fn matches_registry(&self, crate_name: &str) -> bool {
+        let Some(version_selector) = &self.version else {
+            return crate_matches(&self.name, crate_name);
+        };
+        let (name, version) = clear_version_value(crate_name);
+        if !glob_match(&self.name, &name) {
+            return false;
+        }
+        match version_selector {
+            VersionSelector::Requirement(requirement) => {
+                Version::parse(&version).is_ok_and(|version| requirement.matches(&version))
+            }
+            // glob with less part than version matches start of version
+            VersionSelector::Glob(pattern) => {
+                glob_match(pattern, &version) || glob_match(&format!("{pattern}.*"), &version)
+            }
+        }
+    }
+
+    /// check if installed git crate full name such as repo-eab33b6 matches selector.
+    /// Git crates do not have version so selector with version never matches
+    pub fn matches_git(&self, crate_name: &str) -> bool {
+        self.version.is_none() && crate_matches(&self.name, crate_name)
+    }
+
+    /// list installed registry crates which matches selector
+    pub fn expand_registry<'a>(&self, installed: &'a [String]) -> Vec<&'a String> {
+        installed
+            .iter()
+            .filter(|crate_name| self.matches_registry(crate_name))
+            .collect()
+    }
+
+    /// list installed git crates which matches selector
+    pub fn expand_git<'a>(&self, installed: &'a [String]) -> Vec<&'a String> {
+        installed
+            .iter()
+            .filter(|crate_name| self.matches_git(crate_name))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::CrateSelector;
+    use crate::test_util::{dir_path, fake_cargo_home, registry_dir, scan, REGISTRY};
+
+    #[test]
+    fn test_crate_selector() {
+        let selector = "tokio".parse::<CrateSelector>().unwrap();
+        assert!(selector.matches_registry("tokio-1.20.0"));
+        assert!(selector.matches_registry("tokio-0.2.25"));
+        assert!(!selector.matches_registry("tokio-util-0.7.0"));
+        assert!(selector.matches_git("tokio-eab33b6"));
+
+        let selector = "tokio-1.20.0".parse::<CrateSelector>().unwrap();
+        assert!(selector.matches_registry("tokio-1.20.0"));
+        assert!(!selector.matches_registry("tokio-1.21.0"));
+
+        let selector = "tokio@<1.20".parse::<CrateSelector>().unwrap();
+        assert!(selector.matches_registry("tokio-1.19.2"));
+        assert!(!selector.matches_registry("tokio-1.20.0"));
+        assert!(!selector.matches_git("tokio-eab33b6"));
+
+        let selector = "serde_*".parse::<CrateSelector>().unwrap();
+        assert!(selector.matches_registry("serde_json-1.0.100"));
+        assert!(!selector.matches_registry("serde-1.0.100"));
+
+        let selector = "windows-*@0.3x".parse::<CrateSelector>().unwrap();
+        assert!(selector.matches_registry("windows-sys-0.36.1"));
+        assert!(!selector.matches_registry("windows-sys-0.42.0"));
+        assert!(!selector.matches_registry("windows-sys-0.3.0"));
+        assert!(!selector.matches_registry("windows-0.36.1"));
+
+        assert!("@1.0".parse::<CrateSelector>().is_err());
+        assert!("tokio@".parse::<CrateSelector>().is_err());
+    }
+
+    // test selector expands to installed crates and only matched crate is removed
+    #[test]
+    fn test_remove_selector() {
+        let cargo_home = fake_cargo_home("selector", REGISTRY);
+        let prerelease = cargo_home
+            .path()
+            .join("registry/src")
+            .join(REGISTRY)
+            .join("alpha-0.1.0-rc.1");
+        fs::create_dir_all(&prerelease).unwrap();
+        let dir_path = dir_path(&cargo_home);
+        let (_, _, crate_list) = scan(&dir_path);
+        let installed = crate_list.installed_registry();
+        let selector = "alpha".parse::<CrateSelector>().unwrap();
+        assert_eq!(selector.expand_registry(installed).len(), 3);
+        let selector = "alpha@>=0.1.0, <0.2".parse::<CrateSelector>().unwrap();
+        assert_eq!(selector.expand_registry(installed), vec!["alpha-0.1.0"]);
+        let selector = "*ta@1.x".parse::<CrateSelector>().unwrap();
+        assert_eq!(selector.expand_registry(installed), vec!["zeta-1.0.0"]);
+        let mut registry_dir = registry_dir(&dir_path, &crate_list);
+        registry_dir.remove_crate("alpha-0.1.0", false).unwrap();
+        assert!(!dir_path
+            .src_dir()
+            .join(REGISTRY)
+            .join("alpha-0.1.0")
+            .exists());
+        assert!(prerelease.exists());
+    }
+}
@@ -1,12 +1,13 @@
 use std::{
-    fs,
+    env, fs,
     path::{Path, PathBuf},
 };
 
 use anyhow::{Context, Result};
 
-// Struct for storing Directory path
-pub(crate) struct DirPath {
+/// Struct for storing Directory path
+pub struct DirPath {
+    home_dir: PathBuf,
     bin_dir: PathBuf,
     config_file: PathBuf,
     git_dir: PathBuf,
@@ -16,25 +17,43 @@ pub(crate) struct DirPath {
     cache_dir: PathBuf,
     index_dir: PathBuf,
     src_dir: PathBuf,
+    trash_dir: PathBuf,
+    journal_file: PathBuf,
+    rustup_dir: PathBuf,
 }
 
 impl DirPath {
-    // set directory path
-    pub(crate) fn new() -> Result<Self> {
+    /// set directory path. If cargo home is not provided it is resolved at runtime
+    pub fn new(cargo_home: Option<&Path>) -> Result<Self> {
         // set config file directory path
         let config_dir = dirs_next::config_dir().context("Cannot get config directory location")?;
+        Self::with_config_dir(cargo_home, &config_dir)
+    }
+
+    // set directory path with config file stored inside provided config directory
+    pub(crate) fn with_config_dir(cargo_home: Option<&Path>, config_dir: &Path) -> Result<Self> {
         // if config dir not exists create
         if !config_dir.exists() {
-            fs::create_dir_all(&config_dir).context("Failed to create config dir")?;
+            fs::create_dir_all(config_dir).context("Failed to create config dir")?;
         }
         let config_file = config_dir.join("cargo_trim_config.toml");
 
+        // set trash directory path where removed crates are moved in trash mode
+        let data_dir = dirs_next::data_dir().context("Cannot get data directory location")?;
+        let trash_dir = data_dir.join("cargo-trim").join("trash");
+
+        // set journal file path where all removal are recorded
+        let journal_file = data_dir.join("cargo-trim").join("journal.jsonl");
+
         // If config file does not exists create config file
         if !config_file.exists() {
             fs::File::create(&config_file).context("Failed to create config file")?;
         }
 
-        let home_dir = Path::new(env!("CARGO_HOME")).to_path_buf();
+        let home_dir = cargo_home_dir(cargo_home)?;
+
+        // set rustup home directory path which contains installed toolchains
+        let rustup_dir = rustup_home_dir()?;
 
         // set bin directory path
         let bin_dir = home_dir.join("bin");
@@ -55,6 +74,7 @@ impl DirPath {
         let index_dir = registry_dir.join("index");
 
         Ok(Self {
+            home_dir,
             bin_dir,
             config_file,
             git_dir,
@@ -64,51 +84,128 @@ impl DirPath {
             cache_dir,
             index_dir,
             src_dir,
+            trash_dir,
+            journal_file,
+            rustup_dir,
         })
     }
 
-    // return path of bin dir
-    pub(crate) fn bin_dir(&self) -> &PathBuf {
+    /// return path of cargo home dir
+    pub fn home_dir(&self) -> &PathBuf {
+        &self.home_dir
+    }
+
+    /// return path of bin dir
+    pub fn bin_dir(&self) -> &PathBuf {
         &self.bin_dir
     }
 
-    // return path of config file
-    pub(crate) fn config_file(&self) -> &PathBuf {
+    /// return path of config file
+    pub fn config_file(&self) -> &PathBuf {
         &self.config_file
     }
 
-    // return path of git dir
-    pub(crate) fn git_dir(&self) -> &PathBuf {
+    /// return path of git dir
+    pub fn git_dir(&self) -> &PathBuf {
         &self.git_dir
     }
 
-    // return path of checkout dir
-    pub(crate) fn checkout_dir(&self) -> &PathBuf {
+    /// return path of checkout dir
+    pub fn checkout_dir(&self) -> &PathBuf {
         &self.checkout_dir
     }
 
-    // return path of db dir
-    pub(crate) fn db_dir(&self) -> &PathBuf {
+    /// return path of db dir
+    pub fn db_dir(&self) -> &PathBuf {
         &self.db_dir
     }
 
-    // return path of registry dir
-    pub(crate) fn registry_dir(&self) -> &PathBuf {
+    /// return path of registry dir
+    pub fn registry_dir(&self) -> &PathBuf {
         &self.registry_dir
     }
 
-    // return path of cache dir
-    pub(crate) fn cache_dir(&self) -> &PathBuf {
+    /// return path of cache dir
+    pub fn cache_dir(&self) -> &PathBuf {
         &self.cache_dir
     }
 
-    // return path of index dir
-    pub(crate) fn index_dir(&self) -> &PathBuf {
+    /// return path of index dir
+    pub fn index_dir(&self) -> &PathBuf {
         &self.index_dir
     }
 
-    // return path of src dir
-    pub(crate) fn src_dir(&self) -> &PathBuf {
+    /// return path of src dir
+    pub fn src_dir(&self) -> &PathBuf {
         &self.src_dir
     }
+
+    /// return path of trash dir
+    pub fn trash_dir(&self) -> &PathBuf {
+        &self.trash_dir
+    }
+
+    /// return path of journal file
+    pub fn journal_file(&self) -> &PathBuf {
+        &self.journal_file
+    }
+
+    /// return path of rustup home dir
+    pub fn rustup_dir(&self) -> &PathBuf {
+        &self.rustup_dir
+    }
+}
+
+// determine cargo home directory. Provided path have highest priority then
+// $CARGO_HOME env variable is used and at last fallback to $HOME/.cargo
+fn cargo_home_dir(cargo_home: Option<&Path>) -> Result<PathBuf> {
+    let home_dir = if let Some(path) = cargo_home {
+        path.to_path_buf()
+    } else if let Some(path) = env::var_os("CARGO_HOME").filter(|path| !path.is_empty()) {
+        PathBuf::from(path)
+    } else {
+        dirs_next::home_dir()
+            .context("Cannot get home directory location")?
+            .join(".cargo")
+    };
+    // relative path is resolved from current working directory similar to cargo
+    if home_dir.is_relative() {
+        let current_dir = env::current_dir().context("Current working directory is invalid")?;
+        return Ok(current_dir.join(home_dir));
+    }
+    Ok(home_dir)
+}
+
+// determine rustup home directory. $RUSTUP_HOME env variable is used if it is
+// set otherwise fallback to $HOME/.rustup
+fn rustup_home_dir() -> Result<PathBuf> {
+    if let Some(path) = env::var_os("RUSTUP_HOME").filter(|path| !path.is_empty()) {
+        let path = PathBuf::from(path);
+        if path.is_relative() {
+            let current_dir = env::current_dir().context("Current working directory is invalid")?;
+            return Ok(current_dir.join(path));
+        }
+        return Ok(path);
+    }
+    Ok(dirs_next::home_dir()
+        .context("Cannot get home directory location")?
+        .join(".rustup"))
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::cargo_home_dir;
+
+    #[test]
+    fn test_cargo_home_dir() {
+        // temp dir is absolute path in every platform
+        let absolute = std::env::temp_dir().join("cargo");
+        assert_eq!(cargo_home_dir(Some(&absolute)).unwrap(), absolute);
+        assert_eq!(
+            cargo_home_dir(Some(Path::new("cargo_home"))).unwrap(),
+            std::env::current_dir().unwrap().join("cargo_home")
+        );
+    }
 }
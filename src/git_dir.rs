@@ -1,86 +1,207 @@
-use std::{fs, path::Path};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
-use anyhow::Result;
-use colored::Colorize;
+use anyhow::{bail, Context, Result};
 
-use crate::{crate_detail::CrateDetail, utils::delete_folder};
+use crate::{
+    journal::Journal,
+    trash::{remove_path, Trash},
+    utils::crate_matches,
+};
 
-// Store git dir folder information
-pub(crate) struct GitDir<'a> {
-    checkout_dir: &'a str,
-    db_dir: &'a str,
+/// Store git dir folder information
+pub struct GitDir<'a> {
+    checkout_dir: &'a Path,
+    db_dir: &'a Path,
+    trash: Option<Arc<Trash>>,
+    journal: Option<Arc<Journal>>,
+    keep: Vec<String>,
 }
 
 impl<'a> GitDir<'a> {
-    // create new GitDir
-    pub(crate) fn new(checkout_dir: &'a Path, db_dir: &'a Path) -> Self {
-        let checkout_dir = checkout_dir.to_str().unwrap();
-        let db_dir = db_dir.to_str().unwrap();
+    /// create new `GitDir`
+    pub fn new(checkout_dir: &'a Path, db_dir: &'a Path) -> Self {
         Self {
             checkout_dir,
             db_dir,
+            trash: None,
+            journal: None,
+            keep: Vec::new(),
         }
     }
 
-    // remove crates
-    pub(crate) fn remove_crate(&self, crate_name: &str, dry_run: bool) {
-        let is_success;
-        if crate_name.contains("-HEAD") {
-            is_success = remove_crate(Path::new(&self.db_dir), crate_name, dry_run).is_ok();
-        } else {
-            is_success = remove_crate(Path::new(&self.checkout_dir), crate_name, dry_run).is_ok();
+    /// move removed git checkout and db to trash instead of deleting them
+    pub fn set_trash(&mut self, trash: Arc<Trash>) {
+        self.trash = Some(trash);
+    }
+
+    /// record removed crates in journal
+    pub fn set_journal(&mut self, journal: Arc<Journal>) {
+        self.journal = Some(journal);
+    }
+
+    /// set crate name, full name or glob pattern of crates which are never cleaned
+    pub fn set_keep(&mut self, keep: &[String]) {
+        self.keep = keep.to_vec();
+    }
+
+    /// check if crate is present in keep list
+    pub fn is_kept(&self, crate_name: &str) -> bool {
+        self.keep
+            .iter()
+            .any(|pattern| crate_matches(pattern, crate_name))
+    }
+
+    /// remove checkout of all crates. Whole checkouts folder is removed when keep
+    /// list is empty otherwise checkout of crates present in keep list is left in
+    /// place
+    pub fn remove_all_checkouts(&self, dry_run: bool) -> Result<()> {
+        let trash = self.trash.as_deref();
+        let journal = self.journal.as_deref();
+        let checkout_dir = self.checkout_dir;
+        if self.keep.is_empty() {
+            return remove_path(checkout_dir, "checkouts", trash, journal, dry_run)
+                .context("failed to remove git checkouts");
         }
-        if dry_run {
-            println!(
-                "{} {} {:?}",
-                "Dry run:".color("yellow"),
-                "Removed".color("red"),
-                crate_name
-            );
-        } else if is_success {
-            println!("{} {:?}", "Removed".color("red"), crate_name);
-        } else {
-            println!("Failed to remove {:?}", crate_name);
+        for (repository_name, path) in list_repository(checkout_dir)? {
+            for rev in fs::read_dir(path)? {
+                let path = rev?.path();
+                let Some(rev_sha) = path.file_name().and_then(|name| name.to_str()) else {
+                    continue;
+                };
+                let crate_name = format!("{repository_name}-{rev_sha}");
+                if !self.is_kept(&crate_name) {
+                    remove_path(&path, &crate_name, trash, journal, dry_run)?;
+                }
+            }
         }
+        Ok(())
     }
 
-    // Remove list of crates
-    pub(crate) fn remove_crate_list(
+    /// remove database of all crates. Whole db folder is removed when keep list is
+    /// empty otherwise database of crates present in keep list is left in place
+    pub fn remove_all_db(&self, dry_run: bool) -> Result<()> {
+        let trash = self.trash.as_deref();
+        let journal = self.journal.as_deref();
+        let db_dir = self.db_dir;
+        if self.keep.is_empty() {
+            return remove_path(db_dir, "db", trash, journal, dry_run)
+                .context("failed to remove git db");
+        }
+        for (repository_name, path) in list_repository(db_dir)? {
+            let crate_name = format!("{repository_name}-HEAD");
+            if !self.is_kept(&crate_name) {
+                remove_path(&path, &crate_name, trash, journal, dry_run)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// remove crates from all repository folders
+    pub fn remove_crate(&self, crate_name: &str, dry_run: bool) -> Result<()> {
+        self.remove_crate_in(crate_name, None, dry_run)
+    }
+
+    /// remove crates only from provided repository folders
+    pub fn remove_crate_from_repository(
+        &self,
+        crate_name: &str,
+        repository_dirs: &[String],
+        dry_run: bool,
+    ) -> Result<()> {
+        self.remove_crate_in(crate_name, Some(repository_dirs), dry_run)
+    }
+
+    // remove crates from repository folders. If repository folders is not provided
+    // crate is removed from all repository folder. Crate present in keep list is
+    // never removed and error is returned instead
+    fn remove_crate_in(
         &self,
-        crate_detail: &CrateDetail,
-        list: &[String],
+        crate_name: &str,
+        repository_dirs: Option<&[String]>,
         dry_run: bool,
-    ) -> f64 {
-        let mut size_cleaned = 0.0;
-        for crate_name in list {
-            self.remove_crate(crate_name, dry_run);
-            size_cleaned += crate_detail.find(crate_name, "GIT");
+    ) -> Result<()> {
+        if self.is_kept(crate_name) {
+            bail!("{crate_name} is present in keep list and is not removed");
         }
-        size_cleaned
+        let location = if crate_name.contains("-HEAD") {
+            self.db_dir
+        } else {
+            self.checkout_dir
+        };
+        remove_crate(
+            location,
+            crate_name,
+            repository_dirs,
+            self.trash.as_deref(),
+            self.journal.as_deref(),
+            dry_run,
+        )
+        .with_context(|| format!("failed to remove git crate {crate_name}"))
     }
 }
 
-// preform remove operation
-fn remove_crate(location: &Path, crate_name: &str, dry_run: bool) -> Result<()> {
+// preform remove operation. Crate is moved to trash if trash is provided
+fn remove_crate(
+    location: &Path,
+    crate_name: &str,
+    repository_dirs: Option<&[String]>,
+    trash: Option<&Trash>,
+    journal: Option<&Journal>,
+    dry_run: bool,
+) -> Result<()> {
+    // split crate name to split repository name and rev sha
+    let name = crate_name.rsplitn(2, '-').collect::<Vec<&str>>();
+    let repository_name = name[1];
+    let rev_sha = name[0];
     for entry in fs::read_dir(location)? {
         let path = entry?.path();
-        // split directory name to split crate and rev sha
-        let name = crate_name.rsplitn(2, '-').collect::<Vec<&str>>();
-        let crate_name = name[1];
-        let rev_sha = name[0];
-        if path.to_str().unwrap().contains(crate_name) {
-            if rev_sha.contains("HEAD") {
-                delete_folder(&path, dry_run)?;
-            } else {
-                for rev in fs::read_dir(path)? {
-                    let path = rev?.path();
-                    let file_name = path.file_name().unwrap().to_str().unwrap();
-                    if file_name == rev_sha {
-                        delete_folder(&path, dry_run)?;
-                    }
+        let Some(dir_name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        // repository folder name is repository name followed by hash of url
+        if dir_name.rsplit_once('-').map(|(name, _)| name) != Some(repository_name) {
+            continue;
+        }
+        if repository_dirs.is_some_and(|dirs| !dirs.iter().any(|dir| dir == dir_name)) {
+            continue;
+        }
+        if rev_sha.contains("HEAD") {
+            remove_path(&path, crate_name, trash, journal, dry_run)?;
+        } else {
+            for rev in fs::read_dir(path)? {
+                let path = rev?.path();
+                if path.file_name().and_then(|name| name.to_str()) == Some(rev_sha) {
+                    remove_path(&path, crate_name, trash, journal, dry_run)?;
                 }
             }
         }
     }
     Ok(())
 }
+
+// list repository folders present in checkouts or db folder along with name of
+// repository. Repository folder name is repository name followed by hash of url
+fn list_repository(location: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let mut repositories = Vec::new();
+    if !location.exists() {
+        return Ok(repositories);
+    }
+    for entry in fs::read_dir(location)? {
+        let path = entry?.path();
+        let repository_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.rsplit_once('-'))
+            .map(|(name, _)| name.to_string());
+        if let Some(repository_name) = repository_name {
+            if path.is_dir() {
+                repositories.push((repository_name, path));
+            }
+        }
+    }
+    Ok(repositories)
+}
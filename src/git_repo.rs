@@ -0,0 +1,505 @@
+//! Operation performed on git repository present in cargo home such as registry
+//! index, git db and git checkouts using libgit2 so git binary is not required
+
+use std::{
+    collections::HashSet,
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::Path,
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use git2::{ErrorCode, ObjectType, Oid, ReferenceType, Repository};
+
+// loose object older than grace period is pruned even if it is not reachable.
+// Same as default prune expiry of git gc
+const PRUNE_GRACE_SECONDS: u64 = 2 * 7 * 24 * 60 * 60;
+
+// return latest commit rev value of git repository. HEAD of git db created by
+// cargo is not set so if HEAD cannot be resolved latest commit pointed by any
+// refs is returned. Folder which is not git repository do not have any commit
+// path is debug formatted so it is printed within quotes
+#[allow(clippy::unnecessary_debug_formatting)]
+pub(crate) fn latest_commit(repo_path: &Path) -> Result<Option<String>> {
+    let repo = match Repository::open(repo_path) {
+        Ok(repo) => repo,
+        Err(e) if e.code() == ErrorCode::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(e).with_context(|| format!("failed to open git repository {repo_path:?}"))
+        }
+    };
+    if let Ok(commit) = repo.head().and_then(|head| head.peel_to_commit()) {
+        return Ok(Some(commit.id().to_string()));
+    }
+    let mut latest_commit: Option<git2::Commit> = None;
+    for reference in repo.references().context("failed to list git references")? {
+        if let Ok(commit) = reference?.peel_to_commit() {
+            if latest_commit
+                .as_ref()
+                .is_none_or(|latest| latest.time().seconds() < commit.time().seconds())
+            {
+                latest_commit = Some(commit);
+            }
+        }
+    }
+    Ok(latest_commit.map(|commit| commit.id().to_string()))
+}
+
+/// Exclusive lock on package cache of cargo home. Cargo holds same lock while it
+/// downloads crates or updates registry index and git repositories so cargo do
+/// not modify any git repository while lock is held. Lock is released on drop
+pub struct PackageCacheLock {
+    _file: File,
+}
+
+impl PackageCacheLock {
+    /// take lock on package cache of provided cargo home. It blocks until lock
+    /// held by running cargo is released
+    pub fn acquire(cargo_home: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(cargo_home.join(".package-cache"))
+            .context("failed to open package cache lock file")?;
+        file.lock().context("failed to lock package cache")?;
+        Ok(Self { _file: file })
+    }
+}
+
+/// remove reflog of HEAD and all references so commits only reachable from reflog
+/// can be pruned
+pub fn expire_reflog(repo_path: &Path, _lock: &PackageCacheLock) -> Result<()> {
+    let repo = Repository::open(repo_path).context("failed to open git repository")?;
+    let mut names = vec!["HEAD".to_string()];
+    for reference in repo.references().context("failed to list git references")? {
+        if let Some(name) = reference?.name() {
+            names.push(name.to_string());
+        }
+    }
+    for name in names {
+        repo.reflog_delete(&name)
+            .with_context(|| format!("failed to remove reflog of {name}"))?;
+    }
+    Ok(())
+}
+
+/// pack all direct references into packed-refs file and remove loose references.
+/// Symbolic references cannot be packed so they are kept as loose references.
+/// Error is returned if packed-refs file is already locked by other process
+pub fn pack_refs(repo_path: &Path, _lock: &PackageCacheLock) -> Result<()> {
+    let repo = Repository::open(repo_path).context("failed to open git repository")?;
+    let mut packed_refs = Vec::new();
+    for reference in repo.references().context("failed to list git references")? {
+        let reference = reference?;
+        if reference.kind() != Some(ReferenceType::Direct) {
+            continue;
+        }
+        let name = reference.name_bytes().to_vec();
+        let target = reference
+            .target()
+            .context("failed to get target of direct reference")?;
+        // annotated tag is stored along with object pointed by tag
+        let object = repo.find_object(target, None)?;
+        let peeled = if object.kind() == Some(ObjectType::Tag) {
+            Some(object.peel(ObjectType::Any)?.id())
+        } else {
+            None
+        };
+        packed_refs.push((name, target, peeled));
+    }
+    packed_refs.sort();
+    let mut content = b"# pack-refs with: peeled fully-peeled sorted \n".to_vec();
+    for (name, target, peeled) in &packed_refs {
+        content.extend_from_slice(format!("{target} ").as_bytes());
+        content.extend_from_slice(name);
+        content.push(b'\n');
+        if let Some(peeled) = peeled {
+            content.extend_from_slice(format!("^{peeled}\n").as_bytes());
+        }
+    }
+    let git_dir = repo.path();
+    let lock_file = git_dir.join("packed-refs.lock");
+    // lock is created exclusively same as git so packed-refs file is not modified
+    // while other git process is updating it
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&lock_file)
+        .context("failed to lock packed-refs file")?;
+    let result = file
+        .write_all(&content)
+        .and_then(|()| file.sync_all())
+        .and_then(|()| fs::rename(&lock_file, git_dir.join("packed-refs")));
+    if let Err(e) = result {
+        let _ = fs::remove_file(&lock_file);
+        return Err(e).context("failed to replace packed-refs file");
+    }
+    for (name, target, _) in &packed_refs {
+        let Ok(name) = std::str::from_utf8(name) else {
+            continue;
+        };
+        // loose reference is only removed if it still points to packed target
+        let loose_ref = git_dir.join(name);
+        let is_packed = fs::read_to_string(&loose_ref)
+            .is_ok_and(|loose_target| loose_target.trim() == target.to_string());
+        if is_packed {
+            fs::remove_file(loose_ref).context("failed to remove loose reference")?;
+        }
+    }
+    Ok(())
+}
+
+/// write all objects reachable from HEAD and references into single pack. Old pack
+/// is only removed when all of its objects are present in new pack and loose
+/// object is only removed when it is present in new pack or it is older than
+/// grace period so objects written by other process are never pruned
+pub fn gc(repo_path: &Path, _lock: &PackageCacheLock) -> Result<()> {
+    let repo = Repository::open(repo_path).context("failed to open git repository")?;
+    let mut pack_builder = repo
+        .packbuilder()
+        .context("failed to create pack builder")?;
+    let mut rev_walk = repo.revwalk().context("failed to create rev walk")?;
+    if let Ok(commit) = repo.head().and_then(|head| head.peel_to_commit()) {
+        rev_walk.push(commit.id())?;
+    }
+    for reference in repo.references().context("failed to list git references")? {
+        let Some(target) = reference?.target() else {
+            continue;
+        };
+        let object = repo.find_object(target, None)?;
+        match object.kind() {
+            Some(ObjectType::Commit) => rev_walk.push(target)?,
+            Some(ObjectType::Tag) => {
+                pack_builder.insert_object(target, None)?;
+                let peeled = object.peel(ObjectType::Any)?;
+                if peeled.kind() == Some(ObjectType::Commit) {
+                    rev_walk.push(peeled.id())?;
+                } else {
+                    pack_builder.insert_recursive(peeled.id(), None)?;
+                }
+            }
+            _ => pack_builder.insert_recursive(target, None)?,
+        }
+    }
+    pack_builder
+        .insert_walk(&mut rev_walk)
+        .context("failed to insert reachable objects to pack")?;
+    // do not prune anything if nothing is reachable
+    if pack_builder.object_count() == 0 {
+        return Ok(());
+    }
+    let objects_dir = repo.path().join("objects");
+    let pack_dir = objects_dir.join("pack");
+    fs::create_dir_all(&pack_dir).context("failed to create pack directory")?;
+    pack_builder
+        .write(&pack_dir, 0)
+        .context("failed to write pack file")?;
+    let pack_name = format!(
+        "pack-{}",
+        pack_builder
+            .name()
+            .context("failed to get pack file name")?
+    );
+    // close repository before removing files which can be opened by repository
+    drop(pack_builder);
+    drop(rev_walk);
+    drop(repo);
+    let packed_objects = read_pack_index(&pack_dir.join(format!("{pack_name}.idx")))?
+        .into_iter()
+        .collect::<HashSet<_>>();
+    // remove old packs whose all objects are present in new pack
+    for entry in fs::read_dir(&pack_dir).context("failed to read pack directory")? {
+        let path = entry?.path();
+        let file_stem = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("");
+        if path.extension().is_none_or(|extension| extension != "idx")
+            || file_stem == pack_name
+            || path.with_extension("keep").exists()
+        {
+            continue;
+        }
+        let objects = read_pack_index(&path)?;
+        if objects.iter().all(|oid| packed_objects.contains(oid)) {
+            remove_pack(&path)?;
+        }
+    }
+    // remove loose objects which is already packed or which is older than grace
+    // period
+    for entry in fs::read_dir(&objects_dir).context("failed to read objects directory")? {
+        let path = entry?.path();
+        let file_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("")
+            .to_string();
+        if file_name.len() != 2 || !file_name.chars().all(|c| c.is_ascii_hexdigit()) {
+            continue;
+        }
+        for object in fs::read_dir(&path).context("failed to read loose objects")? {
+            let object = object?;
+            let Ok(oid) = Oid::from_str(&format!(
+                "{file_name}{}",
+                object.file_name().to_string_lossy()
+            )) else {
+                continue;
+            };
+            let age = object.metadata()?.modified()?.elapsed().unwrap_or_default();
+            if packed_objects.contains(&oid) || age >= Duration::from_secs(PRUNE_GRACE_SECONDS) {
+                fs::remove_file(object.path()).context("failed to remove loose object")?;
+            }
+        }
+        if fs::read_dir(&path).is_ok_and(|mut entries| entries.next().is_none()) {
+            fs::remove_dir(&path).context("failed to remove loose objects directory")?;
+        }
+    }
+    // commit graph is only cache which can point to pruned objects
+    for commit_graph in &["info/commit-graph", "info/commit-graphs"] {
+        let path = objects_dir.join(commit_graph);
+        if path.is_dir() {
+            fs::remove_dir_all(&path).context("failed to remove commit graph")?;
+        } else if path.is_file() {
+            fs::remove_file(&path).context("failed to remove commit graph")?;
+        }
+    }
+    Ok(())
+}
+
+// read id of all objects present in pack from pack index file. Both version 1
+// and version 2 of pack index is supported
+fn read_pack_index(path: &Path) -> Result<Vec<Oid>> {
+    const FANOUT_SIZE: usize = 256 * 4;
+    const OID_SIZE: usize = 20;
+    let content = fs::read(path).context("failed to read pack index")?;
+    let is_v2 = content.starts_with(b"\xfftOc");
+    let (fanout_start, entry_size, oid_offset) = if is_v2 {
+        if content.get(4..8) != Some(&[0, 0, 0, 2][..]) {
+            bail!("unsupported pack index version of {}", path.display());
+        }
+        (8, OID_SIZE, 0)
+    } else {
+        (0, 4 + OID_SIZE, 4)
+    };
+    let entries_start = fanout_start + FANOUT_SIZE;
+    let count = content
+        .get(entries_start - 4..entries_start)
+        .map(|count| u32::from_be_bytes([count[0], count[1], count[2], count[3]]) as usize)
+        .context("pack index is truncated")?;
+    (0..count)
+        .map(|index| {
+            let start = entries_start + index * entry_size + oid_offset;
+            let oid = content
+                .get(start..start + OID_SIZE)
+                .context("pack index is truncated")?;
+            Ok(Oid::from_bytes(oid)?)
+        })
+        .collect()
+}
+
+// remove pack along with all of its auxiliary files. Index is removed first so
+// pack is no longer used by reader before pack itself is removed
+fn remove_pack(index_path: &Path) -> Result<()> {
+    for extension in &["idx", "pack", "rev", "bitmap", "mtimes", "promisor"] {
+        let path = index_path.with_extension(extension);
+        if path.exists() {
+            fs::remove_file(&path).context("failed to remove old pack file")?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        fs::{self, File},
+        path::{Path, PathBuf},
+        time::{Duration, SystemTime},
+    };
+
+    use git2::{ObjectType, Oid, Repository, Signature, Time};
+
+    use super::{
+        expire_reflog, gc, latest_commit, pack_refs, read_pack_index, PackageCacheLock,
+        PRUNE_GRACE_SECONDS,
+    };
+    use crate::test_util::TempDir;
+
+    // create commit with provided time in repository and update provided reference
+    fn commit(repo: &Repository, reference: &str, time: i64) -> Oid {
+        let signature =
+            Signature::new("cargo-trim", "trim@example.com", &Time::new(time, 0)).unwrap();
+        let tree_id = repo.treebuilder(None).unwrap().write().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        repo.commit(
+            Some(reference),
+            &signature,
+            &signature,
+            "commit",
+            &tree,
+            &[],
+        )
+        .unwrap()
+    }
+
+    // write pack which only contains provided objects
+    fn write_pack(repo: &Repository, objects: &[Oid]) -> String {
+        let mut pack_builder = repo.packbuilder().unwrap();
+        for oid in objects {
+            pack_builder.insert_object(*oid, None).unwrap();
+        }
+        pack_builder
+            .write(&repo.path().join("objects/pack"), 0)
+            .unwrap();
+        format!("pack-{}", pack_builder.name().unwrap())
+    }
+
+    // return path of loose object
+    fn loose_object(repo_path: &Path, oid: Oid) -> PathBuf {
+        let oid = oid.to_string();
+        repo_path.join("objects").join(&oid[..2]).join(&oid[2..])
+    }
+
+    #[test]
+    fn test_git_repo() {
+        let temp_dir = TempDir::new("git-repo");
+        let repo_path = temp_dir.path();
+        // bare repository with unborn HEAD same as git db created by cargo
+        let repo = Repository::init_bare(repo_path).unwrap();
+        let lock = PackageCacheLock::acquire(repo_path).unwrap();
+        commit(&repo, "refs/remotes/origin/old", 100);
+        let latest = commit(&repo, "refs/remotes/origin/HEAD", 200);
+        repo.reference_symbolic(
+            "refs/remotes/origin/main",
+            "refs/remotes/origin/HEAD",
+            true,
+            "symbolic",
+        )
+        .unwrap();
+        assert_eq!(latest_commit(repo_path).unwrap(), Some(latest.to_string()));
+        assert_eq!(latest_commit(Path::new("/")).unwrap(), None);
+
+        expire_reflog(repo_path, &lock).unwrap();
+        pack_refs(repo_path, &lock).unwrap();
+        assert!(!repo_path.join("refs/remotes/origin/HEAD").exists());
+        let packed_refs = fs::read_to_string(repo_path.join("packed-refs")).unwrap();
+        assert!(packed_refs.contains(&format!("{latest} refs/remotes/origin/HEAD")));
+        // symbolic reference is kept as loose reference
+        assert!(repo_path.join("refs/remotes/origin/main").exists());
+        let repo = Repository::open(repo_path).unwrap();
+        let main = repo.find_reference("refs/remotes/origin/main").unwrap();
+        assert_eq!(main.resolve().unwrap().target(), Some(latest));
+
+        // packed-refs is not modified while it is locked by other process
+        File::create(repo_path.join("packed-refs.lock")).unwrap();
+        assert!(pack_refs(repo_path, &lock).is_err());
+        assert_eq!(
+            fs::read_to_string(repo_path.join("packed-refs")).unwrap(),
+            packed_refs
+        );
+    }
+
+    #[test]
+    fn test_gc() {
+        let temp_dir = TempDir::new("git-gc");
+        let repo_path = temp_dir.path();
+        let repo = Repository::init_bare(repo_path).unwrap();
+        let lock = PackageCacheLock::acquire(repo_path).unwrap();
+        let signature =
+            Signature::new("cargo-trim", "trim@example.com", &Time::new(100, 0)).unwrap();
+
+        // commit with real blob and tree along with annotated tag
+        let blob = repo.blob(b"fn main() {}").unwrap();
+        let mut tree_builder = repo.treebuilder(None).unwrap();
+        tree_builder.insert("main.rs", blob, 0o100_644).unwrap();
+        let tree = repo.find_tree(tree_builder.write().unwrap()).unwrap();
+        let first = repo
+            .commit(
+                Some("refs/heads/main"),
+                &signature,
+                &signature,
+                "first",
+                &tree,
+                &[],
+            )
+            .unwrap();
+        let first_commit = repo.find_commit(first).unwrap();
+        let tag = repo
+            .tag("v1", first_commit.as_object(), &signature, "v1", false)
+            .unwrap();
+        let first_pack = write_pack(&repo, &[first, tree.id(), blob, tag]);
+
+        // second commit and its objects are present in another pack
+        let second_blob = repo.blob(b"fn lib() {}").unwrap();
+        let mut tree_builder = repo.treebuilder(Some(&tree)).unwrap();
+        tree_builder
+            .insert("lib.rs", second_blob, 0o100_644)
+            .unwrap();
+        let second_tree = repo.find_tree(tree_builder.write().unwrap()).unwrap();
+        let second = repo
+            .commit(
+                Some("refs/heads/main"),
+                &signature,
+                &signature,
+                "second",
+                &second_tree,
+                &[&first_commit],
+            )
+            .unwrap();
+        let second_pack = write_pack(&repo, &[second, second_tree.id(), second_blob]);
+
+        // pack which contains unreachable object is never removed
+        let unreachable_packed = repo.blob(b"unreachable packed").unwrap();
+        let unreachable_pack = write_pack(&repo, &[unreachable_packed, blob]);
+        // new unreachable loose object is kept and old one is pruned
+        let new_loose = repo.blob(b"new loose").unwrap();
+        let old_loose = repo.blob(b"old loose").unwrap();
+        let old_time = SystemTime::now() - Duration::from_secs(PRUNE_GRACE_SECONDS + 60);
+        File::options()
+            .write(true)
+            .open(loose_object(repo_path, old_loose))
+            .unwrap()
+            .set_modified(old_time)
+            .unwrap();
+
+        gc(repo_path, &lock).unwrap();
+        let pack_dir = repo_path.join("objects/pack");
+        assert!(!pack_dir.join(format!("{first_pack}.pack")).exists());
+        assert!(!pack_dir.join(format!("{second_pack}.idx")).exists());
+        assert!(pack_dir.join(format!("{unreachable_pack}.pack")).exists());
+        assert!(!loose_object(repo_path, blob).exists());
+        assert!(!loose_object(repo_path, old_loose).exists());
+        assert!(loose_object(repo_path, new_loose).exists());
+        let packs = fs::read_dir(&pack_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "idx"))
+            .collect::<Vec<_>>();
+        assert_eq!(packs.len(), 2);
+        let objects = packs
+            .iter()
+            .flat_map(|path| read_pack_index(path).unwrap())
+            .collect::<Vec<_>>();
+        assert!(objects.contains(&unreachable_packed));
+
+        // all reachable objects can still be read
+        let repo = Repository::open(repo_path).unwrap();
+        assert_eq!(repo.find_blob(blob).unwrap().content(), b"fn main() {}");
+        assert_eq!(
+            repo.find_blob(second_blob).unwrap().content(),
+            b"fn lib() {}"
+        );
+        assert_eq!(
+            repo.find_commit(second).unwrap().parent_id(0).unwrap(),
+            first
+        );
+        let tag = repo.find_tag(tag).unwrap();
+        assert_eq!(tag.target_type(), Some(ObjectType::Commit));
+        assert_eq!(tag.target_id(), first);
+        assert!(repo.find_blob(new_loose).is_ok());
+        assert!(repo.find_blob(old_loose).is_err());
+    }
+}
@@ -0,0 +1,692 @@
+// Packages installed by cargo install. Cargo tracks installed packages and
+// binaries owned by them in .crates.toml and .crates2.json file of cargo home.
+// Both files are read and updated as generic value so fields which are not used
+// by cargo trim are preserved. Same as cargo both files are locked exclusively
+// while they are read and updated
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value as JsonValue;
+use toml::Value as TomlValue;
+
+use crate::{
+    dir_path::DirPath,
+    journal::Journal,
+    trash::{remove_path, Trash},
+};
+
+// binaries which rustup installs in bin directory as proxy of toolchain
+const RUSTUP_PROXIES: [&str; 14] = [
+    "rustup",
+    "cargo",
+    "cargo-clippy",
+    "cargo-fmt",
+    "cargo-miri",
+    "clippy-driver",
+    "rls",
+    "rust-analyzer",
+    "rust-gdb",
+    "rust-gdbgui",
+    "rust-lldb",
+    "rustc",
+    "rustdoc",
+    "rustfmt",
+];
+
+/// Classification of binary present in bin directory or install metadata
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinStatus {
+    /// binary is present and owned by installed package
+    Tracked,
+    /// binary is present but not owned by any installed package
+    Untracked,
+    /// binary is rustup itself or proxy installed by rustup
+    RustupProxy,
+    /// binary is owned by installed package but not present in bin directory
+    Missing,
+}
+
+impl BinStatus {
+    /// return name of status
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Tracked => "tracked",
+            Self::Untracked => "untracked",
+            Self::RustupProxy => "rustup-proxy",
+            Self::Missing => "missing",
+        }
+    }
+}
+
+/// Single binary present in bin directory or install metadata
+#[derive(Clone, Debug)]
+pub struct BinEntry {
+    name: String,
+    path: PathBuf,
+    status: BinStatus,
+    package: Option<String>,
+}
+
+impl BinEntry {
+    /// return file name of binary
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// return path of binary in bin directory
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// return classification of binary
+    pub fn status(&self) -> BinStatus {
+        self.status
+    }
+
+    /// return name of package which owns binary
+    pub fn package(&self) -> Option<&str> {
+        self.package.as_deref()
+    }
+}
+
+/// Single package installed by cargo install
+#[derive(Clone, Debug)]
+pub struct InstalledPackage {
+    id: String,
+    name: String,
+    version: String,
+    source: String,
+    bins: Vec<String>,
+    features: Vec<String>,
+    all_features: bool,
+    no_default_features: bool,
+    profile: Option<String>,
+    target: Option<String>,
+    rustc: Option<String>,
+}
+
+impl InstalledPackage {
+    // create package from package id such as ripgrep 13.0.0
+    // (registry+https://github.com/rust-lang/crates.io-index)
+    fn new(id: &str) -> Self {
+        let mut parts = id.splitn(3, ' ');
+        let name = parts.next().unwrap_or_default().to_string();
+        let version = parts.next().unwrap_or_default().to_string();
+        let source = parts
+            .next()
+            .unwrap_or_default()
+            .trim_start_matches('(')
+            .trim_end_matches(')')
+            .to_string();
+        Self {
+            id: id.to_string(),
+            name,
+            version,
+            source,
+            bins: Vec::new(),
+            features: Vec::new(),
+            all_features: false,
+            no_default_features: false,
+            profile: None,
+            target: None,
+            rustc: None,
+        }
+    }
+
+    /// return package id used as key in metadata files
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// return name of package
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// return version of package
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// return source of package such as `registry+https://github.com/rust-lang/crates.io-index`
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// return binaries owned by package
+    pub fn bins(&self) -> &[String] {
+        &self.bins
+    }
+
+    /// return features enabled while installing package
+    pub fn features(&self) -> &[String] {
+        &self.features
+    }
+
+    /// return if package was installed with all features
+    pub fn all_features(&self) -> bool {
+        self.all_features
+    }
+
+    /// return if package was installed without default features
+    pub fn no_default_features(&self) -> bool {
+        self.no_default_features
+    }
+
+    /// return profile used while installing package
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    /// return target triple for which package was installed
+    pub fn target(&self) -> Option<&str> {
+        self.target.as_deref()
+    }
+
+    /// return version of rustc used while installing package
+    pub fn rustc(&self) -> Option<&str> {
+        self.rustc.as_deref()
+    }
+}
+
+/// Stores install metadata of .crates.toml and .crates2.json file. Both files
+/// are locked until tracker is dropped
+pub struct InstallTracker {
+    bin_dir: PathBuf,
+    crates_toml: Option<File>,
+    crates2_json: Option<File>,
+    v1: Option<TomlValue>,
+    v2: Option<JsonValue>,
+}
+
+impl InstallTracker {
+    /// read install metadata of cargo home. Metadata file which does not exist is
+    /// treated as empty. Same lock as cargo install and cargo uninstall is taken on
+    /// metadata files so it blocks until running cargo install is finished
+    pub fn new(dir_path: &DirPath) -> Result<Self> {
+        let crates_toml = open_locked(&dir_path.home_dir().join(".crates.toml"))
+            .context("failed to lock .crates.toml file")?;
+        let crates2_json = open_locked(&dir_path.home_dir().join(".crates2.json"))
+            .context("failed to lock .crates2.json file")?;
+        let v1 = match &crates_toml {
+            Some(file) => {
+                let content = read_file(file).context("failed to read .crates.toml file")?;
+                Some(toml::from_str(&content).context("failed to parse .crates.toml file")?)
+            }
+            None => None,
+        };
+        let v2 = match &crates2_json {
+            Some(file) => {
+                let content = read_file(file).context("failed to read .crates2.json file")?;
+                Some(serde_json::from_str(&content).context("failed to parse .crates2.json file")?)
+            }
+            None => None,
+        };
+        Ok(Self {
+            bin_dir: dir_path.bin_dir().clone(),
+            crates_toml,
+            crates2_json,
+            v1,
+            v2,
+        })
+    }
+
+    /// list all installed packages sorted by name. Details of .crates2.json is
+    /// preferred and package which is only present in .crates.toml only has
+    /// binaries
+    pub fn packages(&self) -> Vec<InstalledPackage> {
+        let mut packages: Vec<InstalledPackage> = Vec::new();
+        if let Some(installs) = self.v2_installs() {
+            for (id, install) in installs {
+                let mut package = InstalledPackage::new(id);
+                package.bins = json_strings(install.get("bins"));
+                package.features = json_strings(install.get("features"));
+                package.all_features = install
+                    .get("all_features")
+                    .and_then(JsonValue::as_bool)
+                    .unwrap_or(false);
+                package.no_default_features = install
+                    .get("no_default_features")
+                    .and_then(JsonValue::as_bool)
+                    .unwrap_or(false);
+                package.profile = json_string(install.get("profile"));
+                package.target = json_string(install.get("target"));
+                package.rustc = json_string(install.get("rustc"));
+                packages.push(package);
+            }
+        }
+        if let Some(v1) = self.v1_table() {
+            for (id, bins) in v1 {
+                if packages.iter().any(|package| package.id == *id) {
+                    continue;
+                }
+                let mut package = InstalledPackage::new(id);
+                package.bins = bins
+                    .as_array()
+                    .map(|bins| {
+                        bins.iter()
+                            .filter_map(|bin| bin.as_str().map(ToString::to_string))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                packages.push(package);
+            }
+        }
+        packages.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+        packages
+    }
+
+    /// return path of binary present in bin directory
+    pub fn bin_path(&self, bin: &str) -> PathBuf {
+        self.bin_dir.join(bin)
+    }
+
+    /// uninstall package by removing all of its binaries and its entry from both
+    /// metadata files. Binaries are moved to trash if trash is provided and their
+    /// removal is recorded in journal if journal is provided. If some binaries
+    /// failed to be removed only removed binaries are dropped from metadata and
+    /// first error is returned. Nothing is changed for dry run
+    pub fn uninstall(
+        &mut self,
+        package: &InstalledPackage,
+        trash: Option<&Trash>,
+        journal: Option<&Journal>,
+        dry_run: bool,
+    ) -> Result<()> {
+        let mut result = Ok(());
+        let mut removed = Vec::new();
+        for bin in package.bins() {
+            let bin_result =
+                remove_path(&self.bin_path(bin), package.name(), trash, journal, dry_run)
+                    .with_context(|| format!("failed to remove binary {bin}"));
+            if bin_result.is_ok() {
+                removed.push(bin.clone());
+            }
+            result = result.and(bin_result);
+        }
+        if dry_run {
+            return result;
+        }
+        if removed.len() == package.bins().len() {
+            self.remove_entry(package.id())?;
+        } else if !removed.is_empty() {
+            self.remove_bins(package.id(), &removed);
+            self.save()?;
+        }
+        result
+    }
+
+    /// classify every binary present in bin directory as well as every binary
+    /// owned by installed packages. Entries are sorted by name
+    pub fn check(&self) -> Result<Vec<BinEntry>> {
+        let packages = self.packages();
+        let mut entries = Vec::new();
+        if self.bin_dir.exists() {
+            for entry in fs::read_dir(&self.bin_dir).context("failed to read bin directory")? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    continue;
+                }
+                let name = path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string();
+                let package = packages
+                    .iter()
+                    .find(|package| package.bins().contains(&name));
+                let status = if package.is_some() {
+                    BinStatus::Tracked
+                } else if is_rustup_proxy(&path) {
+                    BinStatus::RustupProxy
+                } else {
+                    BinStatus::Untracked
+                };
+                entries.push(BinEntry {
+                    name,
+                    path,
+                    status,
+                    package: package.map(|package| package.name().to_string()),
+                });
+            }
+        }
+        for package in &packages {
+            for bin in package.bins() {
+                let path = self.bin_path(bin);
+                if !path.exists() {
+                    entries.push(BinEntry {
+                        name: bin.clone(),
+                        path,
+                        status: BinStatus::Missing,
+                        package: Some(package.name().to_string()),
+                    });
+                }
+            }
+        }
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(entries)
+    }
+
+    /// repair install metadata by removing binaries which are not present in bin
+    /// directory from both metadata files. Package whose all binaries are missing is
+    /// removed. Return list of removed binaries along with name of their package.
+    /// Nothing is changed for dry run
+    pub fn repair(&mut self, dry_run: bool) -> Result<Vec<(String, String)>> {
+        let mut repaired = Vec::new();
+        for package in self.packages() {
+            let missing = package
+                .bins()
+                .iter()
+                .filter(|bin| !self.bin_path(bin).exists())
+                .cloned()
+                .collect::<Vec<_>>();
+            if missing.is_empty() {
+                continue;
+            }
+            if !dry_run {
+                self.remove_bins(package.id(), &missing);
+            }
+            for bin in missing {
+                repaired.push((package.name().to_string(), bin));
+            }
+        }
+        if !dry_run && !repaired.is_empty() {
+            self.save()?;
+        }
+        Ok(repaired)
+    }
+
+    // remove binaries from package entry of both metadata files. Entry without
+    // any remaining binary is removed
+    fn remove_bins(&mut self, id: &str, bins: &[String]) {
+        if let Some(v1) = self
+            .v1
+            .as_mut()
+            .and_then(|v1| v1.get_mut("v1"))
+            .and_then(TomlValue::as_table_mut)
+        {
+            if let Some(values) = v1.get_mut(id).and_then(TomlValue::as_array_mut) {
+                values.retain(|value| {
+                    value
+                        .as_str()
+                        .is_none_or(|bin| !bins.iter().any(|b| b == bin))
+                });
+                if values.is_empty() {
+                    v1.remove(id);
+                }
+            }
+        }
+        if let Some(installs) = self
+            .v2
+            .as_mut()
+            .and_then(|v2| v2.get_mut("installs"))
+            .and_then(JsonValue::as_object_mut)
+        {
+            if let Some(values) = installs
+                .get_mut(id)
+                .and_then(|install| install.get_mut("bins"))
+                .and_then(JsonValue::as_array_mut)
+            {
+                values.retain(|value| {
+                    value
+                        .as_str()
+                        .is_none_or(|bin| !bins.iter().any(|b| b == bin))
+                });
+                if values.is_empty() {
+                    installs.remove(id);
+                }
+            }
+        }
+    }
+
+    /// remove binary which is not owned by any installed package from bin
+    /// directory
+    pub fn remove_untracked(
+        &self,
+        entry: &BinEntry,
+        trash: Option<&Trash>,
+        journal: Option<&Journal>,
+        dry_run: bool,
+    ) -> Result<()> {
+        if entry.status() != BinStatus::Untracked {
+            bail!("binary {:?} is not untracked", entry.name());
+        }
+        remove_path(entry.path(), entry.name(), trash, journal, dry_run)
+    }
+
+    // remove package entry from both metadata files and save them
+    fn remove_entry(&mut self, id: &str) -> Result<()> {
+        if let Some(v1) = self
+            .v1
+            .as_mut()
+            .and_then(|v1| v1.get_mut("v1"))
+            .and_then(TomlValue::as_table_mut)
+        {
+            v1.remove(id);
+        }
+        if let Some(installs) = self
+            .v2
+            .as_mut()
+            .and_then(|v2| v2.get_mut("installs"))
+            .and_then(JsonValue::as_object_mut)
+        {
+            installs.remove(id);
+        }
+        self.save()
+    }
+
+    // save metadata to locked .crates.toml and .crates2.json file
+    fn save(&self) -> Result<()> {
+        if let (Some(v1), Some(file)) = (&self.v1, &self.crates_toml) {
+            let content = toml::to_string(v1).context("failed to serialize .crates.toml file")?;
+            write_file(file, &content).context("failed to write .crates.toml file")?;
+        }
+        if let (Some(v2), Some(file)) = (&self.v2, &self.crates2_json) {
+            let content =
+                serde_json::to_string(v2).context("failed to serialize .crates2.json file")?;
+            write_file(file, &content).context("failed to write .crates2.json file")?;
+        }
+        Ok(())
+    }
+
+    // return installs object of .crates2.json
+    fn v2_installs(&self) -> Option<&serde_json::Map<String, JsonValue>> {
+        self.v2.as_ref()?.get("installs")?.as_object()
+    }
+
+    // return v1 table of .crates.toml
+    fn v1_table(&self) -> Option<&toml::value::Table> {
+        self.v1.as_ref()?.get("v1")?.as_table()
+    }
+}
+
+// check if binary is rustup or its proxy. Proxy is either symlink to rustup or
+// copy of rustup with name of toolchain binary
+fn is_rustup_proxy(path: &Path) -> bool {
+    let Some(stem) = path.file_stem().map(|stem| stem.to_string_lossy()) else {
+        return false;
+    };
+    if let Ok(link) = fs::read_link(path) {
+        return link
+            .file_stem()
+            .is_some_and(|link_stem| link_stem == "rustup");
+    }
+    RUSTUP_PROXIES.contains(&stem.as_ref())
+}
+
+// convert json array value to list of string
+fn json_strings(value: Option<&JsonValue>) -> Vec<String> {
+    value
+        .and_then(JsonValue::as_array)
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|value| value.as_str().map(ToString::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// convert json string value to string
+fn json_string(value: Option<&JsonValue>) -> Option<String> {
+    value.and_then(JsonValue::as_str).map(ToString::to_string)
+}
+
+// open metadata file for read and write and take exclusive lock on it which is
+// same lock taken by cargo. File is not replaced while saving since lock is held
+// on opened file. File which does not exist is not created
+fn open_locked(path: &Path) -> io::Result<Option<File>> {
+    let file = match OpenOptions::new().read(true).write(true).open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    file.lock()?;
+    Ok(Some(file))
+}
+
+// read content of locked file from start
+fn read_file(mut file: &File) -> io::Result<String> {
+    let mut content = String::new();
+    file.seek(SeekFrom::Start(0))?;
+    file.read_to_string(&mut content)?;
+    Ok(content)
+}
+
+// replace content of locked file
+fn write_file(mut file: &File, content: &str) -> io::Result<()> {
+    file.seek(SeekFrom::Start(0))?;
+    file.set_len(0)?;
+    file.write_all(content.as_bytes())?;
+    file.flush()
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::{BinStatus, InstallTracker};
+    use crate::test_util::{dir_path, TempDir};
+
+    // test listing and uninstalling packages installed by cargo install
+    #[test]
+    fn test_install_tracker() {
+        let cargo_home = TempDir::new("bin");
+        let bin_dir = cargo_home.path().join("bin");
+        fs::create_dir_all(&bin_dir).unwrap();
+        fs::write(bin_dir.join("rg"), "binary").unwrap();
+        fs::write(bin_dir.join("tool"), "binary").unwrap();
+        let ripgrep = "ripgrep 13.0.0 (registry+https://github.com/rust-lang/crates.io-index)";
+        let tool = "tool 0.1.0 (path+file:///tmp/tool)";
+        fs::write(
+            cargo_home.path().join(".crates.toml"),
+            format!("[v1]\n\"{ripgrep}\" = [\"rg\"]\n\"{tool}\" = [\"tool\"]\n"),
+        )
+        .unwrap();
+        fs::write(
+            cargo_home.path().join(".crates2.json"),
+            format!(
+                r#"{{"installs":{{"{ripgrep}":{{"bins":["rg"],"features":["pcre2"],"all_features":false,"no_default_features":true,"profile":"release","target":null,"rustc":null,"extra":1}}}}}}"#
+            ),
+        )
+        .unwrap();
+        let dir_path = dir_path(&cargo_home);
+        let mut install_tracker = InstallTracker::new(&dir_path).unwrap();
+        let packages = install_tracker.packages();
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].name(), "ripgrep");
+        assert_eq!(packages[0].version(), "13.0.0");
+        assert_eq!(
+            packages[0].source(),
+            "registry+https://github.com/rust-lang/crates.io-index"
+        );
+        assert_eq!(packages[0].bins(), ["rg".to_string()]);
+        assert_eq!(packages[0].features(), ["pcre2".to_string()]);
+        assert!(packages[0].no_default_features());
+        assert_eq!(packages[0].profile(), Some("release"));
+        assert_eq!(packages[1].name(), "tool");
+        assert_eq!(packages[1].bins(), ["tool".to_string()]);
+        install_tracker
+            .uninstall(&packages[0], None, None, true)
+            .unwrap();
+        assert!(bin_dir.join("rg").exists());
+        install_tracker
+            .uninstall(&packages[0], None, None, false)
+            .unwrap();
+        assert!(!bin_dir.join("rg").exists());
+        assert!(bin_dir.join("tool").exists());
+        // metadata is locked same as cargo until tracker is dropped
+        let locked_file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(cargo_home.path().join(".crates2.json"))
+            .unwrap();
+        assert!(locked_file.try_lock().is_err());
+        drop(install_tracker);
+        assert!(locked_file.try_lock().is_ok());
+        drop(locked_file);
+        let crates_toml = fs::read_to_string(cargo_home.path().join(".crates.toml")).unwrap();
+        assert!(!crates_toml.contains("ripgrep") && crates_toml.contains("tool"));
+        let crates2_json = fs::read_to_string(cargo_home.path().join(".crates2.json")).unwrap();
+        assert_eq!(crates2_json, r#"{"installs":{}}"#);
+        let packages = InstallTracker::new(&dir_path).unwrap().packages();
+        assert_eq!(packages.len(), 1);
+    }
+
+    // test classifying binaries of bin directory and repairing install metadata
+    #[test]
+    fn test_bin_check() {
+        let cargo_home = TempDir::new("bin-check");
+        let bin_dir = cargo_home.path().join("bin");
+        fs::create_dir_all(&bin_dir).unwrap();
+        for bin in &["rg", "handmade", "rustup", "cargo"] {
+            fs::write(bin_dir.join(bin), "binary").unwrap();
+        }
+        let ripgrep = "ripgrep 13.0.0 (registry+https://github.com/rust-lang/crates.io-index)";
+        let fd = "fd-find 8.7.0 (registry+https://github.com/rust-lang/crates.io-index)";
+        fs::write(
+            cargo_home.path().join(".crates2.json"),
+            format!(
+                r#"{{"installs":{{"{ripgrep}":{{"bins":["rg","rga"]}},"{fd}":{{"bins":["fd"]}}}}}}"#
+            ),
+        )
+        .unwrap();
+        let dir_path = dir_path(&cargo_home);
+        let mut install_tracker = InstallTracker::new(&dir_path).unwrap();
+        let entries = install_tracker
+            .check()
+            .unwrap()
+            .iter()
+            .map(|entry| (entry.name().to_string(), entry.status()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            vec![
+                ("cargo".to_string(), BinStatus::RustupProxy),
+                ("fd".to_string(), BinStatus::Missing),
+                ("handmade".to_string(), BinStatus::Untracked),
+                ("rg".to_string(), BinStatus::Tracked),
+                ("rga".to_string(), BinStatus::Missing),
+                ("rustup".to_string(), BinStatus::RustupProxy),
+            ]
+        );
+        for entry in install_tracker.check().unwrap() {
+            let result = install_tracker.remove_untracked(&entry, None, None, false);
+            assert_eq!(result.is_ok(), entry.status() == BinStatus::Untracked);
+        }
+        assert!(!bin_dir.join("handmade").exists());
+        assert!(bin_dir.join("rg").exists());
+        assert_eq!(install_tracker.repair(true).unwrap().len(), 2);
+        assert_eq!(install_tracker.packages().len(), 2);
+        assert_eq!(install_tracker.repair(false).unwrap().len(), 2);
+        drop(install_tracker);
+        let packages = InstallTracker::new(&dir_path).unwrap().packages();
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].bins(), ["rg".to_string()]);
+    }
+}
@@ -0,0 +1,265 @@
+// Journal of all removal made by cargo trim. Each removed file or folder is
+// appended as single line of json to journal file so past runs can be audited
+
+use std::{
+    collections::HashMap,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::trash::{remove_path, Trash};
+
+/// Entry of single file or folder removed by cargo trim
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JournalEntry {
+    run: String,
+    timestamp: u64,
+    reason: String,
+    #[serde(rename = "crate")]
+    crate_name: String,
+    path: PathBuf,
+    size: u64,
+    trashed: bool,
+}
+
+impl JournalEntry {
+    /// return id of run which removed entry
+    pub fn run(&self) -> &str {
+        &self.run
+    }
+
+    /// return seconds since unix epoch when entry was removed
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// return reason for which entry was removed such as old or orphan
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+
+    /// return name of crate or folder removed
+    pub fn crate_name(&self) -> &str {
+        &self.crate_name
+    }
+
+    /// return path of removed file or folder
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// return size of removed file or folder in bytes
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// return if entry was moved to trash instead of being deleted
+    pub fn trashed(&self) -> bool {
+        self.trashed
+    }
+}
+
+/// All entries removed during single run
+pub struct JournalRun {
+    run: String,
+    entries: Vec<JournalEntry>,
+}
+
+impl JournalRun {
+    /// return id of run
+    pub fn run(&self) -> &str {
+        &self.run
+    }
+
+    /// return seconds since unix epoch when first entry of run was removed
+    pub fn timestamp(&self) -> u64 {
+        self.entries.first().map_or(0, JournalEntry::timestamp)
+    }
+
+    /// return list of unique reasons of run in order of removal
+    pub fn reasons(&self) -> Vec<&str> {
+        let mut reasons = Vec::new();
+        for entry in &self.entries {
+            if !reasons.contains(&entry.reason()) {
+                reasons.push(entry.reason());
+            }
+        }
+        reasons
+    }
+
+    /// return entries removed during run
+    pub fn entries(&self) -> &[JournalEntry] {
+        &self.entries
+    }
+
+    /// return total size of all entries of run in bytes
+    pub fn size(&self) -> u64 {
+        self.entries.iter().map(JournalEntry::size).sum()
+    }
+}
+
+/// Stores journal file location along with current run and reason of removal
+pub struct Journal {
+    path: PathBuf,
+    run: String,
+    reason: Mutex<String>,
+}
+
+impl Journal {
+    /// create new journal which appends entries to provided file
+    pub fn new(journal_file: &Path) -> Self {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        Self {
+            path: journal_file.to_path_buf(),
+            run: format!("{}-{}", seconds, std::process::id()),
+            reason: Mutex::new("remove".to_string()),
+        }
+    }
+
+    /// return id of current run
+    pub fn run(&self) -> &str {
+        &self.run
+    }
+
+    /// set reason which is recorded for all removal done after this call
+    pub fn set_reason(&self, reason: &str) {
+        *self.reason.lock().unwrap() = reason.to_string();
+    }
+
+    // append removal of file or folder to journal
+    pub(crate) fn record(
+        &self,
+        crate_name: &str,
+        path: &Path,
+        size: u64,
+        trashed: bool,
+    ) -> Result<()> {
+        let entry = JournalEntry {
+            run: self.run.clone(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            reason: self.reason.lock().unwrap().clone(),
+            crate_name: crate_name.to_string(),
+            path: path.to_path_buf(),
+            size,
+            trashed,
+        };
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).context("failed to create journal folder")?;
+        }
+        let mut content =
+            serde_json::to_string(&entry).context("failed to serialize journal entry")?;
+        content.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(content.as_bytes()))
+            .context("failed to append journal entry")?;
+        Ok(())
+    }
+
+    /// delete folder and record it in journal. Folder is moved to trash if trash is
+    /// provided. Nothing is deleted or recorded for dry run
+    pub fn remove_folder(
+        &self,
+        name: &str,
+        path: &Path,
+        trash: Option<&Trash>,
+        dry_run: bool,
+    ) -> Result<()> {
+        remove_path(path, name, trash, Some(self), dry_run)
+    }
+
+    /// list all entries present in journal. Line which cannot be parsed is skipped
+    pub fn list_entries(&self) -> Result<Vec<JournalEntry>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(&self.path).context("failed to read journal file")?;
+        Ok(content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    /// list all runs present in journal from oldest to newest
+    pub fn list_runs(&self) -> Result<Vec<JournalRun>> {
+        let mut runs: Vec<JournalRun> = Vec::new();
+        // position of run in list of runs
+        let mut run_position: HashMap<String, usize> = HashMap::new();
+        for entry in self.list_entries()? {
+            if let Some(&position) = run_position.get(&entry.run) {
+                runs[position].entries.push(entry);
+            } else {
+                run_position.insert(entry.run.clone(), runs.len());
+                runs.push(JournalRun {
+                    run: entry.run.clone(),
+                    entries: vec![entry],
+                });
+            }
+        }
+        runs.sort_by_key(JournalRun::timestamp);
+        Ok(runs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::Journal;
+    use crate::{test_util::TempDir, trash::Trash};
+
+    #[test]
+    fn test_journal() {
+        let temp_dir = TempDir::new("journal");
+        let folder = temp_dir.path().join("checkouts");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("file"), "content").unwrap();
+        let journal = Journal::new(&temp_dir.path().join("journal.jsonl"));
+
+        journal
+            .remove_folder("checkouts", &folder, None, true)
+            .unwrap();
+        assert!(folder.exists());
+        assert!(journal.list_runs().unwrap().is_empty());
+
+        journal.set_reason("wipe");
+        journal
+            .remove_folder("checkouts", &folder, None, false)
+            .unwrap();
+        assert!(!folder.exists());
+        let runs = journal.list_runs().unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].run(), journal.run());
+        assert_eq!(runs[0].reasons(), vec!["wipe"]);
+        assert_eq!(runs[0].size(), 7);
+        assert_eq!(runs[0].entries()[0].crate_name(), "checkouts");
+        assert_eq!(runs[0].entries()[0].path(), folder);
+
+        // folder removed with trash can be restored
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("file"), "content").unwrap();
+        let trash = Trash::new(&temp_dir.path().join("trash"));
+        journal
+            .remove_folder("checkouts", &folder, Some(&trash), false)
+            .unwrap();
+        assert!(!folder.exists());
+        assert!(journal.list_entries().unwrap()[1].trashed());
+        let batches = trash.list_batches().unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].entries()[0].original_path(), folder);
+    }
+}
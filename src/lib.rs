@@ -0,0 +1,50 @@
+//! Library interface of cargo-trim
+//!
+//! Provides types used to locate cargo home directories, scan installed
+//! registry and git crates, classify them and remove them. Nothing in this
+//! crate prints to stdout; callers decide how to present returned values.
+#![warn(unreachable_pub, anonymous_parameters, bare_trait_objects)]
+#![deny(unsafe_code)]
+#![deny(clippy::all)]
+#![warn(clippy::pedantic)]
+#![allow(
+    clippy::too_many_lines,
+    clippy::struct_excessive_bools,
+    clippy::missing_errors_doc,
+    clippy::missing_panics_doc,
+    clippy::must_use_candidate,
+    clippy::module_name_repetitions
+)]
+
+mod config_file;
+mod crate_detail;
+mod crate_selector;
+mod dir_path;
+mod git_dir;
+pub mod git_repo;
+mod install_tracker;
+mod journal;
+mod list_crate;
+mod progress;
+mod registry_dir;
+mod registry_source;
+mod source_hash;
+mod target_dir;
+#[cfg(test)]
+mod test_util;
+mod trash;
+pub mod utils;
+
+pub use config_file::ConfigFile;
+pub use crate_detail::CrateDetail;
+pub use crate_selector::CrateSelector;
+pub use dir_path::DirPath;
+pub use git_dir::GitDir;
+pub use install_tracker::{BinEntry, BinStatus, InstallTracker, InstalledPackage};
+pub use journal::{Journal, JournalEntry, JournalRun};
+pub use list_crate::{CargoTomlLocation, CrateKind, CrateList, OldMode};
+pub use progress::Progress;
+pub use registry_dir::RegistryDir;
+pub use registry_source::RegistrySource;
+pub use target_dir::{TargetArtifact, TargetDir};
+pub use trash::{Trash, TrashBatch, TrashEntry};
@@ -1,35 +1,45 @@
 use std::{
+    collections::{HashMap, HashSet},
     fs,
     path::{Path, PathBuf},
+    str::FromStr,
+    time::{Duration, SystemTime},
 };
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
+use semver::Version;
 use serde::Deserialize;
 
 use crate::{
-    config_file::ConfigFile, crate_detail::CrateDetail, dir_path::DirPath,
+    config_file::ConfigFile,
+    crate_detail::CrateDetail,
+    dir_path::DirPath,
+    git_repo::latest_commit,
+    registry_source::{list_registry_dir_name, lock_source_registry_dirs},
+    source_hash::git_dir_names,
     utils::clear_version_value,
 };
 
-// struct store Cargo.toml file location
-pub(crate) struct CargoTomlLocation {
+/// struct store Cargo.toml file location
+#[derive(Default)]
+pub struct CargoTomlLocation {
     path: Vec<PathBuf>,
 }
 
 impl CargoTomlLocation {
-    pub(crate) fn new() -> Self {
+    pub fn new() -> Self {
         Self { path: Vec::new() }
     }
 
-    pub(crate) fn add_path(&mut self, path: PathBuf) {
+    pub fn add_path(&mut self, path: PathBuf) {
         self.path.push(path);
     }
 
-    pub(crate) fn append(&mut self, mut lock_location: Self) {
+    pub fn append(&mut self, mut lock_location: Self) {
         self.path.append(&mut lock_location.path);
     }
 
-    pub(crate) fn location_path(&self) -> &Vec<PathBuf> {
+    pub fn location_path(&self) -> &Vec<PathBuf> {
         &self.path
     }
 }
@@ -40,8 +50,8 @@ struct LockData {
 }
 
 impl LockData {
-    fn package(&self) -> &Option<Vec<Package>> {
-        &self.package
+    fn package(&self) -> Option<&Vec<Package>> {
+        self.package.as_ref()
     }
 }
 
@@ -61,13 +71,72 @@ impl Package {
         &self.version
     }
 
-    fn source(&self) -> &Option<String> {
-        &self.source
+    fn source(&self) -> Option<&String> {
+        self.source.as_ref()
     }
 }
 
-// struct to store all crate list detail with its type
-pub(crate) struct CrateList {
+/// Mode used for classifying registry crate version as old
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OldMode {
+    /// version is old if newer version of same crate is present
+    #[default]
+    Latest,
+    /// version is old only if newer semver compatible version of same crate is
+    /// present
+    Semver,
+}
+
+impl FromStr for OldMode {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value {
+            "latest" => Ok(Self::Latest),
+            "semver" => Ok(Self::Semver),
+            _ => bail!(
+                "invalid old mode {:?}, possible values are latest and semver",
+                value
+            ),
+        }
+    }
+}
+
+/// Location of cargo home in which crate is present
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CrateKind {
+    /// crate downloaded from registry and stored in registry cache and src folder
+    Registry,
+    /// crate fetched from git repository and stored in git db and checkouts folder
+    Git,
+}
+
+impl CrateKind {
+    /// return lowercase name of crate kind
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Registry => "registry",
+            Self::Git => "git",
+        }
+    }
+
+    /// split full name of crate into name and version. Version of git crate is
+    /// checkout rev or HEAD for git db
+    pub fn split_name(self, crate_full_name: &str) -> (String, String) {
+        match self {
+            Self::Registry => clear_version_value(crate_full_name),
+            Self::Git => {
+                let (name, rev) = crate_full_name
+                    .rsplit_once('-')
+                    .unwrap_or((crate_full_name, ""));
+                (name.to_string(), rev.to_string())
+            }
+        }
+    }
+}
+
+/// struct to store all crate list detail with its type
+pub struct CrateList {
     installed_bin: Vec<String>,
     installed_crate_registry: Vec<String>,
     installed_crate_git: Vec<String>,
@@ -77,16 +146,21 @@ pub(crate) struct CrateList {
     used_crate_git: Vec<String>,
     orphan_crate_registry: Vec<String>,
     orphan_crate_git: Vec<String>,
+    used_crate_registry_dir: HashMap<String, Vec<String>>,
+    orphan_crate_registry_dir: HashMap<String, Vec<String>>,
+    used_crate_git_dir: HashMap<String, Vec<String>>,
+    orphan_crate_git_dir: HashMap<String, Vec<String>>,
     cargo_toml_location: CargoTomlLocation,
 }
 
 impl CrateList {
-    // create list of all types of crate present in directory
+    /// create list of all types of crate present in directory
     #[allow(clippy::too_many_lines)]
-    pub(crate) fn create_list(
+    pub fn create_list(
         dir_path: &DirPath,
         config_file: &ConfigFile,
         crate_detail: &mut CrateDetail,
+        keep_versions: Option<usize>,
     ) -> Result<Self> {
         let bin_dir = dir_path.bin_dir().as_path();
         let cache_dir = dir_path.cache_dir();
@@ -101,77 +175,49 @@ impl CrateList {
         let installed_crate_git = crate_detail.list_installed_crate_git(checkout_dir, db_dir)?;
 
         // list old registry crate
-        let mut old_crate_registry = Vec::new();
-        let mut version_removed_crate = remove_version(&installed_crate_registry);
-        version_removed_crate.sort();
-        if !version_removed_crate.is_empty() {
-            let mut common_crate_version = Vec::new();
-            // check a two version of same crate if crate has two version then it is added
-            // to vec and checked to determine old crate version
-            for i in 0..(version_removed_crate.len() - 1) {
-                let (name, version) = &version_removed_crate[i];
-                let (next_name, _) = &version_removed_crate[i + 1];
-                if name == next_name {
-                    common_crate_version.push((version, i));
-                } else {
-                    // do not need to check if there is no old crate version in list so no two same
-                    // version crate
-                    if !common_crate_version.is_empty() {
-                        common_crate_version.push((version, i));
-                        let mut latest_version = version;
-                        // this is used to check which version is latest since we have sorted string
-                        // so 0.10.0 will come before 0.9.0. so it need to
-                        // be done to properly determine latest version
-                        for (common_version, _) in &common_crate_version {
-                            if semver::Version::parse(latest_version).unwrap()
-                                < semver::Version::parse(common_version).unwrap()
-                            {
-                                latest_version = common_version;
-                            }
-                        }
-                        for (crate_version, position) in &common_crate_version {
-                            if crate_version.as_str() != latest_version {
-                                old_crate_registry.push(
-                                    installed_crate_registry.get(*position).unwrap().to_string(),
-                                );
-                            }
-                        }
-                    }
-                    common_crate_version = Vec::new();
+        let old_crate_registry = list_old_registry(
+            &installed_crate_registry,
+            crate_detail,
+            keep_versions.unwrap_or_else(|| config_file.keep_versions()),
+            crate_detail.old_mode(),
+        );
+
+        // list old git crate. Git checkout is old if its rev value is not latest rev
+        // value of git db of same repository
+        let mut old_crate_git = Vec::new();
+        let mut latest_rev = HashMap::new();
+        if db_dir.exists() {
+            for entry in fs::read_dir(db_dir).context("failed to read db dir")? {
+                let path = entry?.path();
+                let repository_dir = path.file_name().unwrap().to_str().unwrap().to_string();
+                if let Some(rev_value) = latest_commit(&path)? {
+                    latest_rev.insert(repository_dir, rev_value);
                 }
             }
         }
-        old_crate_registry.sort();
-        old_crate_registry.dedup();
-
-        // list old git crate
-        let mut old_crate_git = Vec::new();
-        let mut full_name_list = Vec::new();
-        // analyze each crates of db dir and create list of head rev value
-        for crates in fs::read_dir(db_dir).context("failed to read db dir")? {
-            let entry = crates?.path();
-            let path = entry.as_path();
-            let file_name = path.file_name().unwrap().to_str().unwrap();
-            let name = file_name.rsplitn(2, '-').collect::<Vec<&str>>();
-            let mut rev_value = latest_rev_value(path)?;
-            rev_value.retain(|c| c != '\'');
-            let full_name = format!("{}-{}", name[1], rev_value);
-            full_name_list.push(full_name);
-        }
         for crate_name in &installed_crate_git {
-            if !crate_name.contains("-HEAD") {
-                // check if crate is in rev value else list that crate as old crate
-                if !full_name_list.contains(crate_name) {
-                    old_crate_git.push(crate_name.to_string());
-                }
+            let crate_rev = crate_name.rsplit('-').next().unwrap_or_default();
+            if crate_rev == "HEAD" {
+                continue;
+            }
+            let is_latest = crate_detail
+                .find_git_dirs(crate_name)
+                .iter()
+                .any(|repository_dir| {
+                    latest_rev
+                        .get(repository_dir)
+                        .is_some_and(|rev_value: &String| rev_value.starts_with(crate_rev))
+                });
+            if !is_latest {
+                old_crate_git.push(crate_name.clone());
             }
         }
         old_crate_git.sort();
         old_crate_git.dedup();
 
         // list all used crates in rust program
-        let mut used_crate_registry = Vec::new();
-        let mut used_crate_git = Vec::new();
+        let mut used_crate_registry_source = Vec::new();
+        let mut used_crate_git_source = Vec::new();
         let mut cargo_toml_location = CargoTomlLocation::new();
         let config_directory = config_file.directory().clone();
         // read a Cargo.lock file and determine out a used registry and git crate
@@ -180,41 +226,118 @@ impl CrateList {
             let (mut registry_crate, mut git_crate) =
                 read_content(list_cargo_toml.location_path())?;
             cargo_toml_location.append(list_cargo_toml);
-            used_crate_registry.append(&mut registry_crate);
-            used_crate_git.append(&mut git_crate);
+            used_crate_registry_source.append(&mut registry_crate);
+            used_crate_git_source.append(&mut git_crate);
         }
+
+        // map used registry crates to registry folder from which they are used by
+        // matching Cargo.lock source with registry folder name. If registry folder of
+        // source is not present in cargo home such as source which is replaced by
+        // other source, crate is treated as used in all registry containing it
+        let registry_dirs = list_registry_dir_name(dir_path)?;
+        let mut used_crate_registry_dir: HashMap<String, Vec<String>> = HashMap::new();
+        for (crate_name, source) in &used_crate_registry_source {
+            let mut source_dirs = lock_source_registry_dirs(source);
+            source_dirs.retain(|registry_dir| registry_dirs.contains(registry_dir));
+            if source_dirs.is_empty() {
+                source_dirs = crate_detail.find_registries(crate_name).to_vec();
+            }
+            let used_dirs = used_crate_registry_dir
+                .entry(crate_name.clone())
+                .or_default();
+            used_dirs.append(&mut source_dirs);
+            used_dirs.sort();
+            used_dirs.dedup();
+        }
+        // when registry filter is used only crates used from filtered registry are
+        // listed as used crates
+        let mut used_crate_registry = used_crate_registry_dir
+            .iter()
+            .filter(|(_, used_dirs)| {
+                if used_dirs.is_empty() {
+                    !crate_detail.has_registry_filter()
+                } else {
+                    used_dirs
+                        .iter()
+                        .any(|used_dir| crate_detail.is_registry_scanned(used_dir))
+                }
+            })
+            .map(|(crate_name, _)| crate_name.clone())
+            .collect::<Vec<_>>();
         used_crate_registry.sort();
-        used_crate_registry.dedup();
+
+        // map used git crates to repository folder by matching folder name with name
+        // computed from repository url present in Cargo.lock source. Database
+        // folder is used if repository is used whereas checkout folder is used only
+        // if its revision is used. Crate which is not present in cargo home is listed
+        // with short revision value
+        let mut used_crate_git = Vec::new();
+        let mut used_crate_git_dir: HashMap<String, Vec<String>> = HashMap::new();
+        for (source, rev) in &used_crate_git_source {
+            let repository_dirs = git_dir_names(source);
+            let mut is_checkout_present = false;
+            for crate_name in &installed_crate_git {
+                let crate_rev = crate_name.rsplit('-').next().unwrap_or_default();
+                let is_head = crate_rev == "HEAD";
+                if !is_head && !rev.starts_with(crate_rev) {
+                    continue;
+                }
+                for repository_dir in crate_detail.find_git_dirs(crate_name) {
+                    if repository_dirs.contains(repository_dir) {
+                        is_checkout_present |= !is_head;
+                        let used_dirs = used_crate_git_dir.entry(crate_name.clone()).or_default();
+                        used_dirs.push(repository_dir.clone());
+                        used_dirs.sort();
+                        used_dirs.dedup();
+                    }
+                }
+            }
+            if !is_checkout_present {
+                let ident = repository_dirs[0]
+                    .rsplitn(2, '-')
+                    .last()
+                    .unwrap_or_default();
+                used_crate_git.push(format!("{}-{}", ident, rev.get(..7).unwrap_or(rev)));
+            }
+        }
+        used_crate_git.extend(used_crate_git_dir.keys().cloned());
         used_crate_git.sort();
-        used_crate_registry.dedup();
+        used_crate_git.dedup();
 
-        // list orphan crates. If crate is not used then it is orphan
+        // list orphan crates. If crate is not used then it is orphan. Registry crate
+        // is orphan if it is present in registry from which it is not used
         let mut orphan_crate_registry = Vec::new();
+        let mut orphan_crate_registry_dir = HashMap::new();
         let mut orphan_crate_git = Vec::new();
+        let mut orphan_crate_git_dir = HashMap::new();
         for crates in &installed_crate_registry {
-            if !used_crate_registry.contains(crates) {
-                orphan_crate_registry.push(crates.to_string());
+            let used_dirs = used_crate_registry_dir.get(crates);
+            let orphan_dirs = crate_detail
+                .find_registries(crates)
+                .iter()
+                .filter(|registry_dir| {
+                    used_dirs.is_none_or(|used_dirs| !used_dirs.contains(registry_dir))
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            if !orphan_dirs.is_empty() {
+                orphan_crate_registry.push(crates.clone());
+                orphan_crate_registry_dir.insert(crates.clone(), orphan_dirs);
             }
         }
         for crates in &installed_crate_git {
-            if crates.contains("-HEAD") {
-                let split_installed = crates.rsplitn(2, '-').collect::<Vec<&str>>();
-                if used_crate_git.is_empty() {
-                    orphan_crate_git.push(crates.to_string());
-                }
-                let mut used_in_project = false;
-                for used in &used_crate_git {
-                    if used.contains(split_installed[1]) {
-                        used_in_project = true;
-                        // Break if found to be used one time no need to check for other
-                        break;
-                    }
-                }
-                if !used_in_project {
-                    orphan_crate_git.push(crates.to_string());
-                }
-            } else if !used_crate_git.contains(crates) {
-                orphan_crate_git.push(crates.to_string());
+            let used_dirs = used_crate_git_dir.get(crates);
+            let orphan_dirs = crate_detail
+                .find_git_dirs(crates)
+                .iter()
+                .filter(|repository_dir| {
+                    used_dirs.is_none_or(|used_dirs| !used_dirs.contains(repository_dir))
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            if !orphan_dirs.is_empty() {
+                orphan_crate_git.push(crates.clone());
+                orphan_crate_git_dir.insert(crates.clone(), orphan_dirs);
             }
         }
         orphan_crate_registry.sort();
@@ -232,91 +355,225 @@ impl CrateList {
             used_crate_git,
             orphan_crate_registry,
             orphan_crate_git,
+            used_crate_registry_dir,
+            orphan_crate_registry_dir,
+            used_crate_git_dir,
+            orphan_crate_git_dir,
             cargo_toml_location,
         })
     }
 
-    // provide list of installed bin
-    pub(crate) fn installed_bin(&self) -> &Vec<String> {
+    /// provide list of installed bin
+    pub fn installed_bin(&self) -> &Vec<String> {
         &self.installed_bin
     }
 
-    // provide list of installed registry
-    pub(crate) fn installed_registry(&self) -> &Vec<String> {
+    /// provide list of installed registry
+    pub fn installed_registry(&self) -> &Vec<String> {
         &self.installed_crate_registry
     }
 
-    // provide list of old registry
-    pub(crate) fn old_registry(&self) -> &Vec<String> {
+    /// provide list of old registry
+    pub fn old_registry(&self) -> &Vec<String> {
         &self.old_crate_registry
     }
 
-    // provide list of used registry
-    pub(crate) fn used_registry(&self) -> &Vec<String> {
+    /// provide list of used registry
+    pub fn used_registry(&self) -> &Vec<String> {
         &self.used_crate_registry
     }
 
-    // provide list o orphan registry
-    pub(crate) fn orphan_registry(&self) -> &Vec<String> {
+    /// provide list o orphan registry
+    pub fn orphan_registry(&self) -> &Vec<String> {
         &self.orphan_crate_registry
     }
 
-    // provide list of installed git
-    pub(crate) fn installed_git(&self) -> &Vec<String> {
+    /// provide list of registry folder from which registry crate is used
+    pub fn used_registry_dirs(&self, crate_name: &str) -> &[String] {
+        self.used_crate_registry_dir
+            .get(crate_name)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// provide list of registry folder in which registry crate is orphan
+    pub fn orphan_registry_dirs(&self, crate_name: &str) -> &[String] {
+        self.orphan_crate_registry_dir
+            .get(crate_name)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// provide list of repository folder from which git crate is used
+    pub fn used_git_dirs(&self, crate_name: &str) -> &[String] {
+        self.used_crate_git_dir
+            .get(crate_name)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// provide list of repository folder in which git crate is orphan
+    pub fn orphan_git_dirs(&self, crate_name: &str) -> &[String] {
+        self.orphan_crate_git_dir
+            .get(crate_name)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// provide list of installed git
+    pub fn installed_git(&self) -> &Vec<String> {
         &self.installed_crate_git
     }
 
-    // provide list of old git
-    pub(crate) fn old_git(&self) -> &Vec<String> {
+    /// provide list of old git
+    pub fn old_git(&self) -> &Vec<String> {
         &self.old_crate_git
     }
 
-    // provide list of used git
-    pub(crate) fn used_git(&self) -> &Vec<String> {
+    /// provide list of used git
+    pub fn used_git(&self) -> &Vec<String> {
         &self.used_crate_git
     }
 
-    // provide list of orphan git
-    pub(crate) fn orphan_git(&self) -> &Vec<String> {
+    /// provide list of orphan git
+    pub fn orphan_git(&self) -> &Vec<String> {
         &self.orphan_crate_git
     }
 
-    // list out path of directory which contains cargo lock file
-    pub(crate) fn cargo_toml_location(&self) -> &CargoTomlLocation {
+    /// list out path of directory which contains cargo lock file
+    pub fn cargo_toml_location(&self) -> &CargoTomlLocation {
         &self.cargo_toml_location
     }
 
-    // list crates which is both old and orphan
-    pub(crate) fn list_old_orphan_registry(&self) -> Vec<String> {
+    /// list crates which is both old and orphan
+    pub fn list_old_orphan_registry(&self) -> Vec<String> {
         let mut old_orphan_registry = Vec::new();
         let orphan_list = self.orphan_registry();
         for crates in self.old_registry() {
             if orphan_list.contains(crates) {
-                old_orphan_registry.push(crates.to_string());
+                old_orphan_registry.push(crates.clone());
             }
         }
         old_orphan_registry
     }
 
-    // list out git crates which is both old and orphan
-    pub(crate) fn list_old_orphan_git(&self) -> Vec<String> {
+    /// list out git crates which is both old and orphan
+    pub fn list_old_orphan_git(&self) -> Vec<String> {
         let mut old_orphan_git = Vec::new();
         let orphan_list = self.orphan_git();
         for crates in self.old_git() {
             if orphan_list.contains(crates) {
-                old_orphan_git.push(crates.to_string());
+                old_orphan_git.push(crates.clone());
             }
         }
         old_orphan_git
     }
+
+    /// classify crate of provided kind as old, orphan or used
+    pub fn classification(&self, crate_name: &str, kind: CrateKind) -> Vec<&'static str> {
+        let (old_list, orphan_list, used_list) = match kind {
+            CrateKind::Registry => (
+                self.old_registry(),
+                self.orphan_registry(),
+                self.used_registry(),
+            ),
+            CrateKind::Git => (self.old_git(), self.orphan_git(), self.used_git()),
+        };
+        let mut classification = Vec::new();
+        let crate_name = crate_name.to_string();
+        if old_list.contains(&crate_name) {
+            classification.push("old");
+        }
+        if orphan_list.contains(&crate_name) {
+            classification.push("orphan");
+        }
+        if used_list.contains(&crate_name) {
+            classification.push("used");
+        }
+        classification
+    }
+
+    /// list registry crates which are not used for provided duration
+    pub fn list_older_than_registry(
+        &self,
+        crate_detail: &CrateDetail,
+        duration: Duration,
+    ) -> Vec<String> {
+        list_older_than(
+            self.installed_registry(),
+            crate_detail,
+            duration,
+            CrateKind::Registry,
+        )
+    }
+
+    /// list git crates which are not used for provided duration
+    pub fn list_older_than_git(
+        &self,
+        crate_detail: &CrateDetail,
+        duration: Duration,
+    ) -> Vec<String> {
+        list_older_than(self.installed_git(), crate_detail, duration, CrateKind::Git)
+    }
+
+    /// list crates in order in which they should be evicted along with their
+    /// kind. Crates which are not used by any project are listed before used
+    /// crates and each group is ordered from least recently used crate
+    pub fn list_eviction_order(&self, crate_detail: &CrateDetail) -> Vec<(String, CrateKind)> {
+        let mut eviction_list = Vec::new();
+        let orphan_registry = self.orphan_registry().iter().collect::<HashSet<_>>();
+        let orphan_git = self.orphan_git().iter().collect::<HashSet<_>>();
+        for crate_name in self.installed_registry() {
+            let is_used = !orphan_registry.contains(crate_name);
+            let last_used = crate_detail.find_last_used(crate_name, CrateKind::Registry);
+            eviction_list.push((is_used, last_used, crate_name, CrateKind::Registry));
+        }
+        for crate_name in self.installed_git() {
+            let is_used = !orphan_git.contains(crate_name);
+            let last_used = crate_detail.find_last_used(crate_name, CrateKind::Git);
+            eviction_list.push((is_used, last_used, crate_name, CrateKind::Git));
+        }
+        sort_eviction_order(&mut eviction_list);
+        eviction_list
+            .into_iter()
+            .map(|(_, _, crate_name, kind)| (crate_name.clone(), kind))
+            .collect()
+    }
 }
 
+// list crates from provided list whose age is greater than duration
+fn list_older_than(
+    crate_list: &[String],
+    crate_detail: &CrateDetail,
+    duration: Duration,
+    kind: CrateKind,
+) -> Vec<String> {
+    crate_list
+        .iter()
+        .filter(|crate_name| {
+            crate_detail
+                .find_age(crate_name, kind)
+                .is_some_and(|age| age >= duration)
+        })
+        .cloned()
+        .collect()
+}
+
+// sort eviction list so unused crates comes first and crates are ordered from
+// least recently used. Crate with unknown last used time is treated as oldest
+fn sort_eviction_order<T: Ord>(eviction_list: &mut [(bool, Option<SystemTime>, T, CrateKind)]) {
+    eviction_list.sort_by(|a, b| (a.0, a.1, &a.2).cmp(&(b.0, b.1, &b.2)));
+}
+
+// registry crate name along with its source present in cargo lock file
+type RegistrySourceCrate = (String, String);
+
+// git repository url along with commit rev value present in cargo lock file
+type GitSourceCrate = (String, String);
+
 // Read out content of cargo.lock file to list out crates present so can be used
-// for orphan clean
-fn read_content(list: &[PathBuf]) -> Result<(Vec<String>, Vec<String>)> {
+// for orphan clean. Registry crates are listed along with their source and git
+// crates are listed as repository url along with rev value
+fn read_content(list: &[PathBuf]) -> Result<(Vec<RegistrySourceCrate>, Vec<GitSourceCrate>)> {
     let mut present_crate_registry = Vec::new();
     let mut present_crate_git = Vec::new();
-    for lock in list.iter() {
+    for lock in list {
         let mut lock_folder = lock.clone();
         lock_folder.push("Cargo.lock");
         if lock_folder.exists() {
@@ -329,33 +586,15 @@ fn read_content(list: &[PathBuf]) -> Result<(Vec<String>, Vec<String>)> {
                     if let Some(source) = package.source() {
                         let name = package.name();
                         let version = package.version();
-                        if source.contains("registry+") {
-                            let full_name = format!("{}-{}", name, version);
-                            present_crate_registry.push(full_name);
+                        if source.starts_with("registry+") || source.starts_with("sparse+") {
+                            let full_name = format!("{name}-{version}");
+                            present_crate_registry.push((full_name, source.clone()));
                         }
-                        if source.contains("git+") {
-                            if source.contains("?rev=")
-                                || source.contains("?branch=")
-                                || source.contains("?tag=")
-                            {
-                                let split_url: Vec<&str> = if source.contains("?rev=") {
-                                    source.split("?rev=").collect()
-                                } else if source.contains("?branch=") {
-                                    source.split("?branch=").collect()
-                                } else {
-                                    source.split("?tag=").collect()
-                                };
-                                let rev_sha: Vec<&str> = split_url[1].split('#').collect();
-                                let rev_value = rev_sha[1];
-                                let rev_short_form = &rev_value[..=6];
-                                let full_name = format!("{}-{}", name, rev_short_form);
-                                present_crate_git.push(full_name);
-                            } else {
-                                let rev_sha: Vec<&str> = source.split('#').collect();
-                                let rev_value = rev_sha[1];
-                                let rev_short_form = &rev_value[..=6];
-                                let full_name = format!("{}-{}", name, rev_short_form);
-                                present_crate_git.push(full_name);
+                        // git source contains repository url followed by full
+                        // commit rev value after #
+                        if source.starts_with("git+") {
+                            if let Some((url, rev)) = source.rsplit_once('#') {
+                                present_crate_git.push((url.to_string(), rev.to_string()));
                             }
                         }
                     }
@@ -366,27 +605,410 @@ fn read_content(list: &[PathBuf]) -> Result<(Vec<String>, Vec<String>)> {
     Ok((present_crate_registry, present_crate_git))
 }
 
-// Function used to remove version from installed_crate_registry list so can be
-// used for old clean flag
-fn remove_version(installed_crate_registry: &[String]) -> Vec<(String, String)> {
-    let mut removed_version = Vec::new();
+// return semver compatibility group of version. Versions are compatible if they
+// have same major version, same minor version for 0.x or same patch version for
+// 0.0.x
+fn compatibility_group(version: &Version) -> (u64, u64, u64) {
+    match (version.major, version.minor) {
+        (0, 0) => (0, 0, version.patch),
+        (0, minor) => (0, minor, 0),
+        (major, _) => (major, 0, 0),
+    }
+}
+
+// list old registry crates. Crate is old if there are at least provided number of
+// newer versions of same crate in registry. In semver mode only newer semver
+// compatible versions are counted and pre-release version never makes stable
+// version old, whereas pre-release version is old if newer compatible stable or
+// pre-release version is present. Crate present in multiple registry is only old
+// if it is old in all registries
+fn list_old_registry(
+    installed_crate_registry: &[String],
+    crate_detail: &CrateDetail,
+    keep_versions: usize,
+    old_mode: OldMode,
+) -> Vec<String> {
+    // group crate versions by registry, crate name and compatibility group
+    let mut crate_versions: HashMap<_, Vec<(Version, &String)>> = HashMap::new();
     for crate_full_name in installed_crate_registry {
-        let data = clear_version_value(crate_full_name);
-        removed_version.push(data);
+        let (name, version) = clear_version_value(crate_full_name);
+        let Ok(version) = Version::parse(&version) else {
+            continue;
+        };
+        let group = match old_mode {
+            OldMode::Latest => None,
+            OldMode::Semver => Some(compatibility_group(&version)),
+        };
+        let registries = crate_detail.find_registries(crate_full_name);
+        if registries.is_empty() {
+            crate_versions
+                .entry(("", name, group))
+                .or_default()
+                .push((version, crate_full_name));
+            continue;
+        }
+        for registry in registries {
+            crate_versions
+                .entry((registry.as_str(), name.clone(), group))
+                .or_default()
+                .push((version.clone(), crate_full_name));
+        }
     }
-    removed_version
+    // count number of registries in which crate is old
+    let mut old_count: HashMap<&String, usize> = HashMap::new();
+    for versions in crate_versions.values_mut() {
+        versions.sort_by(|a, b| b.0.cmp(&a.0));
+        let mut newer_stable_versions = 0;
+        for (newer_versions, (version, crate_full_name)) in versions.iter().enumerate() {
+            let newer = if old_mode == OldMode::Semver && version.pre.is_empty() {
+                newer_stable_versions
+            } else {
+                newer_versions
+            };
+            if newer >= keep_versions {
+                *old_count.entry(crate_full_name).or_default() += 1;
+            }
+            if version.pre.is_empty() {
+                newer_stable_versions += 1;
+            }
+        }
+    }
+    let mut old_crate_registry = old_count
+        .into_iter()
+        .filter(|(crate_full_name, count)| {
+            *count >= crate_detail.find_registries(crate_full_name).len().max(1)
+        })
+        .map(|(crate_full_name, _)| crate_full_name.clone())
+        .collect::<Vec<_>>();
+    old_crate_registry.sort();
+    old_crate_registry
 }
 
-// get latest commit rev value from git repository
-fn latest_rev_value(path: &Path) -> Result<String> {
-    let output = std::process::Command::new("git")
-        .arg("log")
-        .arg("--pretty=format:'%h'")
-        .arg("--max-count=1")
-        .current_dir(path)
-        .output()
-        .context("failed to execute git log command")?;
-    Ok(std::str::from_utf8(&output.stdout)
-        .context("stdout is not utf8")?
-        .to_string())
+#[cfg(test)]
+mod test {
+    use std::{
+        fs,
+        time::{Duration, SystemTime},
+    };
+
+    use super::{list_old_registry, sort_eviction_order, CrateKind, CrateList, OldMode};
+    use crate::{
+        config_file::ConfigFile,
+        crate_detail::CrateDetail,
+        git_dir::GitDir,
+        test_util::{dir_path, fake_cargo_home, registry_dir, scan, REGISTRY, SPARSE_REGISTRY},
+    };
+
+    #[test]
+    fn test_list_old_registry() {
+        let installed = [
+            "tokio-0.2.25",
+            "tokio-1.19.2",
+            "tokio-1.20.0",
+            "rand-0.7.3",
+            "rand-0.8.4",
+            "rand-0.8.5",
+            "serde-1.0.100",
+            "serde-1.0.101-rc.1",
+            "serde-2.0.0-beta.1",
+            "serde-2.0.0-beta.2",
+        ]
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+        let crate_detail = CrateDetail::default();
+        assert_eq!(
+            list_old_registry(&installed, &crate_detail, 1, OldMode::Latest),
+            vec![
+                "rand-0.7.3",
+                "rand-0.8.4",
+                "serde-1.0.100",
+                "serde-1.0.101-rc.1",
+                "serde-2.0.0-beta.1",
+                "tokio-0.2.25",
+                "tokio-1.19.2",
+            ]
+        );
+        assert_eq!(
+            list_old_registry(&installed, &crate_detail, 1, OldMode::Semver),
+            vec!["rand-0.8.4", "serde-2.0.0-beta.1", "tokio-1.19.2"]
+        );
+        assert!(list_old_registry(&installed, &crate_detail, 2, OldMode::Semver).is_empty());
+        assert!("semver".parse::<OldMode>().is_ok());
+        assert!("major".parse::<OldMode>().is_err());
+    }
+
+    #[test]
+    fn test_split_name() {
+        assert_eq!(
+            CrateKind::Registry.split_name("serde-json-1.0.0-rc.1"),
+            ("serde-json".to_string(), "1.0.0-rc.1".to_string())
+        );
+        assert_eq!(
+            CrateKind::Git.split_name("cargo-trim-eab33b6"),
+            ("cargo-trim".to_string(), "eab33b6".to_string())
+        );
+        assert_eq!(
+            CrateKind::Git.split_name("cargo-trim-HEAD"),
+            ("cargo-trim".to_string(), "HEAD".to_string())
+        );
+    }
+
+    #[test]
+    fn test_sort_eviction_order() {
+        let old_time = Some(SystemTime::UNIX_EPOCH);
+        let new_time = Some(SystemTime::UNIX_EPOCH + Duration::from_secs(100));
+        let mut eviction_list = vec![
+            (true, old_time, "used_old", CrateKind::Registry),
+            (false, new_time, "orphan_new", CrateKind::Git),
+            (true, new_time, "used_new", CrateKind::Registry),
+            (false, old_time, "orphan_old", CrateKind::Registry),
+            (false, None, "orphan_unknown", CrateKind::Git),
+        ];
+        sort_eviction_order(&mut eviction_list);
+        let order = eviction_list
+            .iter()
+            .map(|(_, _, crate_name, _)| *crate_name)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            order,
+            vec![
+                "orphan_unknown",
+                "orphan_old",
+                "orphan_new",
+                "used_old",
+                "used_new"
+            ]
+        );
+    }
+
+    // test listing and classifying crates of cargo home
+    #[test]
+    fn test_create_list() {
+        let cargo_home = fake_cargo_home("list", REGISTRY);
+        let dir_path = dir_path(&cargo_home);
+        let (_, crate_detail, crate_list) = scan(&dir_path);
+        assert_eq!(
+            crate_list.installed_registry(),
+            &vec!["alpha-0.1.0", "alpha-0.2.0", "zeta-1.0.0"]
+        );
+        assert_eq!(crate_list.old_registry(), &vec!["alpha-0.1.0"]);
+        assert_eq!(crate_list.orphan_registry().len(), 3);
+        assert_eq!(crate_detail.find_registries("zeta-1.0.0"), &[REGISTRY]);
+        assert_eq!(
+            crate_detail.find_bytes("zeta-1.0.0", CrateKind::Registry),
+            5
+        );
+    }
+
+    // test used and orphan crates are computed per registry source
+    #[test]
+    fn test_orphan_per_registry() {
+        let cargo_home = fake_cargo_home("per-registry", SPARSE_REGISTRY);
+        // crates.io crate is also extracted in git registry
+        let src = cargo_home
+            .path()
+            .join("registry/src")
+            .join(REGISTRY)
+            .join("alpha-0.2.0");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(src.join("lib.rs"), "").unwrap();
+        let other_registry = "my-host-0123456789abcdef";
+        for crate_name in &["alpha-0.2.0", "beta-1.0.0"] {
+            let src = cargo_home
+                .path()
+                .join("registry/src")
+                .join(other_registry)
+                .join(crate_name);
+            fs::create_dir_all(&src).unwrap();
+            fs::write(src.join("lib.rs"), "lib").unwrap();
+        }
+        let project = cargo_home.path().join("project");
+        fs::create_dir_all(&project).unwrap();
+        fs::write(project.join("Cargo.toml"), "").unwrap();
+        fs::write(
+            project.join("Cargo.lock"),
+            r#"
+[[package]]
+name = "alpha"
+version = "0.2.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "beta"
+version = "1.0.0"
+source = "sparse+https://unknown-host/index/"
+"#,
+        )
+        .unwrap();
+        let dir_path = dir_path(&cargo_home);
+        fs::write(
+            dir_path.config_file(),
+            format!("directory = [{:?}]\n", project.to_str().unwrap()),
+        )
+        .unwrap();
+        let (_, crate_detail, crate_list) = scan(&dir_path);
+        assert_eq!(
+            crate_list.used_registry(),
+            &vec!["alpha-0.2.0", "beta-1.0.0"]
+        );
+        // crates.io source matches both git and sparse registry folder
+        assert_eq!(
+            crate_list.used_registry_dirs("alpha-0.2.0"),
+            &[REGISTRY, SPARSE_REGISTRY]
+        );
+        // registry of beta source is not present so it is used in all registry
+        assert_eq!(
+            crate_list.used_registry_dirs("beta-1.0.0"),
+            &[other_registry]
+        );
+        assert_eq!(
+            crate_list.orphan_registry(),
+            &vec!["alpha-0.1.0", "alpha-0.2.0", "zeta-1.0.0"]
+        );
+        assert_eq!(
+            crate_list.orphan_registry_dirs("alpha-0.2.0"),
+            &[other_registry]
+        );
+        // only size of registry in which crate is orphan is cleaned
+        assert_eq!(
+            crate_detail.find_bytes("alpha-0.2.0", CrateKind::Registry),
+            8
+        );
+        assert_eq!(
+            crate_detail.find_bytes_in_registries(
+                "alpha-0.2.0",
+                crate_list.orphan_registry_dirs("alpha-0.2.0")
+            ),
+            3
+        );
+        let mut registry_dir = registry_dir(&dir_path, &crate_list);
+        registry_dir
+            .remove_crate_from_registry(
+                "alpha-0.2.0",
+                crate_list.orphan_registry_dirs("alpha-0.2.0"),
+                false,
+            )
+            .unwrap();
+        assert!(!dir_path
+            .src_dir()
+            .join(other_registry)
+            .join("alpha-0.2.0")
+            .exists());
+        assert!(dir_path
+            .src_dir()
+            .join(REGISTRY)
+            .join("alpha-0.2.0")
+            .exists());
+    }
+
+    // test git crates are matched with repository url present in Cargo.lock
+    #[test]
+    fn test_orphan_git_repository() {
+        let cargo_home = fake_cargo_home("git-repository", REGISTRY);
+        // folder name of repository file:///tmp/gitchk/repo computed by cargo
+        let used_repository = "repo-1e82ad8f16715332";
+        let other_repository = "repo-0123456789abcdef";
+        let similar_repository = "repo-extra-0123456789abcdef";
+        for repository in &[used_repository, other_repository, similar_repository] {
+            fs::create_dir_all(cargo_home.path().join("git/db").join(repository)).unwrap();
+            let checkout = cargo_home.path().join("git/checkouts").join(repository);
+            fs::create_dir_all(checkout.join("eab33b6")).unwrap();
+            fs::write(checkout.join("eab33b6/lib.rs"), "").unwrap();
+        }
+        let project = cargo_home.path().join("project");
+        fs::create_dir_all(&project).unwrap();
+        fs::write(project.join("Cargo.toml"), "").unwrap();
+        fs::write(
+            project.join("Cargo.lock"),
+            r#"
+[[package]]
+name = "dep"
+version = "0.1.0"
+source = "git+file:///tmp/gitchk/repo/#eab33b60b5593c11e4387912880c45fe5d7e5d11"
+"#,
+        )
+        .unwrap();
+        let dir_path = dir_path(&cargo_home);
+        fs::write(
+            dir_path.config_file(),
+            format!("directory = [{:?}]\n", project.to_str().unwrap()),
+        )
+        .unwrap();
+        let (_, _, crate_list) = scan(&dir_path);
+        assert_eq!(crate_list.used_git(), &vec!["repo-HEAD", "repo-eab33b6"]);
+        assert_eq!(crate_list.used_git_dirs("repo-HEAD"), &[used_repository]);
+        assert_eq!(
+            crate_list.orphan_git(),
+            &vec![
+                "repo-HEAD",
+                "repo-eab33b6",
+                "repo-extra-HEAD",
+                "repo-extra-eab33b6"
+            ]
+        );
+        assert_eq!(crate_list.orphan_git_dirs("repo-HEAD"), &[other_repository]);
+        let git_dir = GitDir::new(dir_path.checkout_dir(), dir_path.db_dir());
+        git_dir
+            .remove_crate_from_repository(
+                "repo-eab33b6",
+                crate_list.orphan_git_dirs("repo-eab33b6"),
+                false,
+            )
+            .unwrap();
+        let checkout_dir = dir_path.checkout_dir();
+        assert!(checkout_dir.join(used_repository).join("eab33b6").exists());
+        assert!(!checkout_dir.join(other_repository).join("eab33b6").exists());
+        assert!(checkout_dir
+            .join(similar_repository)
+            .join("eab33b6")
+            .exists());
+    }
+
+    // test scanning with different number of jobs give same result
+    #[test]
+    fn test_scan_jobs() {
+        let cargo_home = fake_cargo_home("jobs", REGISTRY);
+        let dir_path = dir_path(&cargo_home);
+        let (config_file, _, _) = scan(&dir_path);
+        let mut results = Vec::new();
+        for jobs in &[1, 4] {
+            let mut crate_detail = CrateDetail::default();
+            crate_detail.set_jobs(*jobs).unwrap();
+            let crate_list =
+                CrateList::create_list(&dir_path, &config_file, &mut crate_detail, None).unwrap();
+            let sizes = crate_list
+                .installed_registry()
+                .iter()
+                .map(|crate_name| crate_detail.find_bytes(crate_name, CrateKind::Registry))
+                .collect::<Vec<_>>();
+            results.push((crate_list.installed_registry().clone(), sizes));
+        }
+        assert_eq!(results[0], results[1]);
+        assert!(CrateDetail::default().set_jobs(0).is_err());
+    }
+
+    // test newest versions per crate stored in config file are not classified as old
+    #[test]
+    fn test_keep_versions() {
+        let cargo_home = fake_cargo_home("keep-versions", REGISTRY);
+        let src = cargo_home
+            .path()
+            .join("registry/src")
+            .join(REGISTRY)
+            .join("alpha-0.3.0");
+        fs::create_dir_all(&src).unwrap();
+        let dir_path = dir_path(&cargo_home);
+        let mut config_file = ConfigFile::init(dir_path.config_file()).unwrap();
+        assert_eq!(config_file.keep_versions(), 1);
+        config_file.set_keep_versions(Some(2), false, true).unwrap();
+        let (config_file, mut crate_detail, crate_list) = scan(&dir_path);
+        assert_eq!(config_file.keep_versions(), 2);
+        assert_eq!(crate_list.old_registry(), &vec!["alpha-0.1.0"]);
+        // provided keep versions override config file value without changing it
+        let crate_list =
+            CrateList::create_list(&dir_path, &config_file, &mut crate_detail, Some(3)).unwrap();
+        assert!(crate_list.old_registry().is_empty());
+        assert_eq!(config_file.keep_versions(), 2);
+    }
 }
@@ -5,13 +5,8 @@
 #![allow(clippy::too_many_lines, clippy::struct_excessive_bools)]
 
 mod command;
-mod config_file;
-mod crate_detail;
-mod dir_path;
-mod git_dir;
-mod list_crate;
-mod registry_dir;
-mod utils;
+mod output;
+mod progress_bar;
 
 use std::env;
 
@@ -0,0 +1,1003 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use cargo_trim::{
+    BinEntry, BinStatus, CrateDetail, CrateKind, CrateList, InstalledPackage, JournalEntry,
+    JournalRun, RegistrySource, TargetDir, TrashBatch, TrashEntry,
+};
+use colored::Colorize;
+use serde::Serialize;
+
+// Record of single crate used for machine readable list output
+#[derive(Serialize)]
+pub(crate) struct CrateRecord<'a> {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    list: &'a str,
+    name: String,
+    version: String,
+    kind: &'static str,
+    registry: Option<&'a str>,
+    registry_name: Option<&'a str>,
+    registry_url: Option<&'a str>,
+    archive_size: u64,
+    source_size: u64,
+    last_used: Option<u64>,
+    classification: Vec<&'static str>,
+}
+
+impl<'a> CrateRecord<'a> {
+    // create new crate record for crate of provided kind present in registry
+    pub(crate) fn new(
+        list: &'a str,
+        crate_name: &str,
+        kind: CrateKind,
+        registry: Option<&'a str>,
+        registry_source: &'a RegistrySource,
+        crate_detail: &CrateDetail,
+        crate_list: &CrateList,
+    ) -> Self {
+        let (name, version) = kind.split_name(crate_name);
+        let (archive_size, source_size) = match kind {
+            CrateKind::Registry => (
+                get_bytes(crate_detail.registry_crates_archive(), crate_name),
+                get_bytes(crate_detail.registry_crates_source(), crate_name),
+            ),
+            CrateKind::Git => (
+                get_bytes(crate_detail.git_crates_archive(), crate_name),
+                get_bytes(crate_detail.git_crates_source(), crate_name),
+            ),
+        };
+        let last_used = crate_detail
+            .find_last_used(crate_name, kind)
+            .and_then(|last_used| last_used.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map(|duration| duration.as_secs());
+        Self {
+            record_type: "crate",
+            list,
+            name,
+            version,
+            kind: kind.as_str(),
+            registry,
+            registry_name: registry.and_then(|registry| registry_source.name(registry)),
+            registry_url: registry.and_then(|registry| registry_source.url(registry)),
+            archive_size,
+            source_size,
+            last_used,
+            classification: crate_list.classification(crate_name, kind),
+        }
+    }
+}
+
+// Record of single query section such as registry cache folder
+#[derive(Serialize)]
+pub(crate) struct QueryRecord<'a> {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    section: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    registry: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<&'a Path>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    profile: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_build: Option<u64>,
+    count: Option<usize>,
+    size: u64,
+}
+
+impl<'a> QueryRecord<'a> {
+    // create new query record. Registry is only provided for section of single
+    // registry
+    pub(crate) fn new(
+        section: &'a str,
+        registry: Option<&'a str>,
+        count: Option<usize>,
+        size: u64,
+    ) -> Self {
+        Self {
+            record_type: "query",
+            section,
+            registry,
+            path: None,
+            profile: None,
+            last_build: None,
+            count,
+            size,
+        }
+    }
+
+    // create new query record of target directory. Profile is only provided for
+    // section of single profile of target directory
+    pub(crate) fn target(
+        section: &'a str,
+        path: &'a Path,
+        profile: Option<&'a str>,
+        last_build: Option<SystemTime>,
+        size: u64,
+    ) -> Self {
+        Self {
+            record_type: "query",
+            section,
+            registry: None,
+            path: Some(path),
+            profile,
+            last_build: last_build.map(|last_build| {
+                last_build
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs()
+            }),
+            count: None,
+            size,
+        }
+    }
+}
+
+// Record of single crate listed in top crates
+#[derive(Serialize)]
+pub(crate) struct TopRecord<'a> {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    category: &'a str,
+    rank: usize,
+    name: &'a str,
+    size: u64,
+}
+
+impl<'a> TopRecord<'a> {
+    // create new top record
+    pub(crate) fn new(category: &'a str, rank: usize, name: &'a str, size: u64) -> Self {
+        Self {
+            record_type: "top",
+            category,
+            rank,
+            name,
+            size,
+        }
+    }
+}
+
+// Record of single crate removed from cache
+#[derive(Serialize)]
+pub(crate) struct RemovedRecord<'a> {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    name: &'a str,
+    kind: &'a str,
+    success: bool,
+    dry_run: bool,
+}
+
+impl<'a> RemovedRecord<'a> {
+    // create new removed record
+    pub(crate) fn new(name: &'a str, kind: &'a str, success: bool, dry_run: bool) -> Self {
+        Self {
+            record_type: "removed",
+            name,
+            kind,
+            success,
+            dry_run,
+        }
+    }
+}
+
+// Record of single crate kept since it is present in keep list
+#[derive(Serialize)]
+pub(crate) struct KeptRecord<'a> {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    name: &'a str,
+    kind: &'a str,
+}
+
+impl<'a> KeptRecord<'a> {
+    // create new kept record
+    pub(crate) fn new(name: &'a str, kind: &'a str) -> Self {
+        Self {
+            record_type: "kept",
+            name,
+            kind,
+        }
+    }
+}
+
+// Record of crates matched by selector
+#[derive(Serialize)]
+pub(crate) struct SelectorRecord<'a> {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    selector: &'a str,
+    crates: &'a [&'a String],
+}
+
+impl<'a> SelectorRecord<'a> {
+    // create new selector record
+    pub(crate) fn new(selector: &'a str, crates: &'a [&'a String]) -> Self {
+        Self {
+            record_type: "selector",
+            selector,
+            crates,
+        }
+    }
+}
+
+// Record of summary of removal operation
+#[derive(Serialize)]
+pub(crate) struct SummaryRecord<'a> {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    operation: &'a str,
+    crates_removed: usize,
+    size: u64,
+    dry_run: bool,
+}
+
+impl<'a> SummaryRecord<'a> {
+    // create new summary record
+    pub(crate) fn new(operation: &'a str, crates_removed: usize, size: u64, dry_run: bool) -> Self {
+        Self {
+            record_type: "summary",
+            operation,
+            crates_removed,
+            size,
+            dry_run,
+        }
+    }
+}
+
+// Record of single crate restored from trash
+#[derive(Serialize)]
+pub(crate) struct RestoredRecord<'a> {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    name: &'a str,
+    path: &'a Path,
+    success: bool,
+    dry_run: bool,
+}
+
+impl<'a> RestoredRecord<'a> {
+    // create new restored record
+    pub(crate) fn new(entry: &'a TrashEntry, success: bool, dry_run: bool) -> Self {
+        Self {
+            record_type: "restored",
+            name: entry.crate_name(),
+            path: entry.original_path(),
+            success,
+            dry_run,
+        }
+    }
+}
+
+// Record of single batch present in trash
+#[derive(Serialize)]
+pub(crate) struct TrashRecord<'a> {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    id: &'a str,
+    created: u64,
+    size: u64,
+    crates: Vec<&'a str>,
+    purged: bool,
+    dry_run: bool,
+}
+
+impl<'a> TrashRecord<'a> {
+    // create new trash record
+    pub(crate) fn new(batch: &'a TrashBatch, purged: bool, dry_run: bool) -> Self {
+        Self {
+            record_type: "trash",
+            id: batch.id(),
+            created: batch
+                .created()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            size: batch.size(),
+            crates: batch.entries().iter().map(TrashEntry::crate_name).collect(),
+            purged,
+            dry_run,
+        }
+    }
+}
+
+// Record of single run present in journal
+#[derive(Serialize)]
+pub(crate) struct HistoryRecord<'a> {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    run: &'a str,
+    timestamp: u64,
+    reasons: Vec<&'a str>,
+    crates_removed: usize,
+    size: u64,
+}
+
+impl<'a> HistoryRecord<'a> {
+    // create new history record
+    pub(crate) fn new(run: &'a JournalRun) -> Self {
+        Self {
+            record_type: "history",
+            run: run.run(),
+            timestamp: run.timestamp(),
+            reasons: run.reasons(),
+            crates_removed: run.entries().len(),
+            size: run.size(),
+        }
+    }
+}
+
+// Record of single removal present in journal
+#[derive(Serialize)]
+pub(crate) struct JournalRecord<'a> {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    #[serde(flatten)]
+    entry: &'a JournalEntry,
+}
+
+impl<'a> JournalRecord<'a> {
+    // create new journal record
+    pub(crate) fn new(entry: &'a JournalEntry) -> Self {
+        Self {
+            record_type: "journal",
+            entry,
+        }
+    }
+}
+
+// Record of single target directory of projects
+#[derive(Serialize)]
+pub(crate) struct TargetRecord<'a> {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    path: &'a Path,
+    projects: &'a [PathBuf],
+    size: u64,
+    last_used: Option<u64>,
+}
+
+impl<'a> TargetRecord<'a> {
+    // create new target record
+    pub(crate) fn new(target_dir: &'a TargetDir) -> Self {
+        Self {
+            record_type: "target",
+            path: target_dir.path(),
+            projects: target_dir.projects(),
+            size: target_dir.size(),
+            last_used: target_dir.last_used().map(|last_used| {
+                last_used
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs()
+            }),
+        }
+    }
+}
+
+// Record of single package installed by cargo install
+#[derive(Serialize)]
+pub(crate) struct BinRecord<'a> {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    name: &'a str,
+    version: &'a str,
+    source: &'a str,
+    bins: &'a [String],
+    features: &'a [String],
+    all_features: bool,
+    no_default_features: bool,
+    profile: Option<&'a str>,
+    target: Option<&'a str>,
+    rustc: Option<&'a str>,
+}
+
+impl<'a> BinRecord<'a> {
+    // create new bin record
+    pub(crate) fn new(package: &'a InstalledPackage) -> Self {
+        Self {
+            record_type: "bin",
+            name: package.name(),
+            version: package.version(),
+            source: package.source(),
+            bins: package.bins(),
+            features: package.features(),
+            all_features: package.all_features(),
+            no_default_features: package.no_default_features(),
+            profile: package.profile(),
+            target: package.target(),
+            rustc: package.rustc(),
+        }
+    }
+}
+
+// Record of single binary classified by bin check
+#[derive(Serialize)]
+pub(crate) struct BinCheckRecord<'a> {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    name: &'a str,
+    path: &'a Path,
+    status: &'static str,
+    package: Option<&'a str>,
+}
+
+impl<'a> BinCheckRecord<'a> {
+    // create new bin check record
+    pub(crate) fn new(entry: &'a BinEntry) -> Self {
+        Self {
+            record_type: "bin_check",
+            name: entry.name(),
+            path: entry.path(),
+            status: entry.status().as_str(),
+            package: entry.package(),
+        }
+    }
+}
+
+// get size in bytes of crate from hashmap
+fn get_bytes(hashmap: &HashMap<String, u64>, crate_name: &str) -> u64 {
+    hashmap.get(crate_name).copied().unwrap_or(0)
+}
+
+// print record as single line of json
+pub(crate) fn print_json<T: Serialize>(record: &T) {
+    println!(
+        "{}",
+        serde_json::to_string(record).expect("record can always be serialized to json")
+    );
+}
+
+// show title
+pub(crate) fn show_title(
+    title: &str,
+    first_path_len: usize,
+    second_path_len: usize,
+    dash_len: usize,
+) {
+    print_dash(dash_len);
+    println!(
+        "|{:^first_width$}|{:^second_width$}|",
+        title.bold(),
+        "SIZE(MB)".bold(),
+        first_width = first_path_len,
+        second_width = second_path_len
+    );
+    print_dash(dash_len);
+}
+
+// print dash
+pub(crate) fn print_dash(len: usize) {
+    println!("{}", "-".repeat(len).color("green"));
+}
+
+// top_crates() help to list out top n crates
+pub(crate) fn show_top_number_crates(
+    crates: &HashMap<String, u64>,
+    crate_type: &str,
+    number: usize,
+    json_format: bool,
+) {
+    // sort crates by size
+    let mut vector = crates.iter().collect::<Vec<_>>();
+    vector.sort_by(|a, b| (b.1).cmp(a.1));
+    let top_number = std::cmp::min(vector.len(), number);
+    if json_format {
+        for (rank, (crate_name, size)) in vector.iter().take(top_number).enumerate() {
+            print_json(&TopRecord::new(crate_type, rank + 1, crate_name, **size));
+        }
+        return;
+    }
+    let title = format!("Top {top_number} {crate_type}");
+    let first_path_len = 40;
+    let second_path_len = 10;
+    let dash_len = first_path_len + second_path_len + 3;
+    show_title(title.as_str(), first_path_len, second_path_len, dash_len);
+    // check n size and determine if to print n number of output NONE for 0 crates
+    if vector.is_empty() {
+        println!("|{:^40}|{:^10}|", "NONE".color("red"), "0.000".color("red"));
+    } else {
+        (0..top_number).for_each(|i| print_index_value_crate(&vector, i));
+    }
+    print_dash(dash_len);
+}
+
+// print crate name
+#[allow(clippy::cast_precision_loss)]
+pub(crate) fn print_index_value_crate(vector: &[(&String, &u64)], i: usize) {
+    let crate_name = vector[i].0;
+    let size = vector[i].1;
+    let size = (*size as f64) / 1000_f64.powi(2);
+    println!("|{crate_name:^40}|{size:^10.3}|");
+}
+
+// show folder which would have been removed in dry run
+// path is debug formatted so it is printed within quotes
+#[allow(clippy::unnecessary_debug_formatting)]
+pub(crate) fn show_dry_run_removed(path: &Path) {
+    println!(
+        "{} {} {:?}",
+        "Dry run:".color("yellow"),
+        "Removed".color("red"),
+        path
+    );
+}
+
+// show status of removed crate
+pub(crate) fn show_removed_crate(
+    crate_name: &str,
+    kind: &str,
+    is_success: bool,
+    dry_run: bool,
+    json_format: bool,
+) {
+    if json_format {
+        print_json(&RemovedRecord::new(crate_name, kind, is_success, dry_run));
+    } else if dry_run {
+        println!(
+            "{} {} {:?}",
+            "Dry run:".color("yellow"),
+            "Removed".color("red"),
+            crate_name
+        );
+    } else if is_success {
+        println!("{} {:?}", "Removed".color("red"), crate_name);
+    } else {
+        println!("Partially failed to remove some directory and file of {crate_name:?}");
+    }
+}
+
+// show error due to which crate failed to be removed
+pub(crate) fn show_removal_error(error: &anyhow::Error) {
+    eprintln!("{}", format!("  \u{2514} {error:#}").color("red"));
+}
+
+// show status of removed crate along with error due to which removal failed
+pub(crate) fn show_removal_result(
+    crate_name: &str,
+    kind: &str,
+    result: &anyhow::Result<()>,
+    dry_run: bool,
+    json_format: bool,
+) {
+    show_removed_crate(crate_name, kind, result.is_ok(), dry_run, json_format);
+    if let Err(e) = result {
+        show_removal_error(e);
+    }
+}
+
+// show crates matched by selector
+pub(crate) fn show_selector_expansion(selector: &str, crates: &[&String], json_format: bool) {
+    if json_format {
+        print_json(&SelectorRecord::new(selector, crates));
+    } else if crates.is_empty() {
+        println!("Selector {selector:?} does not match any installed crate");
+    } else {
+        println!("Selector {selector:?} matches {crates:?}");
+    }
+}
+
+// show crate which is not removed since it is present in keep list
+pub(crate) fn show_kept_crate(crate_name: &str, kind: &str, json_format: bool) {
+    if json_format {
+        print_json(&KeptRecord::new(crate_name, kind));
+    } else {
+        println!("{} {:?}", "Kept".color("green"), crate_name);
+    }
+}
+
+// show status of crate restored from trash. Crate which is not restored is
+// kept in trash since its original location already exists
+// path is debug formatted so it is printed within quotes
+#[allow(clippy::unnecessary_debug_formatting)]
+pub(crate) fn show_restored_crate(
+    entry: &TrashEntry,
+    is_success: bool,
+    dry_run: bool,
+    json_format: bool,
+) {
+    if json_format {
+        print_json(&RestoredRecord::new(entry, is_success, dry_run));
+    } else if !is_success {
+        println!(
+            "Skipped {:?} since {:?} already exists",
+            entry.crate_name(),
+            entry.original_path()
+        );
+    } else if dry_run {
+        println!(
+            "{} {} {:?}",
+            "Dry run:".color("yellow"),
+            "Restored".color("green"),
+            entry.original_path()
+        );
+    } else {
+        println!("{} {:?}", "Restored".color("green"), entry.original_path());
+    }
+}
+
+// show batch present in trash. Purged batch is shown as removed
+// path is debug formatted so it is printed within quotes
+#[allow(clippy::unnecessary_debug_formatting)]
+pub(crate) fn show_trash_batch(batch: &TrashBatch, purged: bool, dry_run: bool, json_format: bool) {
+    if json_format {
+        print_json(&TrashRecord::new(batch, purged, dry_run));
+        return;
+    }
+    let age = SystemTime::now()
+        .duration_since(batch.created())
+        .unwrap_or_default();
+    let description = format!(
+        "{} ({} crates, {}, {} old)",
+        batch.id(),
+        batch.entries().len(),
+        convert_pretty(batch.size()),
+        convert_age(age)
+    );
+    if !purged {
+        println!("{description}");
+        for entry in batch.entries() {
+            println!("    {} {:?}", entry.crate_name(), entry.original_path());
+        }
+    } else if dry_run {
+        println!(
+            "{} {} {}",
+            "Dry run:".color("yellow"),
+            "Removed".color("red"),
+            description
+        );
+    } else {
+        println!("{} {}", "Removed".color("red"), description);
+    }
+}
+
+// show package installed by cargo install along with binaries owned by it
+pub(crate) fn show_installed_package(package: &InstalledPackage, json_format: bool) {
+    if json_format {
+        print_json(&BinRecord::new(package));
+        return;
+    }
+    println!(
+        "{} v{} ({})",
+        package.name().bold(),
+        package.version(),
+        package.source()
+    );
+    let mut features = package.features().to_vec();
+    if package.all_features() {
+        features.push("--all-features".to_string());
+    }
+    if package.no_default_features() {
+        features.push("--no-default-features".to_string());
+    }
+    if !features.is_empty() {
+        println!("    features: {}", features.join(", "));
+    }
+    if let Some(profile) = package.profile() {
+        println!("    profile: {profile}");
+    }
+    if let Some(target) = package.target() {
+        println!("    target: {target}");
+    }
+    if let Some(rustc) = package.rustc() {
+        println!("    rustc: {}", rustc.lines().next().unwrap_or_default());
+    }
+    for bin in package.bins() {
+        println!("    {}", bin.color("cyan"));
+    }
+}
+
+// show binary of bin directory along with its classification
+pub(crate) fn show_bin_entry(entry: &BinEntry, json_format: bool) {
+    if json_format {
+        print_json(&BinCheckRecord::new(entry));
+        return;
+    }
+    let color = match entry.status() {
+        BinStatus::Tracked => "green",
+        BinStatus::RustupProxy => "blue",
+        BinStatus::Untracked => "yellow",
+        BinStatus::Missing => "red",
+    };
+    match entry.package() {
+        Some(package) => println!(
+            "{:<30} {:<14} {}",
+            entry.name(),
+            entry.status().as_str().color(color),
+            package
+        ),
+        None => println!(
+            "{:<30} {}",
+            entry.name(),
+            entry.status().as_str().color(color)
+        ),
+    }
+}
+
+// convert seconds since unix epoch to age from now
+fn age_from_timestamp(timestamp: u64) -> String {
+    let time = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(timestamp);
+    convert_age(SystemTime::now().duration_since(time).unwrap_or_default())
+}
+
+// show run present in journal along with its total
+pub(crate) fn show_history_run(run: &JournalRun, json_format: bool) {
+    if json_format {
+        print_json(&HistoryRecord::new(run));
+    } else {
+        println!(
+            "{} ({} ago) {} removed {} crates of size {}",
+            run.run(),
+            age_from_timestamp(run.timestamp()),
+            run.reasons().join(",").color("blue"),
+            run.entries().len(),
+            convert_pretty(run.size())
+        );
+    }
+}
+
+// show single removal present in journal
+// path is debug formatted so it is printed within quotes
+#[allow(clippy::unnecessary_debug_formatting)]
+pub(crate) fn show_history_entry(entry: &JournalEntry, json_format: bool) {
+    if json_format {
+        print_json(&JournalRecord::new(entry));
+    } else {
+        let action = if entry.trashed() {
+            "trashed"
+        } else {
+            "removed"
+        };
+        println!(
+            "{} ({} ago) {} {} {:?} by {} of size {}",
+            entry.run(),
+            age_from_timestamp(entry.timestamp()),
+            action.color("red"),
+            entry.crate_name(),
+            entry.path(),
+            entry.reason().color("blue"),
+            convert_pretty(entry.size())
+        );
+    }
+}
+
+// convert duration to largest whole unit for displaying age
+pub(crate) fn convert_age(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let units = [("day", 24 * 60 * 60), ("hour", 60 * 60), ("minute", 60)];
+    for (unit, unit_seconds) in &units {
+        let count = seconds / unit_seconds;
+        if count > 0 {
+            let plural = if count == 1 { "" } else { "s" };
+            return format!("{count} {unit}{plural}");
+        }
+    }
+    let plural = if seconds == 1 { "" } else { "s" };
+    format!("{seconds} second{plural}")
+}
+
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+pub(crate) fn convert_pretty(num: u64) -> String {
+    if num == 0 {
+        return "0 B".to_string();
+    }
+    let num = num as f64;
+    let units = ["B", "kB", "MB", "GB", "TB"];
+    let factor = (num.log10() / 3_f64).floor();
+    let power_factor = if factor >= units.len() as f64 {
+        (units.len() - 1) as f64
+    } else {
+        factor
+    };
+    let pretty_bytes = format!("{:.3}", num / 1000_f64.powf(power_factor))
+        .parse::<f64>()
+        .unwrap();
+    let unit = units[power_factor as usize];
+    format!("{pretty_bytes} {unit}")
+}
+
+// convert size in bytes to MB used in text summary of removal operation
+#[allow(clippy::cast_precision_loss)]
+pub(crate) fn convert_mb(size: u64) -> f64 {
+    size as f64 / 1000_f64.powi(2)
+}
+
+// show summary of removal operation. Message is only used for text output
+pub(crate) fn show_removal_summary(
+    operation: &str,
+    message: &str,
+    crates_removed: usize,
+    size_cleaned: u64,
+    dry_run: bool,
+    json_format: bool,
+) {
+    if json_format {
+        print_json(&SummaryRecord::new(
+            operation,
+            crates_removed,
+            size_cleaned,
+            dry_run,
+        ));
+    } else {
+        println!("{}", message.color("blue"));
+    }
+}
+
+fn query_param_widths() -> (usize, usize) {
+    (50, 10)
+}
+
+pub(crate) fn query_full_width() -> usize {
+    let (a, b) = query_param_widths();
+    a + b + 1
+}
+
+// show size of query section. Label is only used for text output
+pub(crate) fn show_query(
+    label: &str,
+    section: &str,
+    count: Option<usize>,
+    size: u64,
+    json_format: bool,
+) {
+    if json_format {
+        print_json(&QueryRecord::new(section, None, count, size));
+    } else {
+        query_print(label, &convert_pretty(size));
+    }
+}
+
+// show size of target directory along with size of its profiles
+pub(crate) fn show_target_query(target_dir: &TargetDir, json_format: bool) {
+    let last_build = target_dir.last_build();
+    let profile_sizes = target_dir.list_profile_sizes().unwrap_or_default();
+    if json_format {
+        print_json(&QueryRecord::target(
+            "target",
+            target_dir.path(),
+            None,
+            last_build,
+            target_dir.size(),
+        ));
+        for (profile, size) in &profile_sizes {
+            print_json(&QueryRecord::target(
+                "target_profile",
+                target_dir.path(),
+                Some(profile),
+                None,
+                *size,
+            ));
+        }
+        return;
+    }
+    let built = last_build.map_or_else(
+        || "never built".to_string(),
+        |last_build| {
+            format!(
+                "built {} ago",
+                convert_age(last_build.elapsed().unwrap_or_default())
+            )
+        },
+    );
+    query_print(
+        &format!("{} ({}):", target_dir.path().display(), built),
+        &convert_pretty(target_dir.size()),
+    );
+    for (profile, size) in &profile_sizes {
+        query_print(&format!("    {profile}:"), &convert_pretty(*size));
+    }
+}
+
+// show size of query section of single registry
+pub(crate) fn show_registry_query(
+    label: &str,
+    section: &str,
+    registry: &str,
+    count: Option<usize>,
+    size: u64,
+    json_format: bool,
+) {
+    if json_format {
+        print_json(&QueryRecord::new(section, Some(registry), count, size));
+    } else {
+        query_print(label, &convert_pretty(size));
+    }
+}
+
+pub(crate) fn query_print(first_param: &str, second_param: &str) {
+    let (first_path_width, second_path_width) = query_param_widths();
+    println!("{first_param:first_path_width$} {second_param:>second_path_width$}");
+}
+
+// show value added to config file
+pub(crate) fn show_config_added(value: &str, dry_run: bool) {
+    if dry_run {
+        println!("{} Added {:?}", "Dry run:".color("yellow"), value);
+    } else {
+        println!("{} {:?}", "Added".color("red"), value);
+    }
+}
+
+// show value removed from config file
+pub(crate) fn show_config_removed(value: &str, dry_run: bool) {
+    if dry_run {
+        println!(
+            "{} {} {:?}",
+            "Dry run:".color("yellow"),
+            "Removed".color("red"),
+            value
+        );
+    } else {
+        println!("{} {:?}", "Removed".color("red"), value);
+    }
+}
+
+// show config value which was set
+pub(crate) fn show_config_set_value<T: std::fmt::Debug>(key: &str, value: T, dry_run: bool) {
+    if dry_run {
+        println!("{} Set {} to {:?}", "Dry run:".color("yellow"), key, value);
+    } else {
+        println!("Set {key} to {value:?}");
+    }
+}
+
+// show boolean config value which was set
+pub(crate) fn show_config_set(key: &str, value: bool, dry_run: bool) {
+    if dry_run {
+        println!("{} Set {} to {:?}", "Dry run:".color("yellow"), key, value);
+    } else {
+        println!("Set {key} to {value:?}");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{convert_age, convert_pretty};
+
+    const HOUR: u64 = 60 * 60;
+    const DAY: u64 = 24 * HOUR;
+
+    #[test]
+    fn test_convert_pretty() {
+        assert_eq!(convert_pretty(0), "0 B".to_string());
+        assert_eq!(convert_pretty(12), "12 B".to_string());
+        assert_eq!(convert_pretty(1234), "1.234 kB".to_string());
+        assert_eq!(convert_pretty(23908), "23.908 kB".to_string());
+        assert_eq!(convert_pretty(874_940_334), "874.94 MB".to_string());
+        assert_eq!(convert_pretty(8_849_909_404), "8.85 GB".to_string());
+        assert_eq!(convert_pretty(3_417_849_409_404), "3.418 TB".to_string());
+        assert_eq!(
+            convert_pretty(93_453_982_182_159_417),
+            "93453.982 TB".to_string()
+        );
+    }
+
+    #[test]
+    fn test_convert_age() {
+        assert_eq!(convert_age(Duration::from_secs(1)), "1 second".to_string());
+        assert_eq!(
+            convert_age(Duration::from_secs(59)),
+            "59 seconds".to_string()
+        );
+        assert_eq!(convert_age(Duration::from_secs(HOUR)), "1 hour".to_string());
+        assert_eq!(
+            convert_age(Duration::from_secs(200_000)),
+            "2 days".to_string()
+        );
+        assert_eq!(
+            convert_age(Duration::from_secs(30 * DAY)),
+            "30 days".to_string()
+        );
+    }
+}
@@ -0,0 +1,13 @@
+/// Progress of long running operation such as scanning or removing crates.
+/// Library do not print anything so caller provides its own implementation to
+/// show progress
+pub trait Progress: Send + Sync {
+    /// start new operation with total number of items
+    fn start(&self, title: &str, total: usize);
+
+    /// mark item as completed along with its size in bytes
+    fn advance(&self, item: &str, bytes: u64);
+
+    /// finish current operation
+    fn finish(&self);
+}
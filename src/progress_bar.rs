@@ -0,0 +1,129 @@
+use std::{
+    io::{self, IsTerminal, Write},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use cargo_trim::Progress;
+
+use crate::output::convert_pretty;
+
+// minimum interval between two redraw of progress bar
+const DRAW_INTERVAL: Duration = Duration::from_millis(50);
+// maximum number of characters of current item shown in progress bar
+const ITEM_WIDTH: usize = 40;
+
+#[derive(Default)]
+struct ProgressState {
+    title: String,
+    total: usize,
+    done: usize,
+    bytes: u64,
+    item: String,
+    is_drawn: bool,
+    last_draw: Option<Instant>,
+}
+
+// Single line progress bar drawn to stderr. Progress bar is only shown when
+// stdout and stderr both are terminal so piped and redirected output is not
+// affected
+pub(crate) struct ProgressBar {
+    enabled: bool,
+    state: Mutex<ProgressState>,
+}
+
+impl ProgressBar {
+    // create new progress bar which is enabled only for terminal
+    pub(crate) fn new() -> Self {
+        Self {
+            enabled: io::stdout().is_terminal() && io::stderr().is_terminal(),
+            state: Mutex::new(ProgressState::default()),
+        }
+    }
+
+    // clear progress bar while running provided function so output printed by
+    // function do not get mixed with progress bar
+    pub(crate) fn suspend<R>(&self, f: impl FnOnce() -> R) -> R {
+        if !self.enabled {
+            return f();
+        }
+        let mut state = self.state.lock().unwrap();
+        clear(&mut state);
+        let value = f();
+        draw(&mut state);
+        value
+    }
+}
+
+impl Progress for ProgressBar {
+    fn start(&self, title: &str, total: usize) {
+        if !self.enabled {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        clear(&mut state);
+        *state = ProgressState {
+            title: title.to_string(),
+            total,
+            ..ProgressState::default()
+        };
+        draw(&mut state);
+    }
+
+    fn advance(&self, item: &str, bytes: u64) {
+        if !self.enabled {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        state.done += 1;
+        state.bytes += bytes;
+        state.item = item.chars().take(ITEM_WIDTH).collect();
+        if state
+            .last_draw
+            .is_none_or(|last_draw| last_draw.elapsed() >= DRAW_INTERVAL)
+            || state.done == state.total
+        {
+            draw(&mut state);
+        }
+    }
+
+    fn finish(&self) {
+        if !self.enabled {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        clear(&mut state);
+        *state = ProgressState::default();
+    }
+}
+
+// draw progress bar in current line of stderr. Operation without any item is
+// not drawn
+fn draw(state: &mut ProgressState) {
+    if state.title.is_empty() || state.total == 0 {
+        return;
+    }
+    let mut stderr = io::stderr();
+    let _ = write!(
+        stderr,
+        "\r\x1b[2K{} [{}/{}] {} {}",
+        state.title,
+        state.done,
+        state.total,
+        convert_pretty(state.bytes),
+        state.item
+    );
+    let _ = stderr.flush();
+    state.is_drawn = true;
+    state.last_draw = Some(Instant::now());
+}
+
+// clear progress bar if it is drawn
+fn clear(state: &mut ProgressState) {
+    if state.is_drawn {
+        let mut stderr = io::stderr();
+        let _ = write!(stderr, "\r\x1b[2K");
+        let _ = stderr.flush();
+        state.is_drawn = false;
+    }
+}
@@ -1,43 +1,63 @@
-use std::{fs, path::Path};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
-use anyhow::{Context, Result};
-use colored::Colorize;
+use anyhow::{bail, Context, Result};
 
 use crate::{
-    crate_detail::CrateDetail,
-    utils::{clear_version_value, delete_folder},
+    journal::Journal,
+    trash::{remove_path, Trash},
+    utils::{clear_version_value, crate_matches, delete_folder},
 };
 
-// Stores .cargo/registry cache & src information
-pub(crate) struct RegistryDir<'a> {
-    cache_dir: &'a str,
-    src_dir: &'a str,
-    index_cache_dir: Vec<String>,
+/// Stores .cargo/registry cache & src information
+pub struct RegistryDir<'a> {
+    cache_dir: &'a Path,
+    src_dir: &'a Path,
+    index_cache_dir: Vec<(String, PathBuf)>,
     installed_crate: Vec<String>,
+    registry_filter: Option<Vec<String>>,
+    trash: Option<Arc<Trash>>,
+    journal: Option<Arc<Journal>>,
+    keep: Vec<String>,
 }
 
 impl<'a> RegistryDir<'a> {
-    // Create new RegistryDir
-    pub(crate) fn new(
+    /// Create new `RegistryDir`
+    pub fn new(
         cache_dir: &'a Path,
         src_dir: &'a Path,
         index_dir: &Path,
         installed_crate: &[String],
     ) -> Result<Self> {
-        let cache_dir = cache_dir.to_str().unwrap();
-        let src_dir = src_dir.to_str().unwrap();
         let mut index_cache_dir = Vec::new();
-        // read a index .cache dir folder for each registry by analyzing index folder
-        for entry in fs::read_dir(index_dir).context("failed to read index directory")? {
-            let entry = entry?.path();
-            let registry_dir = entry.as_path();
-            for folder in fs::read_dir(registry_dir).context("failed to read registry directory")? {
-                let folder = folder?.path();
-                let folder_name = folder
+        // read a index .cache dir folder for each registry by analyzing index folder.
+        // Both git and sparse registry store crate index cache in .cache folder
+        if index_dir.exists() {
+            for entry in fs::read_dir(index_dir).context("failed to read index directory")? {
+                let entry = entry?.path();
+                let registry_dir = entry.as_path();
+                if !registry_dir.is_dir() {
+                    continue;
+                }
+                let registry_name = registry_dir
                     .file_name()
-                    .context("failed to get file name form registry sub directory")?;
-                if folder_name == ".cache" {
-                    index_cache_dir.push(folder.to_str().unwrap().to_string());
+                    .context("failed to get registry name from index directory")?
+                    .to_str()
+                    .context("registry name of index directory is not valid utf-8")?
+                    .to_string();
+                for folder in
+                    fs::read_dir(registry_dir).context("failed to read registry directory")?
+                {
+                    let folder = folder?.path();
+                    let folder_name = folder
+                        .file_name()
+                        .context("failed to get file name form registry sub directory")?;
+                    if folder_name == ".cache" {
+                        index_cache_dir.push((registry_name.clone(), folder));
+                    }
                 }
             }
         }
@@ -47,76 +67,246 @@ impl<'a> RegistryDir<'a> {
             src_dir,
             index_cache_dir,
             installed_crate: installed_crate.to_owned(),
+            registry_filter: None,
+            trash: None,
+            journal: None,
+            keep: Vec::new(),
         })
     }
 
-    // Remove crate from src & cache directory
-    pub(crate) fn remove_crate(&mut self, crate_name: &str, dry_run: bool) {
-        let mut is_success;
+    /// only remove crates from provided registry folders
+    pub fn set_registry_filter(&mut self, registry_dirs: Vec<String>) {
+        self.index_cache_dir
+            .retain(|(registry_name, _)| registry_dirs.contains(registry_name));
+        self.registry_filter = Some(registry_dirs);
+    }
+
+    /// move removed crate archive and source to trash instead of deleting them
+    pub fn set_trash(&mut self, trash: Arc<Trash>) {
+        self.trash = Some(trash);
+    }
+
+    /// record removed crates in journal
+    pub fn set_journal(&mut self, journal: Arc<Journal>) {
+        self.journal = Some(journal);
+    }
+
+    /// set crate name, full name or glob pattern of crates which are never cleaned
+    pub fn set_keep(&mut self, keep: &[String]) {
+        self.keep = keep.to_vec();
+    }
+
+    /// check if crate is present in keep list
+    pub fn is_kept(&self, crate_name: &str) -> bool {
+        self.keep
+            .iter()
+            .any(|pattern| crate_matches(pattern, crate_name))
+    }
+
+    /// Remove source of all crates. Whole src directory is removed when keep list
+    /// is empty otherwise source of crates present in keep list is left in place
+    pub fn remove_all_src(&self, dry_run: bool) -> Result<()> {
+        self.remove_all_in(self.src_dir, "src", dry_run)
+            .context("failed to remove registry src")
+    }
+
+    /// Remove archive of all crates. Whole cache directory is removed when keep
+    /// list is empty otherwise archive of crates present in keep list is left in
+    /// place
+    pub fn remove_all_cache(&self, dry_run: bool) -> Result<()> {
+        self.remove_all_in(self.cache_dir, "cache", dry_run)
+            .context("failed to remove registry cache")
+    }
+
+    // remove folder as whole or when keep list is not empty remove each crate
+    // present in registry sub folder of folder which is not kept
+    fn remove_all_in(&self, path: &Path, folder_name: &str, dry_run: bool) -> Result<()> {
+        let trash = self.trash.as_deref();
+        let journal = self.journal.as_deref();
+        if self.keep.is_empty() {
+            return remove_path(path, folder_name, trash, journal, dry_run);
+        }
+        if !path.exists() {
+            return Ok(());
+        }
+        for registry in fs::read_dir(path)? {
+            let registry = registry?.path();
+            if !registry.is_dir() {
+                continue;
+            }
+            for entry in fs::read_dir(&registry)? {
+                let path = entry?.path();
+                // source folder is named same as crate and archive have .crate extension
+                let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+                    continue;
+                };
+                let crate_name = file_name.strip_suffix(".crate").unwrap_or(file_name);
+                if !self.is_kept(crate_name) {
+                    remove_path(&path, crate_name, trash, journal, dry_run)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove crate from src & cache directory. Error is returned if any directory
+    /// or file of crate failed to be removed
+    pub fn remove_crate(&mut self, crate_name: &str, dry_run: bool) -> Result<()> {
+        self.remove_crate_in(crate_name, None, dry_run)
+    }
+
+    /// Remove crate only from provided registry folders. Error is returned if any
+    /// directory or file of crate failed to be removed
+    pub fn remove_crate_from_registry(
+        &mut self,
+        crate_name: &str,
+        registry_dirs: &[String],
+        dry_run: bool,
+    ) -> Result<()> {
+        self.remove_crate_in(crate_name, Some(registry_dirs), dry_run)
+    }
+
+    // Remove crate from provided registry folders. If registry folders are not
+    // provided crate is removed from all registry which are not filtered out. All
+    // folders are tried even if removal of one fails and first error is returned.
+    // Crate present in keep list is never removed and error is returned instead
+    fn remove_crate_in(
+        &mut self,
+        crate_name: &str,
+        registry_dirs: Option<&[String]>,
+        dry_run: bool,
+    ) -> Result<()> {
+        if self.is_kept(crate_name) {
+            bail!("{crate_name} is present in keep list and is not removed");
+        }
+        let registry_filter = match (registry_dirs, &self.registry_filter) {
+            (Some(registry_dirs), Some(filter)) => Some(
+                registry_dirs
+                    .iter()
+                    .filter(|registry_dir| filter.contains(registry_dir))
+                    .cloned()
+                    .collect::<Vec<_>>(),
+            ),
+            (Some(registry_dirs), None) => Some(registry_dirs.to_vec()),
+            (None, filter) => filter.clone(),
+        };
+        // list registry which contains crate before removing crate
+        let mut crate_registry = self.crate_registry(crate_name);
+        let is_removed_from_all = registry_filter.as_ref().is_none_or(|filter| {
+            crate_registry
+                .iter()
+                .all(|registry_name| filter.contains(registry_name))
+        });
+        crate_registry.retain(|registry_name| {
+            registry_filter
+                .as_ref()
+                .is_none_or(|filter| filter.contains(registry_name))
+        });
         // remove crate from cache dir
-        is_success = remove_crate(Path::new(&self.cache_dir), crate_name, dry_run).is_ok();
-        // remove crate from index dir
-        is_success =
-            remove_crate(Path::new(&self.src_dir), crate_name, dry_run).is_ok() && is_success;
+        let mut result = remove_crate(
+            self.cache_dir,
+            crate_name,
+            registry_filter.as_ref(),
+            self.trash.as_deref(),
+            self.journal.as_deref(),
+            dry_run,
+        )
+        .with_context(|| format!("failed to remove {crate_name} from registry cache"));
+        // remove crate from src dir
+        result = result.and(
+            remove_crate(
+                self.src_dir,
+                crate_name,
+                registry_filter.as_ref(),
+                self.trash.as_deref(),
+                self.journal.as_deref(),
+                dry_run,
+            )
+            .with_context(|| format!("failed to remove {crate_name} from registry src")),
+        );
         let split_value = clear_version_value(crate_name);
         let name = split_value.0;
         let index_cache = self.index_cache_dir.clone();
         // remove index cache dir if their is only one crate. It will also clean crate
         // name from installed crate name owned locally by it so when two version of
-        // same crate is deleted it properly remove index cache
-        for index_cache_dir in &index_cache {
-            let same_name_list = self.installed_crate.iter().filter(|&x| x.contains(&name));
+        // same crate is deleted it properly remove index cache. Only index cache of
+        // registry which contains crate is cleaned
+        for (registry_name, index_cache_dir) in &index_cache {
+            if !crate_registry.contains(registry_name) {
+                continue;
+            }
+            let same_name_list = self
+                .installed_crate
+                .iter()
+                .filter(|&x| clear_version_value(x).0 == name);
             if same_name_list.count() == 1 {
-                is_success = remove_index_cache(Path::new(&index_cache_dir), crate_name, dry_run)
-                    .is_ok()
-                    && is_success;
+                result = result.and(
+                    remove_index_cache(
+                        index_cache_dir,
+                        crate_name,
+                        self.trash.as_deref(),
+                        self.journal.as_deref(),
+                        dry_run,
+                    )
+                    .with_context(|| format!("failed to remove index cache of {crate_name}")),
+                );
             }
-            is_success = remove_empty_index_cache_dir(Path::new(&index_cache_dir), dry_run).is_ok()
-                && is_success;
-            self.installed_crate.retain(|x| x != crate_name);
-        }
-        if dry_run {
-            println!(
-                "{} {} {:?}",
-                "Dry run:".color("yellow"),
-                "Removed".color("red"),
-                crate_name
-            );
-        } else if is_success {
-            println!("{} {:?}", "Removed".color("red"), crate_name);
-        } else {
-            println!(
-                "Partially failed to remove some directory and file of {:?}",
-                crate_name
+            result = result.and(
+                remove_empty_index_cache_dir(index_cache_dir, dry_run)
+                    .context("failed to remove empty index cache directory"),
             );
         }
+        // crate is still installed if it is present in registry which was not cleaned
+        if is_removed_from_all {
+            self.installed_crate.retain(|x| x != crate_name);
+        }
+        result
     }
 
-    // Remove list of crates
-    pub(crate) fn remove_crate_list(
-        &mut self,
-        crate_detail: &CrateDetail,
-        list: &[String],
-        dry_run: bool,
-    ) -> f64 {
-        let mut size_cleaned = 0.0;
-        for crate_name in list {
-            self.remove_crate(crate_name, dry_run);
-            size_cleaned += crate_detail.find(crate_name, "REGISTRY");
-        }
-        size_cleaned
+    // list name of registry which contains crate in cache or src directory
+    fn crate_registry(&self, crate_name: &str) -> Vec<String> {
+        self.index_cache_dir
+            .iter()
+            .filter(|(registry_name, _)| {
+                self.cache_dir
+                    .join(registry_name)
+                    .join(format!("{crate_name}.crate"))
+                    .exists()
+                    || self.src_dir.join(registry_name).join(crate_name).exists()
+            })
+            .map(|(registry_name, _)| registry_name.clone())
+            .collect()
     }
 }
 
-// Remove crates which name is provided to delete
-fn remove_crate(path: &Path, value: &str, dry_run: bool) -> Result<()> {
+// Remove crates which name is provided to delete. If registry filter is
+// provided crate is only removed from those registry folders. Crate is moved to
+// trash if trash is provided
+fn remove_crate(
+    path: &Path,
+    value: &str,
+    registry_filter: Option<&Vec<String>>,
+    trash: Option<&Trash>,
+    journal: Option<&Journal>,
+    dry_run: bool,
+) -> Result<()> {
     if path.exists() {
         for entry in fs::read_dir(path)? {
             let path = entry?.path();
+            if let Some(registry_dirs) = registry_filter {
+                let registry_name = path.file_name().and_then(|name| name.to_str());
+                if !registry_name.is_some_and(|name| registry_dirs.iter().any(|dir| dir == name)) {
+                    continue;
+                }
+            }
             for entry in fs::read_dir(path)? {
                 let path = entry?.path();
-                if path.to_str().unwrap().contains(value) {
-                    delete_folder(&path, dry_run)?;
+                // source folder is named same as crate and archive have .crate extension
+                let file_name = path.file_name().and_then(|name| name.to_str());
+                if file_name
+                    .is_some_and(|name| name == value || name.strip_suffix(".crate") == Some(value))
+                {
+                    remove_path(&path, value, trash, journal, dry_run)?;
                 }
             }
         }
@@ -125,10 +315,16 @@ fn remove_crate(path: &Path, value: &str, dry_run: bool) -> Result<()> {
 }
 
 // determine crate index cache location and remove crate index cache
-fn remove_index_cache(path: &Path, crate_name: &str, dry_run: bool) -> Result<()> {
+fn remove_index_cache(
+    path: &Path,
+    crate_name: &str,
+    trash: Option<&Trash>,
+    journal: Option<&Journal>,
+    dry_run: bool,
+) -> Result<()> {
     let mut crate_index_cache_location = path.to_path_buf();
-    let split_value = clear_version_value(crate_name);
-    let name = split_value.0;
+    // index cache of crate is stored in lowercase crate name
+    let name = clear_version_value(crate_name).0.to_lowercase();
     match name.len() {
         1 => {
             crate_index_cache_location.push("1");
@@ -148,26 +344,131 @@ fn remove_index_cache(path: &Path, crate_name: &str, dry_run: bool) -> Result<()
             crate_index_cache_location.push(&name[2..4]);
             crate_index_cache_location.push(name);
         }
-    };
-    delete_folder(&crate_index_cache_location, dry_run)?;
-    Ok(())
+    }
+    remove_path(
+        &crate_index_cache_location,
+        crate_name,
+        trash,
+        journal,
+        dry_run,
+    )
 }
 
-// check if any index cache folder is empty if it is it is removed out
+// check if any index cache folder is empty if it is it is removed out. Sub
+// folder are checked first so folder which only contains empty folder is also
+// removed
 fn remove_empty_index_cache_dir(path: &Path, dry_run: bool) -> Result<()> {
-    if path
-        .read_dir()
-        .map(|mut i| i.next().is_none())
-        .unwrap_or(false)
-    {
-        delete_folder(path, dry_run)?;
-    } else {
-        for entry in path.read_dir()? {
-            let path = entry?.path();
-            if path.is_dir() {
-                remove_empty_index_cache_dir(path.as_path(), dry_run)?;
-            }
+    // index cache can already be removed by light cleanup or wipe
+    if !path.exists() {
+        return Ok(());
+    }
+    for entry in path.read_dir()? {
+        let path = entry?.path();
+        if path.is_dir() {
+            remove_empty_index_cache_dir(path.as_path(), dry_run)?;
         }
     }
+    if path.read_dir().is_ok_and(|mut i| i.next().is_none()) {
+        delete_folder(path, dry_run)?;
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use crate::{
+        config_file::ConfigFile,
+        test_util::{dir_path, fake_cargo_home, registry_dir, scan, REGISTRY, SPARSE_REGISTRY},
+        utils::list_registry_index,
+    };
+
+    #[test]
+    fn test_remove_registry_crate() {
+        let cargo_home = fake_cargo_home("remove", REGISTRY);
+        let dir_path = dir_path(&cargo_home);
+        let (_, _, crate_list) = scan(&dir_path);
+        let mut registry_dir = registry_dir(&dir_path, &crate_list);
+        let src = dir_path.src_dir().join(REGISTRY).join("alpha-0.1.0");
+        registry_dir.remove_crate("alpha-0.1.0", true).unwrap();
+        assert!(src.exists());
+        registry_dir.remove_crate("alpha-0.1.0", false).unwrap();
+        assert!(!src.exists());
+        assert!(dir_path
+            .src_dir()
+            .join(REGISTRY)
+            .join("alpha-0.2.0")
+            .exists());
+    }
+
+    // test index cache of sparse registry is cleaned with crate
+    #[test]
+    fn test_remove_sparse_registry_crate() {
+        let cargo_home = fake_cargo_home("sparse", SPARSE_REGISTRY);
+        let index_cache = cargo_home
+            .path()
+            .join("registry/index")
+            .join(SPARSE_REGISTRY)
+            .join(".cache");
+        for index_path in &["al/ph/alpha", "ze/ta/zeta"] {
+            let index_path = index_cache.join(index_path);
+            fs::create_dir_all(index_path.parent().unwrap()).unwrap();
+            fs::write(index_path, "index").unwrap();
+        }
+        let dir_path = dir_path(&cargo_home);
+        let (git_index, sparse_index) = list_registry_index(dir_path.index_dir()).unwrap();
+        assert!(git_index.is_empty());
+        assert_eq!(
+            sparse_index,
+            vec![dir_path.index_dir().join(SPARSE_REGISTRY)]
+        );
+        let (_, _, crate_list) = scan(&dir_path);
+        let mut registry_dir = registry_dir(&dir_path, &crate_list);
+        registry_dir.remove_crate("zeta-1.0.0", false).unwrap();
+        assert!(!dir_path
+            .src_dir()
+            .join(SPARSE_REGISTRY)
+            .join("zeta-1.0.0")
+            .exists());
+        assert!(!index_cache.join("ze").exists());
+        // other version of alpha is still present so index cache is kept
+        registry_dir.remove_crate("alpha-0.1.0", false).unwrap();
+        assert!(index_cache.join("al/ph/alpha").exists());
+        registry_dir.remove_crate("alpha-0.2.0", false).unwrap();
+        assert!(!index_cache.join("al").exists());
+    }
+
+    // test keep list stored in config file protect crates
+    #[test]
+    fn test_keep_list() {
+        let cargo_home = fake_cargo_home("keep", REGISTRY);
+        let dir_path = dir_path(&cargo_home);
+        let mut config_file = ConfigFile::init(dir_path.config_file()).unwrap();
+        config_file.add_keep("zeta", false, true).unwrap();
+        config_file.add_keep("alpha-0.1.*", false, true).unwrap();
+        config_file.add_keep("beta", false, true).unwrap();
+        config_file.remove_keep("beta", false, true).unwrap();
+        let (config_file, _, crate_list) = scan(&dir_path);
+        assert_eq!(config_file.keep(), &vec!["zeta", "alpha-0.1.*"]);
+        let mut registry_dir = registry_dir(&dir_path, &crate_list);
+        registry_dir.set_keep(config_file.keep());
+        assert!(registry_dir.is_kept("zeta-1.0.0"));
+        assert!(registry_dir.is_kept("alpha-0.1.0"));
+        assert!(!registry_dir.is_kept("alpha-0.2.0"));
+        let src = dir_path.src_dir().join(REGISTRY);
+        let cache = dir_path.cache_dir().join(REGISTRY);
+        // kept crate is not removed even when it is removed directly
+        assert!(registry_dir.remove_crate("zeta-1.0.0", false).is_err());
+        assert!(src.join("zeta-1.0.0").exists());
+        assert!(cache.join("zeta-1.0.0.crate").exists());
+        // cleaning whole src and cache folder only removes crates which are not kept
+        registry_dir.remove_all_src(false).unwrap();
+        registry_dir.remove_all_cache(false).unwrap();
+        assert!(src.join("zeta-1.0.0").exists());
+        assert!(src.join("alpha-0.1.0").exists());
+        assert!(!src.join("alpha-0.2.0").exists());
+        assert!(cache.join("zeta-1.0.0.crate").exists());
+        assert!(!cache.join("alpha-0.2.0.crate").exists());
+    }
+}
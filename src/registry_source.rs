@@ -0,0 +1,202 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::{Context, Result};
+
+use crate::{
+    dir_path::DirPath,
+    source_hash::{normalize_url, registry_dir_names},
+};
+
+// name used by cargo for crates.io registry
+const CRATES_IO_NAME: &str = "crates-io";
+// index url of crates.io registry for git and sparse protocol
+const CRATES_IO_INDEX: &str = "https://github.com/rust-lang/crates.io-index";
+const CRATES_IO_SPARSE_INDEX: &str = "sparse+https://index.crates.io/";
+
+/// Stores name and index url of registries which is used to map registry folder
+/// name present in cargo home to registry
+#[derive(Default)]
+pub struct RegistrySource {
+    registry: HashMap<String, (String, String)>,
+    filter: Option<String>,
+}
+
+impl RegistrySource {
+    /// create registry source from crates.io registry and registries present in
+    /// `[registries]` table of `$CARGO_HOME/config.toml`
+    pub fn new(cargo_home: &Path) -> Result<Self> {
+        let mut registry_source = Self::default();
+        registry_source.add_registry(CRATES_IO_NAME, CRATES_IO_INDEX);
+        registry_source.add_registry(CRATES_IO_NAME, CRATES_IO_SPARSE_INDEX);
+        // cargo reads config file without extension if config.toml is not present
+        let config_path = [cargo_home.join("config.toml"), cargo_home.join("config")]
+            .iter()
+            .find(|path| path.is_file())
+            .cloned();
+        if let Some(config_path) = config_path {
+            let content =
+                fs::read_to_string(&config_path).context("failed to read cargo config file")?;
+            let config: toml::Value =
+                toml::from_str(&content).context("failed to parse cargo config file")?;
+            if let Some(registries) = config.get("registries").and_then(toml::Value::as_table) {
+                for (name, value) in registries {
+                    if let Some(index) = value.get("index").and_then(toml::Value::as_str) {
+                        registry_source.add_registry(name, index);
+                    }
+                }
+            }
+        }
+        Ok(registry_source)
+    }
+
+    /// only use registry which matches with provided registry name, url or folder
+    /// name
+    pub fn set_filter(&mut self, registry: &str) {
+        self.filter = Some(registry.to_string());
+    }
+
+    /// return registry filter value
+    pub fn filter(&self) -> Option<&str> {
+        self.filter.as_deref()
+    }
+
+    // add registry with its index url
+    fn add_registry(&mut self, name: &str, index_url: &str) {
+        for dir_name in registry_dir_names(index_url) {
+            self.registry
+                .insert(dir_name, (name.to_string(), index_url.to_string()));
+        }
+    }
+
+    /// return name of registry from registry folder name
+    pub fn name(&self, registry_dir: &str) -> Option<&str> {
+        self.registry
+            .get(registry_dir)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// return index url of registry from registry folder name
+    pub fn url(&self, registry_dir: &str) -> Option<&str> {
+        self.registry.get(registry_dir).map(|(_, url)| url.as_str())
+    }
+
+    /// return name of registry which can be shown to user. Registry folder name is
+    /// returned if registry is not known
+    pub fn display_name(&self, registry_dir: &str) -> String {
+        match self.registry.get(registry_dir) {
+            Some((name, url)) => format!("{name} ({url})"),
+            None => registry_dir.to_string(),
+        }
+    }
+
+    /// check if registry folder matches with registry value. Registry value can be
+    /// name of registry, index url of registry or registry folder name
+    pub fn matches(&self, registry_dir: &str, registry: &str) -> bool {
+        if registry_dir == registry {
+            return true;
+        }
+        self.registry.get(registry_dir).is_some_and(|(name, url)| {
+            name == registry || normalize_url(url) == normalize_url(registry)
+        })
+    }
+
+    /// list registry folder names which are present in registry cache, index and
+    /// src folder. If registry filter is set only matching folder are listed
+    pub fn list_registry_dir(&self, dir_path: &DirPath) -> Result<Vec<String>> {
+        let mut registry_dirs = list_registry_dir_name(dir_path)?;
+        if let Some(registry) = self.filter() {
+            registry_dirs.retain(|registry_dir| self.matches(registry_dir, registry));
+        }
+        Ok(registry_dirs)
+    }
+}
+
+// list registry folder names which are present in registry cache, index and
+// src folder
+pub(crate) fn list_registry_dir_name(dir_path: &DirPath) -> Result<Vec<String>> {
+    let mut registry_dirs = Vec::new();
+    for folder in &[
+        dir_path.cache_dir(),
+        dir_path.index_dir(),
+        dir_path.src_dir(),
+    ] {
+        if !folder.exists() {
+            continue;
+        }
+        for entry in fs::read_dir(folder).context("failed to read registry folder")? {
+            let entry = entry?.path();
+            if !entry.is_dir() {
+                continue;
+            }
+            let registry_dir = entry
+                .file_name()
+                .context("failed to get registry folder name")?
+                .to_str()
+                .unwrap()
+                .to_string();
+            registry_dirs.push(registry_dir);
+        }
+    }
+    registry_dirs.sort();
+    registry_dirs.dedup();
+    Ok(registry_dirs)
+}
+
+// list possible registry folder names of registry source present in Cargo.lock.
+// crates.io registry is always recorded with its git index url in Cargo.lock
+// even when it is fetched using sparse protocol
+pub(crate) fn lock_source_registry_dirs(source: &str) -> Vec<String> {
+    let mut registry_dirs = registry_dir_names(source);
+    if normalize_url(source.trim_start_matches("registry+")) == CRATES_IO_INDEX {
+        registry_dirs.append(&mut registry_dir_names(CRATES_IO_SPARSE_INDEX));
+    }
+    registry_dirs
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::{lock_source_registry_dirs, RegistrySource};
+    use crate::{source_hash::registry_dir_names, test_util::TempDir};
+
+    #[test]
+    fn test_registry_source() {
+        let temp_dir = TempDir::new("registry");
+        let cargo_home = temp_dir.path();
+        fs::create_dir_all(cargo_home).unwrap();
+        fs::write(
+            cargo_home.join("config.toml"),
+            "[registries]\nmy-registry = { index = \"sparse+https://my-host/index/\" }\n",
+        )
+        .unwrap();
+        let registry_source = RegistrySource::new(cargo_home).unwrap();
+        let crates_io_dir = "index.crates.io-1949cf8c6b5b557f";
+        assert_eq!(registry_source.name(crates_io_dir), Some("crates-io"));
+        assert!(registry_source.matches(crates_io_dir, "crates-io"));
+        assert!(registry_source.matches(crates_io_dir, "sparse+https://index.crates.io"));
+        assert!(!registry_source.matches(crates_io_dir, "my-registry"));
+        for registry_dir in registry_dir_names("sparse+https://my-host/index/") {
+            assert!(registry_dir.starts_with("my-host-"));
+            assert_eq!(registry_source.name(&registry_dir), Some("my-registry"));
+            assert_eq!(
+                registry_source.url(&registry_dir),
+                Some("sparse+https://my-host/index/")
+            );
+        }
+        assert_eq!(registry_source.name("my-host-0123456789abcdef"), None);
+    }
+
+    #[test]
+    fn test_lock_source_registry_dirs() {
+        let crates_io_dirs =
+            lock_source_registry_dirs("registry+https://github.com/rust-lang/crates.io-index");
+        assert!(crates_io_dirs.contains(&"github.com-1ecc6299db9ec823".to_string()));
+        assert!(crates_io_dirs.contains(&"index.crates.io-1949cf8c6b5b557f".to_string()));
+        let sparse_dirs = lock_source_registry_dirs("sparse+https://my-host/index/");
+        assert_eq!(
+            sparse_dirs,
+            registry_dir_names("sparse+https://my-host/index/")
+        );
+    }
+}
@@ -0,0 +1,252 @@
+// Cargo names registry and git folder present in cargo home as `<ident>-<hash>`
+// where hash is computed from source url. Older cargo version compute hash using
+// 64 bit SipHash-2-4 whereas newer cargo version use 128 bit SipHash-1-3 so both
+// names are computed and matched with folder present in cargo home
+
+use std::fmt::Write;
+
+// discriminant of registry kind used by cargo while hashing source
+const REGISTRY_KIND: u8 = 2;
+const SPARSE_REGISTRY_KIND: u8 = 3;
+
+// list possible folder names of registry index url
+pub(crate) fn registry_dir_names(index_url: &str) -> Vec<String> {
+    let (kind, url) = if index_url.starts_with("sparse+") {
+        (SPARSE_REGISTRY_KIND, normalize_url(index_url))
+    } else {
+        (
+            REGISTRY_KIND,
+            normalize_url(index_url.trim_start_matches("registry+")),
+        )
+    };
+    let host = url_host(&url);
+    hash_names(Some(kind), &url)
+        .into_iter()
+        .map(|hash| format!("{host}-{hash}"))
+        .collect()
+}
+
+// list possible folder names of git repository url present in git db and
+// checkouts folder. Cargo hash canonical url of repository without any kind so
+// same repository accessed with different url form share same folder
+pub(crate) fn git_dir_names(repository_url: &str) -> Vec<String> {
+    let url = canonical_git_url(repository_url);
+    let ident = url.rsplit('/').next().unwrap_or("");
+    let ident = if ident.is_empty() || url.ends_with("://") {
+        "_empty"
+    } else {
+        ident
+    };
+    hash_names(None, &url)
+        .into_iter()
+        .map(|hash| format!("{ident}-{hash}"))
+        .collect()
+}
+
+// canonicalize git repository url in same way as cargo. Query and fragment are
+// removed, trailing slash and .git extension are removed and github url are
+// lowercased and always use https scheme
+pub(crate) fn canonical_git_url(repository_url: &str) -> String {
+    let url = repository_url.trim_start_matches("git+");
+    let url = url.split(['?', '#']).next().unwrap_or(url);
+    let mut url = normalize_url(url);
+    if url_host(&url) == "github.com" {
+        if let Some((_, rest)) = url.split_once("://") {
+            url = format!("https://{}", rest.to_lowercase());
+        }
+    }
+    if url.ends_with('/') && !url.ends_with("://") {
+        url.pop();
+    }
+    if let Some(stripped) = url.strip_suffix(".git") {
+        if !stripped.ends_with('/') {
+            url = stripped.to_string();
+        }
+    }
+    url
+}
+
+// return host part of url
+pub(crate) fn url_host(url: &str) -> &str {
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = without_scheme.split('/').next().unwrap_or("");
+    let host = authority.rsplit('@').next().unwrap_or("");
+    host.split(':').next().unwrap_or("")
+}
+
+// lowercase scheme and host of url and add root path if url do not contain path
+pub(crate) fn normalize_url(url: &str) -> String {
+    if let Some((scheme, rest)) = url.split_once("://") {
+        let (authority, path) = rest.find('/').map_or((rest, "/"), |pos| rest.split_at(pos));
+        format!(
+            "{}://{}{}",
+            scheme.to_lowercase(),
+            authority.to_lowercase(),
+            path
+        )
+    } else {
+        url.to_string()
+    }
+}
+
+// compute legacy and stable hash value of source. Kind is not hashed if it is
+// not provided
+fn hash_names(kind: Option<u8>, url: &str) -> Vec<String> {
+    let mut legacy_bytes = Vec::new();
+    let mut stable_bytes = Vec::new();
+    if let Some(kind) = kind {
+        // legacy hasher hash discriminant as 8 byte whereas stable hasher hash small
+        // value as single byte
+        legacy_bytes.extend_from_slice(&u64::from(kind).to_le_bytes());
+        stable_bytes.push(kind);
+    }
+    for bytes in [&mut legacy_bytes, &mut stable_bytes] {
+        bytes.extend_from_slice(url.as_bytes());
+        bytes.push(0xff);
+    }
+    let (legacy, _) = sip_hash(&legacy_bytes, 2, 4, false);
+    let (first, second) = sip_hash(&stable_bytes, 1, 3, true);
+    let stable = first.wrapping_mul(3).wrapping_add(second);
+    vec![to_hex(legacy), to_hex(stable)]
+}
+
+// convert hash value to hex string in same way as cargo
+fn to_hex(value: u64) -> String {
+    value
+        .to_le_bytes()
+        .iter()
+        .fold(String::new(), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
+
+// compute SipHash of data with zero key. When wide is true 128 bit hash is
+// returned as two u64 otherwise only first value is used
+fn sip_hash(data: &[u8], c_rounds: usize, d_rounds: usize, wide: bool) -> (u64, u64) {
+    let mut state = [
+        0x736f_6d65_7073_6575_u64,
+        0x646f_7261_6e64_6f6d_u64,
+        0x6c79_6765_6e65_7261_u64,
+        0x7465_6462_7974_6573_u64,
+    ];
+    if wide {
+        state[1] ^= 0xee;
+    }
+    let mut chunks = data.chunks_exact(8);
+    for chunk in &mut chunks {
+        let mut word = [0_u8; 8];
+        word.copy_from_slice(chunk);
+        compress(&mut state, u64::from_le_bytes(word), c_rounds);
+    }
+    let mut last_word = (data.len() as u64 & 0xff) << 56;
+    for (position, byte) in chunks.remainder().iter().enumerate() {
+        last_word |= u64::from(*byte) << (8 * position);
+    }
+    compress(&mut state, last_word, c_rounds);
+    state[2] ^= if wide { 0xee } else { 0xff };
+    sip_rounds(&mut state, d_rounds);
+    let first = state[0] ^ state[1] ^ state[2] ^ state[3];
+    if !wide {
+        return (first, 0);
+    }
+    state[1] ^= 0xdd;
+    sip_rounds(&mut state, d_rounds);
+    let second = state[0] ^ state[1] ^ state[2] ^ state[3];
+    (first, second)
+}
+
+// compress single word into state
+fn compress(state: &mut [u64; 4], word: u64, rounds: usize) {
+    state[3] ^= word;
+    sip_rounds(state, rounds);
+    state[0] ^= word;
+}
+
+// run sip round for provided number of times
+fn sip_rounds(state: &mut [u64; 4], rounds: usize) {
+    for _ in 0..rounds {
+        state[0] = state[0].wrapping_add(state[1]);
+        state[1] = state[1].rotate_left(13) ^ state[0];
+        state[0] = state[0].rotate_left(32);
+        state[2] = state[2].wrapping_add(state[3]);
+        state[3] = state[3].rotate_left(16) ^ state[2];
+        state[0] = state[0].wrapping_add(state[3]);
+        state[3] = state[3].rotate_left(21) ^ state[0];
+        state[2] = state[2].wrapping_add(state[1]);
+        state[1] = state[1].rotate_left(17) ^ state[2];
+        state[2] = state[2].rotate_left(32);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{canonical_git_url, git_dir_names, normalize_url, registry_dir_names, url_host};
+
+    #[test]
+    fn test_registry_dir_names() {
+        assert_eq!(
+            registry_dir_names("https://github.com/rust-lang/crates.io-index"),
+            vec![
+                "github.com-1ecc6299db9ec823".to_string(),
+                "github.com-25cdd57fae9f0462".to_string()
+            ]
+        );
+        assert_eq!(
+            registry_dir_names("sparse+https://index.crates.io/"),
+            vec![
+                "index.crates.io-6f17d22bba15001f".to_string(),
+                "index.crates.io-1949cf8c6b5b557f".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_git_dir_names() {
+        let git_dirs = git_dir_names("git+file:///tmp/gitchk/repo/#eab33b60b5593c11");
+        assert_eq!(git_dirs.len(), 2);
+        assert_eq!(git_dirs[1], "repo-1e82ad8f16715332");
+        assert!(git_dirs[0].starts_with("repo-"));
+        assert_eq!(
+            git_dir_names("https://github.com/Foo/Bar.git"),
+            git_dir_names("git+http://github.com/foo/bar?branch=main#0123456")
+        );
+        assert_ne!(
+            git_dir_names("https://github.com/foo/bar"),
+            git_dir_names("https://github.com/baz/bar")
+        );
+    }
+
+    #[test]
+    fn test_canonical_git_url() {
+        assert_eq!(
+            canonical_git_url("git+https://GitHub.com/Foo/Bar.git?rev=abc#abcdef"),
+            "https://github.com/foo/bar"
+        );
+        assert_eq!(
+            canonical_git_url("https://gitlab.com/Foo/Bar/"),
+            "https://gitlab.com/Foo/Bar"
+        );
+    }
+
+    #[test]
+    fn test_url_host() {
+        assert_eq!(
+            url_host("sparse+https://index.crates.io/"),
+            "index.crates.io"
+        );
+        assert_eq!(url_host("https://user@my-host:8080/git/index"), "my-host");
+    }
+
+    #[test]
+    fn test_normalize_url() {
+        assert_eq!(
+            normalize_url("sparse+https://Index.Crates.io"),
+            "sparse+https://index.crates.io/"
+        );
+        assert_eq!(
+            normalize_url("https://github.com/rust-lang/crates.io-index"),
+            "https://github.com/rust-lang/crates.io-index"
+        );
+    }
+}
@@ -0,0 +1,728 @@
+// Target directories of projects which stores build artifacts. Target directory
+// of project is resolved same as cargo by reading CARGO_TARGET_DIR environment
+// variable and build.target-dir value of cargo config files
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    env, fs,
+    io::Read,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{Context, Result};
+
+use crate::{
+    config_file::ConfigFile,
+    dir_path::DirPath,
+    journal::Journal,
+    trash::{remove_path, Trash},
+    utils::{get_last_used, get_size},
+};
+
+// folders of profile directory which contains build artifacts
+const ARTIFACT_DIRS: [&str; 5] = ["build", "deps", "examples", ".fingerprint", "incremental"];
+
+/// Single build artifact present inside artifact folder of target directory
+pub struct TargetArtifact {
+    path: PathBuf,
+    size: u64,
+    last_used: SystemTime,
+}
+
+impl TargetArtifact {
+    /// return path of artifact
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// return size of artifact in bytes
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// return last used time of artifact
+    pub fn last_used(&self) -> SystemTime {
+        self.last_used
+    }
+
+    /// remove artifact. Artifact is moved to trash if trash is provided and removal
+    /// is recorded in journal if journal is provided
+    pub fn remove(
+        &self,
+        trash: Option<&Trash>,
+        journal: Option<&Journal>,
+        dry_run: bool,
+    ) -> Result<()> {
+        let name = self.path.file_name().unwrap_or_default().to_string_lossy();
+        remove_path(&self.path, &name, trash, journal, dry_run)
+    }
+}
+
+/// Target directory along with projects which uses it
+pub struct TargetDir {
+    path: PathBuf,
+    projects: Vec<PathBuf>,
+    size: u64,
+    last_used: Option<SystemTime>,
+}
+
+impl TargetDir {
+    /// list target directory of all projects present in config file directories.
+    /// Projects which share same target directory are grouped together and target
+    /// directory which does not exist is skipped
+    pub fn list(dir_path: &DirPath, config_file: &ConfigFile) -> Result<Vec<Self>> {
+        let mut target_projects: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::new();
+        for directory in config_file.directory() {
+            let cargo_toml_location = config_file.list_cargo_toml(Path::new(directory))?;
+            for project in cargo_toml_location.location_path() {
+                // directory value can be path of Cargo.toml file itself
+                let project = if project.is_file() {
+                    project.parent().unwrap_or(project)
+                } else {
+                    project.as_path()
+                };
+                let target = resolve_target_dir(project, dir_path.home_dir())?;
+                let projects = target_projects.entry(target).or_default();
+                if !projects.iter().any(|path| path == project) {
+                    projects.push(project.to_path_buf());
+                }
+            }
+        }
+        let mut target_dirs = Vec::new();
+        for (path, mut projects) in target_projects {
+            if !path.is_dir() {
+                continue;
+            }
+            projects.sort();
+            target_dirs.push(Self {
+                size: get_size(&path).unwrap_or(0),
+                last_used: get_last_used(&path).ok(),
+                path,
+                projects,
+            });
+        }
+        Ok(target_dirs)
+    }
+
+    /// return path of target directory
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// return projects which uses target directory
+    pub fn projects(&self) -> &[PathBuf] {
+        &self.projects
+    }
+
+    /// return size of target directory in bytes
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// return last used time of target directory which is latest used time of any
+    /// file present inside target directory
+    pub fn last_used(&self) -> Option<SystemTime> {
+        self.last_used
+    }
+
+    /// check if target directory is not used for provided duration. Target directory
+    /// with unknown last used time is treated as inactive
+    pub fn is_inactive(&self, duration: Duration) -> bool {
+        self.last_used.is_none_or(|last_used| {
+            SystemTime::now()
+                .duration_since(last_used)
+                .unwrap_or_default()
+                >= duration
+        })
+    }
+
+    /// list artifacts of all profiles which are not used for provided duration.
+    /// Profile directory such as debug, release or <triple>/debug is directory
+    /// which contains .fingerprint folder
+    pub fn list_artifacts_older_than(&self, duration: Duration) -> Result<Vec<TargetArtifact>> {
+        let mut artifacts = Vec::new();
+        for profile_dir in list_profile_dirs(&self.path)? {
+            for artifact_dir in &ARTIFACT_DIRS {
+                let artifact_dir = profile_dir.join(artifact_dir);
+                if !artifact_dir.is_dir() {
+                    continue;
+                }
+                for entry in
+                    fs::read_dir(&artifact_dir).context("failed to read artifact directory")?
+                {
+                    let path = entry?.path();
+                    let last_used = get_last_used(&path)?;
+                    let age = SystemTime::now()
+                        .duration_since(last_used)
+                        .unwrap_or_default();
+                    if age >= duration {
+                        artifacts.push(TargetArtifact {
+                            size: get_size(&path).unwrap_or(0),
+                            path,
+                            last_used,
+                        });
+                    }
+                }
+            }
+        }
+        artifacts.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(artifacts)
+    }
+
+    /// return last build time of target directory which is latest modification time
+    /// of fingerprint of any profile. Cargo touches invoked.timestamp file of
+    /// fingerprint every time unit is built
+    pub fn last_build(&self) -> Option<SystemTime> {
+        list_profile_dirs(&self.path)
+            .ok()?
+            .iter()
+            .filter_map(|profile_dir| fs::read_dir(profile_dir.join(".fingerprint")).ok())
+            .flatten()
+            .filter_map(Result::ok)
+            .filter_map(|entry| {
+                let metadata = entry
+                    .path()
+                    .join("invoked.timestamp")
+                    .metadata()
+                    .or_else(|_| entry.metadata())
+                    .ok()?;
+                metadata.modified().ok()
+            })
+            .max()
+    }
+
+    /// list size of each profile such as debug, release, doc or
+    /// x86_64-unknown-linux-gnu/debug present in target directory. Size of
+    /// remaining files such as package folder is listed as other
+    pub fn list_profile_sizes(&self) -> Result<Vec<(String, u64)>> {
+        let mut profile_dirs = list_profile_dirs(&self.path)?;
+        // doc folder is created by cargo doc at target directory or target triple
+        // directory and it does not contain fingerprint
+        let mut doc_parents = vec![self.path.clone()];
+        for profile_dir in &profile_dirs {
+            if let Some(parent) = profile_dir.parent() {
+                if !doc_parents.iter().any(|doc_parent| doc_parent == parent) {
+                    doc_parents.push(parent.to_path_buf());
+                }
+            }
+        }
+        for doc_parent in doc_parents {
+            let doc_dir = doc_parent.join("doc");
+            if doc_dir.is_dir() {
+                profile_dirs.push(doc_dir);
+            }
+        }
+        profile_dirs.sort();
+        let mut profile_sizes = Vec::new();
+        let mut listed_size = 0;
+        for profile_dir in profile_dirs {
+            let size = get_size(&profile_dir).unwrap_or(0);
+            listed_size += size;
+            let name = profile_dir
+                .strip_prefix(&self.path)
+                .unwrap_or(&profile_dir)
+                .to_string_lossy()
+                .to_string();
+            profile_sizes.push((name, size));
+        }
+        let other_size = self.size.saturating_sub(listed_size);
+        if other_size > 0 {
+            profile_sizes.push(("other".to_string(), other_size));
+        }
+        Ok(profile_sizes)
+    }
+
+    /// list incremental folder of all profiles. Incremental folder only contains
+    /// incremental compilation cache which is safe to remove
+    pub fn list_incremental_dirs(&self) -> Result<Vec<TargetArtifact>> {
+        let mut incremental_dirs = Vec::new();
+        for profile_dir in list_profile_dirs(&self.path)? {
+            let path = profile_dir.join("incremental");
+            if path.is_dir() {
+                incremental_dirs.push(TargetArtifact {
+                    size: get_size(&path).unwrap_or(0),
+                    last_used: get_last_used(&path)?,
+                    path,
+                });
+            }
+        }
+        Ok(incremental_dirs)
+    }
+
+    /// list incremental session folders along with their lock files of all
+    /// profiles except newest session of each crate folder such as
+    /// incremental/foo-1x2y3z. Newest session is session whose folder was modified
+    /// last
+    pub fn list_old_incremental_sessions(&self) -> Result<Vec<TargetArtifact>> {
+        let mut sessions = Vec::new();
+        for incremental_dir in self.list_incremental_dirs()? {
+            for entry in fs::read_dir(incremental_dir.path())
+                .context("failed to read incremental directory")?
+            {
+                let crate_dir = entry?.path();
+                if !crate_dir.is_dir() {
+                    continue;
+                }
+                let mut crate_sessions = Vec::new();
+                for session in
+                    fs::read_dir(&crate_dir).context("failed to read incremental directory")?
+                {
+                    let session = session?.path();
+                    if session.is_dir() {
+                        let modified = session.metadata()?.modified()?;
+                        crate_sessions.push((modified, session));
+                    }
+                }
+                crate_sessions.sort();
+                crate_sessions.pop();
+                for (_, session) in crate_sessions {
+                    // lock file of session s-<time>-<id>-<hash> is s-<time>-<id>.lock
+                    let lock_file = session
+                        .file_name()
+                        .and_then(|name| name.to_str()?.rsplit_once('-'))
+                        .map(|(name, _)| crate_dir.join(format!("{name}.lock")));
+                    for path in std::iter::once(session).chain(lock_file) {
+                        if path.exists() {
+                            sessions.push(TargetArtifact {
+                                size: get_size(&path).unwrap_or(0),
+                                last_used: get_last_used(&path)?,
+                                path,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        sessions.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(sessions)
+    }
+
+    /// list artifacts of all profiles which are built by toolchain which is not
+    /// present in provided installed toolchains. Each fingerprint of .fingerprint
+    /// folder records hash of rustc used for building it, so fingerprints are
+    /// grouped by that hash and rustc version of group is read from header of
+    /// rmeta file built by it. Group whose rustc version cannot be determined is
+    /// never listed
+    pub fn list_stale_toolchain_artifacts(
+        &self,
+        installed_toolchains: &[String],
+    ) -> Result<Vec<TargetArtifact>> {
+        let mut artifacts = Vec::new();
+        for profile_dir in list_profile_dirs(&self.path)? {
+            let fingerprint_dir = profile_dir.join(".fingerprint");
+            // group unit hash of fingerprint by rustc hash
+            let mut rustc_units: HashMap<u64, Vec<String>> = HashMap::new();
+            for entry in
+                fs::read_dir(&fingerprint_dir).context("failed to read fingerprint directory")?
+            {
+                let path = entry?.path();
+                let Some(unit_hash) = path.file_name().and_then(|name| unit_hash(name.to_str()?))
+                else {
+                    continue;
+                };
+                if let Some(rustc) = read_fingerprint_rustc(&path) {
+                    rustc_units
+                        .entry(rustc)
+                        .or_default()
+                        .push(unit_hash.to_string());
+                }
+            }
+            // map unit hash to rustc version present in header of its rmeta file
+            let mut unit_versions = HashMap::new();
+            let deps_dir = profile_dir.join("deps");
+            if deps_dir.is_dir() {
+                for entry in fs::read_dir(&deps_dir).context("failed to read deps directory")? {
+                    let path = entry?.path();
+                    if path.extension().and_then(|extension| extension.to_str()) != Some("rmeta") {
+                        continue;
+                    }
+                    let unit_hash = path
+                        .file_name()
+                        .and_then(|name| unit_hash(name.to_str()?))
+                        .map(ToString::to_string);
+                    if let (Some(unit_hash), Some(version)) = (unit_hash, read_rustc_version(&path))
+                    {
+                        unit_versions.insert(unit_hash, version);
+                    }
+                }
+            }
+            let mut stale_units = HashSet::new();
+            for units in rustc_units.values() {
+                let version = units.iter().find_map(|unit| unit_versions.get(unit));
+                if version.is_some_and(|version| !installed_toolchains.contains(version)) {
+                    stale_units.extend(units.iter().cloned());
+                }
+            }
+            if stale_units.is_empty() {
+                continue;
+            }
+            for artifact_dir in &ARTIFACT_DIRS {
+                let artifact_dir = profile_dir.join(artifact_dir);
+                if !artifact_dir.is_dir() {
+                    continue;
+                }
+                for entry in
+                    fs::read_dir(&artifact_dir).context("failed to read artifact directory")?
+                {
+                    let path = entry?.path();
+                    let is_stale = path
+                        .file_name()
+                        .and_then(|name| unit_hash(name.to_str()?))
+                        .is_some_and(|unit_hash| stale_units.contains(unit_hash));
+                    if is_stale {
+                        artifacts.push(TargetArtifact {
+                            size: get_size(&path).unwrap_or(0),
+                            last_used: get_last_used(&path)?,
+                            path,
+                        });
+                    }
+                }
+            }
+        }
+        artifacts.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(artifacts)
+    }
+
+    /// remove whole target directory. Target directory is moved to trash if trash is
+    /// provided and removal is recorded in journal if journal is provided
+    pub fn remove(
+        &self,
+        trash: Option<&Trash>,
+        journal: Option<&Journal>,
+        dry_run: bool,
+    ) -> Result<()> {
+        remove_path(
+            &self.path,
+            &self.path.to_string_lossy(),
+            trash,
+            journal,
+            dry_run,
+        )
+    }
+}
+
+// list profile directories present directly inside target directory or inside
+// target triple directory of target directory
+fn list_profile_dirs(target_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut profile_dirs = Vec::new();
+    for entry in fs::read_dir(target_dir).context("failed to read target directory")? {
+        let path = entry?.path();
+        if !path.is_dir() {
+            continue;
+        }
+        if path.join(".fingerprint").is_dir() {
+            profile_dirs.push(path);
+            continue;
+        }
+        for sub_entry in fs::read_dir(&path).context("failed to read target directory")? {
+            let sub_path = sub_entry?.path();
+            if sub_path.join(".fingerprint").is_dir() {
+                profile_dirs.push(sub_path);
+            }
+        }
+    }
+    profile_dirs.sort();
+    Ok(profile_dirs)
+}
+
+// return unit hash of artifact name such as 7e2a5a63dd55a389 for
+// libanyhow-7e2a5a63dd55a389.rmeta or anyhow-7e2a5a63dd55a389
+fn unit_hash(name: &str) -> Option<&str> {
+    let stem = name.split('.').next()?;
+    let (_, hash) = stem.rsplit_once('-')?;
+    (!hash.is_empty() && hash.chars().all(|c| c.is_ascii_hexdigit())).then_some(hash)
+}
+
+// read rustc hash recorded in json file of fingerprint folder
+fn read_fingerprint_rustc(fingerprint: &Path) -> Option<u64> {
+    fs::read_dir(fingerprint)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|extension| extension.to_str()) == Some("json"))
+        .find_map(|path| {
+            let content = fs::read_to_string(path).ok()?;
+            let fingerprint: serde_json::Value = serde_json::from_str(&content).ok()?;
+            fingerprint.get("rustc")?.as_u64()
+        })
+}
+
+// read rustc version such as rustc 1.75.0 (82e1608df 2023-12-21) which is
+// stored in header of rmeta file
+fn read_rustc_version(rmeta: &Path) -> Option<String> {
+    let mut buffer = Vec::new();
+    fs::File::open(rmeta)
+        .ok()?
+        .take(1024)
+        .read_to_end(&mut buffer)
+        .ok()?;
+    let start = buffer.windows(6).position(|window| window == b"rustc ")?;
+    let end = start + buffer[start..].iter().position(|byte| *byte == b')')?;
+    String::from_utf8(buffer[start..=end].to_vec()).ok()
+}
+
+// resolve target directory of project. Environment variable takes priority over
+// cargo config files and config file nearest to project takes priority over
+// config files of parent directories and cargo home
+fn resolve_target_dir(project: &Path, cargo_home: &Path) -> Result<PathBuf> {
+    for variable in &["CARGO_TARGET_DIR", "CARGO_BUILD_TARGET_DIR"] {
+        if let Some(value) = env::var_os(variable) {
+            let current_dir = env::current_dir().context("failed to get current directory")?;
+            return Ok(current_dir.join(value));
+        }
+    }
+    for directory in project.ancestors() {
+        if let Some(target_dir) = config_target_dir(&directory.join(".cargo"))? {
+            return Ok(target_dir);
+        }
+    }
+    if let Some(target_dir) = config_target_dir(cargo_home)? {
+        return Ok(target_dir);
+    }
+    Ok(workspace_root(project).join("target"))
+}
+
+// read build.target-dir value of cargo config file present in provided cargo
+// folder. Relative value is resolved from parent of cargo folder
+fn config_target_dir(cargo_dir: &Path) -> Result<Option<PathBuf>> {
+    // cargo reads config file without extension if config.toml is not present
+    let config_path = [cargo_dir.join("config.toml"), cargo_dir.join("config")]
+        .iter()
+        .find(|path| path.is_file())
+        .cloned();
+    let Some(config_path) = config_path else {
+        return Ok(None);
+    };
+    let content = fs::read_to_string(&config_path).context("failed to read cargo config file")?;
+    let config: toml::Value =
+        toml::from_str(&content).context("failed to parse cargo config file")?;
+    let target_dir = config
+        .get("build")
+        .and_then(|build| build.get("target-dir"))
+        .and_then(toml::Value::as_str);
+    Ok(target_dir.map(|target_dir| cargo_dir.parent().unwrap_or(cargo_dir).join(target_dir)))
+}
+
+// return workspace root of project. Nearest directory whose Cargo.toml contains
+// workspace table is workspace root, otherwise project is its own root
+fn workspace_root(project: &Path) -> &Path {
+    project
+        .ancestors()
+        .find(|directory| {
+            fs::read_to_string(directory.join("Cargo.toml"))
+                .ok()
+                .and_then(|content| toml::from_str::<toml::Value>(&content).ok())
+                .is_some_and(|manifest| manifest.get("workspace").is_some())
+        })
+        .unwrap_or(project)
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, path::PathBuf, time::Duration};
+
+    use super::{resolve_target_dir, TargetDir};
+    use crate::{
+        config_file::ConfigFile,
+        test_util::{dir_path, TempDir},
+    };
+
+    #[test]
+    fn test_old_incremental_sessions() {
+        let temp_dir = TempDir::new("incremental");
+        let profile_dir = temp_dir.path().join("target/debug");
+        fs::create_dir_all(profile_dir.join(".fingerprint")).unwrap();
+        let crate_dir = profile_dir.join("incremental/foo-1x2y3z");
+        for session in &["s-old-1abc-hash", "s-new-2abc-hash"] {
+            fs::create_dir_all(crate_dir.join(session)).unwrap();
+            let lock_file = session.rsplit_once('-').unwrap().0;
+            fs::write(crate_dir.join(format!("{lock_file}.lock")), "").unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        fs::create_dir_all(profile_dir.join("incremental/bar-4a5b6c/s-only-3abc-hash")).unwrap();
+        let target_dir = TargetDir {
+            path: temp_dir.path().join("target"),
+            projects: Vec::new(),
+            size: 0,
+            last_used: None,
+        };
+
+        let incremental_dirs = target_dir.list_incremental_dirs().unwrap();
+        assert_eq!(incremental_dirs.len(), 1);
+        assert_eq!(incremental_dirs[0].path(), profile_dir.join("incremental"));
+        let sessions = target_dir
+            .list_old_incremental_sessions()
+            .unwrap()
+            .iter()
+            .map(|session| session.path().to_path_buf())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sessions,
+            vec![
+                crate_dir.join("s-old-1abc-hash"),
+                crate_dir.join("s-old-1abc.lock")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stale_toolchain_artifacts() {
+        let temp_dir = TempDir::new("toolchain");
+        let profile_dir = temp_dir.path().join("target/debug");
+        let units = [
+            ("foo-aaaa", 1, "rustc 1.70.0 (90c541806 2023-05-31)"),
+            ("bar-bbbb", 2, "rustc 1.95.0 (59807616e 2026-04-14)"),
+        ];
+        for (unit, rustc, version) in &units {
+            let fingerprint = profile_dir.join(".fingerprint").join(unit);
+            fs::create_dir_all(&fingerprint).unwrap();
+            fs::write(
+                fingerprint.join("lib.json"),
+                format!("{{\"rustc\":{rustc}}}"),
+            )
+            .unwrap();
+            fs::create_dir_all(profile_dir.join("deps")).unwrap();
+            let mut rmeta = b"rust\0\0\0\x08\x23".to_vec();
+            rmeta.extend_from_slice(version.as_bytes());
+            fs::write(profile_dir.join(format!("deps/lib{unit}.rmeta")), rmeta).unwrap();
+            fs::write(profile_dir.join(format!("deps/lib{unit}.rlib")), "rlib").unwrap();
+        }
+        // build script of foo is built by same rustc but has no rmeta file
+        let build_script = profile_dir.join(".fingerprint/foo-cccc");
+        fs::create_dir_all(&build_script).unwrap();
+        fs::write(build_script.join("build.json"), "{\"rustc\":1}").unwrap();
+        fs::create_dir_all(profile_dir.join("build/foo-cccc")).unwrap();
+        let target_dir = TargetDir {
+            path: temp_dir.path().join("target"),
+            projects: Vec::new(),
+            size: 0,
+            last_used: None,
+        };
+
+        let installed = vec!["rustc 1.95.0 (59807616e 2026-04-14)".to_string()];
+        let artifacts = target_dir
+            .list_stale_toolchain_artifacts(&installed)
+            .unwrap()
+            .iter()
+            .map(|artifact| {
+                artifact
+                    .path()
+                    .strip_prefix(&profile_dir)
+                    .unwrap()
+                    .to_path_buf()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            artifacts,
+            vec![
+                PathBuf::from(".fingerprint/foo-aaaa"),
+                PathBuf::from(".fingerprint/foo-cccc"),
+                PathBuf::from("build/foo-cccc"),
+                PathBuf::from("deps/libfoo-aaaa.rlib"),
+                PathBuf::from("deps/libfoo-aaaa.rmeta"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_target_dir() {
+        if std::env::var_os("CARGO_TARGET_DIR").is_some()
+            || std::env::var_os("CARGO_BUILD_TARGET_DIR").is_some()
+        {
+            return;
+        }
+        let temp_dir = TempDir::new("target");
+        let cargo_home = temp_dir.path().join("cargo-home");
+        let workspace = temp_dir.path().join("workspace");
+        let member = workspace.join("member");
+        let custom = temp_dir.path().join("custom");
+        fs::create_dir_all(&cargo_home).unwrap();
+        fs::create_dir_all(&member).unwrap();
+        fs::create_dir_all(custom.join(".cargo")).unwrap();
+        fs::write(
+            workspace.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"member\"]",
+        )
+        .unwrap();
+        fs::write(member.join("Cargo.toml"), "[package]\nname = \"member\"").unwrap();
+        fs::write(custom.join("Cargo.toml"), "[package]\nname = \"custom\"").unwrap();
+        fs::write(
+            custom.join(".cargo/config.toml"),
+            "[build]\ntarget-dir = \"build-output\"",
+        )
+        .unwrap();
+
+        assert_eq!(
+            resolve_target_dir(&member, &cargo_home).unwrap(),
+            workspace.join("target")
+        );
+        assert_eq!(
+            resolve_target_dir(&custom, &cargo_home).unwrap(),
+            custom.join("build-output")
+        );
+        // absolute path is used as it is. Literal string is used so windows path
+        // separator is not treated as escape
+        let shared = temp_dir.path().join("shared").join("target");
+        fs::write(
+            cargo_home.join("config.toml"),
+            format!("[build]\ntarget-dir = '{}'", shared.display()),
+        )
+        .unwrap();
+        assert_eq!(resolve_target_dir(&member, &cargo_home).unwrap(), shared);
+    }
+
+    // test target directory of projects is listed and its stale artifacts removed
+    #[test]
+    fn test_target_dir() {
+        if std::env::var_os("CARGO_TARGET_DIR").is_some()
+            || std::env::var_os("CARGO_BUILD_TARGET_DIR").is_some()
+        {
+            return;
+        }
+        let cargo_home = TempDir::new("target-list");
+        let project = cargo_home.path().join("projects/app");
+        let deps = project.join("target/debug/deps");
+        fs::create_dir_all(&deps).unwrap();
+        fs::create_dir_all(project.join("target/debug/.fingerprint")).unwrap();
+        fs::write(project.join("Cargo.toml"), "[package]").unwrap();
+        fs::write(deps.join("libapp.rlib"), "artifact").unwrap();
+        fs::create_dir_all(project.join("target/doc")).unwrap();
+        fs::write(project.join("target/doc/index.html"), "docs").unwrap();
+        let dir_path = dir_path(&cargo_home);
+        let mut config_file = ConfigFile::init(dir_path.config_file()).unwrap();
+        config_file
+            .add_directory(
+                cargo_home.path().join("projects").to_str().unwrap(),
+                false,
+                false,
+            )
+            .unwrap();
+        let target_dirs = TargetDir::list(&dir_path, &config_file).unwrap();
+        assert_eq!(target_dirs.len(), 1);
+        assert_eq!(target_dirs[0].path(), project.join("target"));
+        assert_eq!(target_dirs[0].projects(), std::slice::from_ref(&project));
+        assert_eq!(target_dirs[0].size(), 12);
+        assert_eq!(
+            target_dirs[0].list_profile_sizes().unwrap(),
+            vec![("debug".to_string(), 8), ("doc".to_string(), 4)]
+        );
+        assert!(target_dirs[0].last_build().is_none());
+        assert!(!target_dirs[0].is_inactive(Duration::from_hours(1)));
+        assert!(target_dirs[0]
+            .list_artifacts_older_than(Duration::from_hours(1))
+            .unwrap()
+            .is_empty());
+        let artifacts = target_dirs[0]
+            .list_artifacts_older_than(Duration::ZERO)
+            .unwrap();
+        assert_eq!(artifacts.len(), 1);
+        for artifact in &artifacts {
+            artifact.remove(None, None, false).unwrap();
+        }
+        assert!(!deps.join("libapp.rlib").exists());
+        assert!(project.join("target").exists());
+        target_dirs[0].remove(None, None, false).unwrap();
+        assert!(!project.join("target").exists());
+    }
+}
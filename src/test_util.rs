@@ -0,0 +1,90 @@
+// Helpers shared by unit tests of different modules
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    config_file::ConfigFile, crate_detail::CrateDetail, dir_path::DirPath, list_crate::CrateList,
+    registry_dir::RegistryDir,
+};
+
+pub(crate) const REGISTRY: &str = "github.com-1ecc6299db9ec823";
+pub(crate) const SPARSE_REGISTRY: &str = "index.crates.io-6f17d22bba15001f";
+
+// counter used to give unique name to temp dir created by same process
+static TEMP_DIR_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+// Directory inside system temp dir which is removed when dropped so it is
+// cleaned even when test panics
+pub(crate) struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    pub(crate) fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "cargo-trim-{name}-{}-{}",
+            std::process::id(),
+            TEMP_DIR_COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self { path }
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+// create fake cargo home with few crates of provided registry in temp directory
+pub(crate) fn fake_cargo_home(name: &str, registry: &str) -> TempDir {
+    let cargo_home = TempDir::new(name);
+    for crate_name in &["alpha-0.1.0", "alpha-0.2.0", "zeta-1.0.0"] {
+        let src = cargo_home
+            .path()
+            .join("registry/src")
+            .join(registry)
+            .join(crate_name);
+        fs::create_dir_all(&src).unwrap();
+        fs::write(src.join("lib.rs"), "").unwrap();
+        let cache = cargo_home.path().join("registry/cache").join(registry);
+        fs::create_dir_all(&cache).unwrap();
+        fs::write(cache.join(format!("{crate_name}.crate")), "crate").unwrap();
+    }
+    cargo_home
+}
+
+// directory path of fake cargo home. Config file is also stored inside cargo
+// home so user config file is never touched
+pub(crate) fn dir_path(cargo_home: &TempDir) -> DirPath {
+    DirPath::with_config_dir(Some(cargo_home.path()), cargo_home.path()).unwrap()
+}
+
+// scan crates of directory path using its config file
+pub(crate) fn scan(dir_path: &DirPath) -> (ConfigFile, CrateDetail, CrateList) {
+    let config_file = ConfigFile::init(dir_path.config_file()).unwrap();
+    let mut crate_detail = CrateDetail::default();
+    let crate_list =
+        CrateList::create_list(dir_path, &config_file, &mut crate_detail, None).unwrap();
+    (config_file, crate_detail, crate_list)
+}
+
+// registry dir containing all installed registry crates of scanned list
+pub(crate) fn registry_dir<'a>(dir_path: &'a DirPath, crate_list: &CrateList) -> RegistryDir<'a> {
+    RegistryDir::new(
+        dir_path.cache_dir(),
+        dir_path.src_dir(),
+        dir_path.index_dir(),
+        crate_list.installed_registry(),
+    )
+    .unwrap()
+}
@@ -0,0 +1,394 @@
+// Quarantine area where removed crate archive, source and git folders are moved
+// instead of deleting them so they can be restored later. Each run of cargo trim
+// create its own batch folder which contains moved files along with manifest
+// file recording original location of each moved file
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    journal::Journal,
+    utils::{delete_folder, get_size},
+};
+
+// name of manifest file present in each batch folder
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Entry of file or folder moved to trash
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TrashEntry {
+    crate_name: String,
+    original_path: PathBuf,
+    stored_name: String,
+}
+
+impl TrashEntry {
+    /// return name of crate whose file or folder was moved to trash
+    pub fn crate_name(&self) -> &str {
+        &self.crate_name
+    }
+
+    /// return path from which file or folder was moved to trash
+    pub fn original_path(&self) -> &Path {
+        &self.original_path
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Manifest {
+    created: u64,
+    entries: Vec<TrashEntry>,
+}
+
+/// Batch of entries moved to trash during single run
+pub struct TrashBatch {
+    path: PathBuf,
+    created: SystemTime,
+    entries: Vec<TrashEntry>,
+    size: u64,
+}
+
+impl TrashBatch {
+    /// return id of batch
+    pub fn id(&self) -> &str {
+        self.path.file_name().unwrap().to_str().unwrap()
+    }
+
+    /// return time when batch was created
+    pub fn created(&self) -> SystemTime {
+        self.created
+    }
+
+    /// return entries present in batch
+    pub fn entries(&self) -> &[TrashEntry] {
+        &self.entries
+    }
+
+    /// return size of batch folder in bytes when batch was listed
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    // write manifest of batch
+    fn write_manifest(&self) -> Result<()> {
+        let created = self
+            .created
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let manifest = Manifest {
+            created,
+            entries: self.entries.clone(),
+        };
+        let content =
+            serde_json::to_string_pretty(&manifest).context("failed to serialize manifest")?;
+        fs::write(self.path.join(MANIFEST_FILE), content).context("failed to write manifest")?;
+        Ok(())
+    }
+}
+
+/// Stores trash folder location and batch of current run
+pub struct Trash {
+    trash_dir: PathBuf,
+    current_batch: Mutex<Option<TrashBatch>>,
+}
+
+impl Trash {
+    /// create new trash which stores removed files in provided folder
+    pub fn new(trash_dir: &Path) -> Self {
+        Self {
+            trash_dir: trash_dir.to_path_buf(),
+            current_batch: Mutex::new(None),
+        }
+    }
+
+    /// return trash folder location
+    pub fn trash_dir(&self) -> &Path {
+        &self.trash_dir
+    }
+
+    /// move file or folder of crate to current batch of trash
+    // path is debug formatted so it is printed within quotes
+    #[allow(clippy::unnecessary_debug_formatting)]
+    pub fn store(&self, crate_name: &str, path: &Path) -> Result<()> {
+        let mut current_batch = self.current_batch.lock().unwrap();
+        if current_batch.is_none() {
+            *current_batch = Some(self.create_batch()?);
+        }
+        let batch = current_batch.as_mut().unwrap();
+        let stored_name = batch.entries.len().to_string();
+        move_path(path, &batch.path.join(&stored_name))
+            .with_context(|| format!("failed to move {path:?} to trash"))?;
+        batch.entries.push(TrashEntry {
+            crate_name: crate_name.to_string(),
+            original_path: path.to_path_buf(),
+            stored_name,
+        });
+        batch.write_manifest()
+    }
+
+    // create new batch folder named from current time and process id
+    fn create_batch(&self) -> Result<TrashBatch> {
+        let created = SystemTime::now();
+        let seconds = created.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let path = self
+            .trash_dir
+            .join(format!("{}-{}", seconds.as_secs(), std::process::id()));
+        fs::create_dir_all(&path).context("failed to create trash batch folder")?;
+        Ok(TrashBatch {
+            path,
+            created: UNIX_EPOCH + Duration::from_secs(seconds.as_secs()),
+            entries: Vec::new(),
+            size: 0,
+        })
+    }
+
+    /// list all batches present in trash from oldest to newest
+    pub fn list_batches(&self) -> Result<Vec<TrashBatch>> {
+        let mut batches = Vec::new();
+        if !self.trash_dir.exists() {
+            return Ok(batches);
+        }
+        for entry in fs::read_dir(&self.trash_dir).context("failed to read trash folder")? {
+            let path = entry?.path();
+            let manifest_path = path.join(MANIFEST_FILE);
+            if !manifest_path.is_file() {
+                continue;
+            }
+            let content =
+                fs::read_to_string(&manifest_path).context("failed to read trash manifest")?;
+            let manifest: Manifest =
+                serde_json::from_str(&content).context("failed to parse trash manifest")?;
+            let size = get_size(&path).unwrap_or(0);
+            batches.push(TrashBatch {
+                path,
+                created: UNIX_EPOCH + Duration::from_secs(manifest.created),
+                entries: manifest.entries,
+                size,
+            });
+        }
+        batches.sort_by(|a, b| (a.created, a.id()).cmp(&(b.created, b.id())));
+        Ok(batches)
+    }
+
+    /// restore entries of crate from newest batch containing crate. If crate name
+    /// is not provided all entries of newest batch is restored. Entry whose
+    /// original path already exists is kept in trash. Restored entries are
+    /// returned along with entries which are kept in trash
+    // path is debug formatted so it is printed within quotes
+    #[allow(clippy::unnecessary_debug_formatting)]
+    pub fn restore(
+        &self,
+        crate_name: Option<&str>,
+        dry_run: bool,
+    ) -> Result<(Vec<TrashEntry>, Vec<TrashEntry>)> {
+        let mut restored = Vec::new();
+        let mut skipped = Vec::new();
+        let mut batches = self.list_batches()?;
+        batches.reverse();
+        for mut batch in batches {
+            let (matched, remaining): (Vec<_>, Vec<_>) = batch
+                .entries
+                .into_iter()
+                .partition(|entry| crate_name.is_none_or(|name| entry.crate_name == name));
+            batch.entries = remaining;
+            if matched.is_empty() {
+                continue;
+            }
+            for entry in matched {
+                if entry.original_path.exists() {
+                    skipped.push(entry.clone());
+                    batch.entries.push(entry);
+                    continue;
+                }
+                if !dry_run {
+                    if let Some(parent) = entry.original_path.parent() {
+                        fs::create_dir_all(parent)
+                            .context("failed to create original parent folder")?;
+                    }
+                    move_path(&batch.path.join(&entry.stored_name), &entry.original_path)
+                        .with_context(|| format!("failed to restore {:?}", entry.original_path))?;
+                }
+                restored.push(entry);
+            }
+            if !dry_run {
+                if batch.entries.is_empty() {
+                    delete_folder(&batch.path, false)?;
+                } else {
+                    batch.write_manifest()?;
+                }
+            }
+            // only newest batch containing crate is restored
+            break;
+        }
+        Ok((restored, skipped))
+    }
+
+    /// remove batches which are older than provided duration. If duration is not
+    /// provided all batches are removed. Removed batches are returned
+    pub fn purge(&self, older_than: Option<Duration>, dry_run: bool) -> Result<Vec<TrashBatch>> {
+        let mut purged = Vec::new();
+        for batch in self.list_batches()? {
+            let age = SystemTime::now()
+                .duration_since(batch.created)
+                .unwrap_or_default();
+            if older_than.is_none_or(|older_than| age >= older_than) {
+                delete_folder(&batch.path, dry_run)?;
+                purged.push(batch);
+            }
+        }
+        Ok(purged)
+    }
+}
+
+// remove file or folder of crate. File or folder is moved to trash if trash is
+// provided otherwise it is deleted. Removal is recorded in journal if journal is
+// provided. Nothing is removed for dry run
+pub(crate) fn remove_path(
+    path: &Path,
+    crate_name: &str,
+    trash: Option<&Trash>,
+    journal: Option<&Journal>,
+    dry_run: bool,
+) -> Result<()> {
+    if dry_run || !path.exists() {
+        return Ok(());
+    }
+    // size is read before removal since it cannot be read later
+    let size = journal.map(|_| get_size(path).unwrap_or(0));
+    match trash {
+        Some(trash) => trash.store(crate_name, path)?,
+        None => delete_folder(path, false)?,
+    }
+    if let (Some(journal), Some(size)) = (journal, size) {
+        journal.record(crate_name, path, size, trash.is_some())?;
+    }
+    Ok(())
+}
+
+// move file or folder. If it cannot be renamed such as when trash is in another
+// file system it is copied and then deleted
+fn move_path(from: &Path, to: &Path) -> Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    copy_path(from, to)?;
+    delete_folder(from, false)
+}
+
+// copy file or folder recursively. Symlink is recreated as symlink instead of
+// copying file or folder pointed by it
+fn copy_path(from: &Path, to: &Path) -> Result<()> {
+    let file_type = fs::symlink_metadata(from)?.file_type();
+    if file_type.is_symlink() {
+        copy_symlink(from, to)?;
+    } else if file_type.is_dir() {
+        fs::create_dir_all(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?.path();
+            copy_path(&entry, &to.join(entry.file_name().unwrap()))?;
+        }
+    } else {
+        fs::copy(from, to)?;
+    }
+    Ok(())
+}
+
+// create symlink which points to same target as provided symlink
+#[cfg(unix)]
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(from)?, to)
+}
+
+// create symlink which points to same target as provided symlink. Windows
+// differentiate between symlink of file and folder
+#[cfg(windows)]
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    let target = fs::read_link(from)?;
+    if fs::metadata(from).is_ok_and(|metadata| metadata.is_dir()) {
+        std::os::windows::fs::symlink_dir(target, to)
+    } else {
+        std::os::windows::fs::symlink_file(target, to)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, path::Path, time::Duration};
+
+    use super::{copy_path, remove_path, Trash};
+    use crate::{test_util::TempDir, utils::delete_folder};
+
+    const HOUR: u64 = 60 * 60;
+
+    #[test]
+    fn test_trash_restore_purge() {
+        let temp_dir = TempDir::new("trash");
+        let crate_dir = temp_dir.path().join("src/foo-0.1.0");
+        fs::create_dir_all(&crate_dir).unwrap();
+        fs::write(crate_dir.join("lib.rs"), "content").unwrap();
+        let trash = Trash::new(&temp_dir.path().join("trash"));
+
+        remove_path(&crate_dir, "foo-0.1.0", Some(&trash), None, false).unwrap();
+        assert!(!crate_dir.exists());
+        let batches = trash.list_batches().unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].entries()[0].crate_name(), "foo-0.1.0");
+        assert_eq!(batches[0].entries()[0].original_path(), crate_dir);
+
+        let (restored, skipped) = trash.restore(Some("bar-0.1.0"), false).unwrap();
+        assert!(restored.is_empty() && skipped.is_empty());
+        let (restored, _) = trash.restore(Some("foo-0.1.0"), false).unwrap();
+        assert_eq!(restored.len(), 1);
+        assert_eq!(
+            fs::read_to_string(crate_dir.join("lib.rs")).unwrap(),
+            "content"
+        );
+        assert!(trash.list_batches().unwrap().is_empty());
+
+        let trash = Trash::new(&temp_dir.path().join("trash"));
+        remove_path(&crate_dir, "foo-0.1.0", Some(&trash), None, false).unwrap();
+        assert!(trash
+            .purge(Some(Duration::from_secs(HOUR)), false)
+            .unwrap()
+            .is_empty());
+        assert_eq!(trash.purge(None, false).unwrap().len(), 1);
+        assert!(trash.list_batches().unwrap().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_copy_symlink() {
+        let temp_dir = TempDir::new("trash-link");
+        let outside_dir = temp_dir.path().join("outside");
+        fs::create_dir_all(&outside_dir).unwrap();
+        fs::write(outside_dir.join("file"), "outside").unwrap();
+        let crate_dir = temp_dir.path().join("src/foo-0.1.0");
+        fs::create_dir_all(&crate_dir).unwrap();
+        std::os::unix::fs::symlink(&outside_dir, crate_dir.join("dir_link")).unwrap();
+        std::os::unix::fs::symlink("missing", crate_dir.join("broken_link")).unwrap();
+
+        let copied_dir = temp_dir.path().join("copied");
+        copy_path(&crate_dir, &copied_dir).unwrap();
+        let dir_link = copied_dir.join("dir_link");
+        assert!(fs::symlink_metadata(&dir_link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(fs::read_link(&dir_link).unwrap(), outside_dir);
+        assert_eq!(
+            fs::read_link(copied_dir.join("broken_link")).unwrap(),
+            Path::new("missing")
+        );
+        // removing copied folder do not remove content pointed by symlink
+        delete_folder(&copied_dir, false).unwrap();
+        assert!(outside_dir.join("file").exists());
+    }
+}
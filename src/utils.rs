@@ -1,7 +1,17 @@
-use std::{collections::HashMap, fs, path::Path};
+//! Filesystem helpers used to measure and clean cargo home folders
 
-use anyhow::Result;
-use colored::Colorize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use anyhow::{Context, Result};
+
+use crate::{
+    journal::Journal,
+    trash::{remove_path, Trash},
+};
 
 // remove semver version part from crates full name
 pub(crate) fn clear_version_value(full_name: &str) -> (String, String) {
@@ -20,53 +30,119 @@ pub(crate) fn clear_version_value(full_name: &str) -> (String, String) {
     (clear_name, version)
 }
 
-// delete folder with folder path provided
+// check if glob pattern matches whole text. `*` matches any number of characters
+// and `?` matches single character
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+    let (mut pattern_pos, mut text_pos) = (0, 0);
+    // position of last star in pattern along with text position it matched till
+    let mut star = None;
+    while text_pos < text.len() {
+        match pattern.get(pattern_pos) {
+            Some('*') => {
+                star = Some((pattern_pos, text_pos));
+                pattern_pos += 1;
+            }
+            Some(&c) if c == '?' || c == text[text_pos] => {
+                pattern_pos += 1;
+                text_pos += 1;
+            }
+            _ => {
+                // backtrack so last star matches one more character
+                let Some((star_pos, star_text_pos)) = star else {
+                    return false;
+                };
+                pattern_pos = star_pos + 1;
+                text_pos = star_text_pos + 1;
+                star = Some((star_pos, text_pos));
+            }
+        }
+    }
+    pattern[pattern_pos..].iter().all(|&c| c == '*')
+}
+
+/// check if installed crate matches provided crate name, full name with version
+/// or glob pattern. Git crate full name contains rev instead of version
+pub fn crate_matches(pattern: &str, crate_name: &str) -> bool {
+    let (name, version) = clear_version_value(crate_name);
+    let name = if version.is_empty() {
+        crate_name
+            .rsplit_once('-')
+            .map_or(crate_name, |(name, _)| name)
+    } else {
+        name.as_str()
+    };
+    glob_match(pattern, crate_name) || glob_match(pattern, name)
+}
+
+// delete folder with folder path provided. Nothing is deleted for dry run
 pub(crate) fn delete_folder(path: &Path, dry_run: bool) -> Result<()> {
-    if path.exists() {
+    if path.exists() && !dry_run {
         if path.is_file() {
-            if dry_run {
-                println!(
-                    "{} {} {:?}",
-                    "Dry run:".color("yellow"),
-                    "Removed".color("red"),
-                    path
-                );
-            } else {
-                fs::remove_file(&path)?;
-            }
+            fs::remove_file(path)?;
         } else if path.is_dir() {
-            if dry_run {
-                println!(
-                    "{} {} {:?}",
-                    "Dry run:".color("yellow"),
-                    "Removed".color("red"),
-                    path
-                );
-            } else {
-                fs::remove_dir_all(path)?;
-            }
+            fs::remove_dir_all(path)?;
         }
     }
     Ok(())
 }
 
-// delete index .cache file
-pub(crate) fn delete_index_cache(index_dir: &Path, dry_run: bool) -> Result<()> {
+/// delete index .cache folder of every registry. Folder is moved to trash if
+/// trash is provided and its removal is recorded in journal if journal is provided
+pub fn delete_index_cache(
+    index_dir: &Path,
+    trash: Option<&Trash>,
+    journal: Option<&Journal>,
+    dry_run: bool,
+) -> Result<()> {
+    if !index_dir.exists() {
+        return Ok(());
+    }
     for entry in fs::read_dir(index_dir)? {
         let registry_dir = entry?.path();
         for folder in fs::read_dir(registry_dir)? {
             let folder = folder?.path();
             let folder_name = folder.file_name().unwrap();
             if folder_name == ".cache" {
-                delete_folder(&folder, dry_run)?;
+                remove_path(&folder, "index-cache", trash, journal, dry_run)?;
             }
         }
     }
     Ok(())
 }
 
-//  get size of directory
-pub(crate) fn get_size(path: &Path) -> Result<u64> {
+// check if registry index folder belongs to sparse registry. Sparse registry
+// index only contains .cache folder and is not a git repo
+pub(crate) fn is_sparse_index(registry_index: &Path) -> bool {
+    !registry_index.join(".git").exists()
+}
+
+/// list registry index folders present in index dir. Returned tuple contains
+/// list of git registry index and list of sparse registry index
+pub fn list_registry_index(index_dir: &Path) -> Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+    let mut git_index = Vec::new();
+    let mut sparse_index = Vec::new();
+    if index_dir.exists() {
+        for entry in fs::read_dir(index_dir).context("failed to read registry index folder")? {
+            let registry_index = entry?.path();
+            if !registry_index.is_dir() {
+                continue;
+            }
+            if is_sparse_index(&registry_index) {
+                sparse_index.push(registry_index);
+            } else {
+                git_index.push(registry_index);
+            }
+        }
+    }
+    git_index.sort();
+    sparse_index.sort();
+    Ok((git_index, sparse_index))
+}
+
+///  get size of directory
+pub fn get_size(path: &Path) -> Result<u64> {
     let mut total_size = 0;
     if path.is_dir() {
         for entry in fs::read_dir(path)? {
@@ -83,137 +159,85 @@ pub(crate) fn get_size(path: &Path) -> Result<u64> {
     Ok(total_size)
 }
 
-#[allow(
-    clippy::cast_precision_loss,
-    clippy::cast_possible_truncation,
-    clippy::cast_sign_loss
-)]
-pub(crate) fn convert_pretty(num: u64) -> String {
-    if num == 0 {
-        return "0 B".to_string();
-    }
-    let num = num as f64;
-    let units = ["B", "kB", "MB", "GB", "TB"];
-    let factor = (num.log10() / 3_f64).floor();
-    let power_factor = if factor >= units.len() as f64 {
-        (units.len() - 1) as f64
-    } else {
-        factor
-    };
-    let pretty_bytes = format!("{:.3}", num / 1000_f64.powf(power_factor))
-        .parse::<f64>()
-        .unwrap();
-    let unit = units[power_factor as usize];
-    format!("{} {}", pretty_bytes, unit)
-}
-
-// show title
-pub(crate) fn show_title(
-    title: &str,
-    first_path_len: usize,
-    second_path_len: usize,
-    dash_len: usize,
-) {
-    print_dash(dash_len);
-    println!(
-        "|{:^first_width$}|{:^second_width$}|",
-        title.bold(),
-        "SIZE(MB)".bold(),
-        first_width = first_path_len,
-        second_width = second_path_len
-    );
-    print_dash(dash_len);
-}
-
-// show total count using data and size
-pub(crate) fn show_total_count(
-    data: &[String],
-    size: f64,
-    first_path_len: usize,
-    second_path_len: usize,
-    dash_len: usize,
-) {
-    if data.is_empty() {
-        println!(
-            "|{:^first_width$}|{:^second_width$}|",
-            "NONE".color("red"),
-            "0.000".color("red"),
-            first_width = first_path_len,
-            second_width = second_path_len,
-        );
-    }
-    print_dash(dash_len);
-    println!(
-        "|{:^first_width$}|{:^second_width$}|",
-        format!("Total no of crates:- {}", data.len()).color("blue"),
-        format!("{:.3}", size).color("blue"),
-        first_width = first_path_len,
-        second_width = second_path_len,
-    );
-    print_dash(dash_len);
+// get last used time of path
+pub(crate) fn get_last_used(path: &Path) -> Result<SystemTime> {
+    get_size_and_last_used(path).map(|(_, last_used)| last_used)
 }
 
-// print dash
-pub(crate) fn print_dash(len: usize) {
-    println!("{}", "-".repeat(len).color("green"));
-}
-
-// top_crates() help to list out top n crates
-pub(crate) fn show_top_number_crates(
-    crates: &HashMap<String, u64>,
-    crate_type: &str,
-    number: usize,
-) {
-    // sort crates by size
-    let mut vector = crates.iter().collect::<Vec<_>>();
-    vector.sort_by(|a, b| (b.1).cmp(a.1));
-    let top_number = std::cmp::min(vector.len(), number);
-    let title = format!("Top {} {}", top_number, crate_type);
-    let first_path_len = 40;
-    let second_path_len = 10;
-    let dash_len = first_path_len + second_path_len + 3;
-    show_title(title.as_str(), first_path_len, second_path_len, dash_len);
-    // check n size and determine if to print n number of output NONE for 0 crates
-    if vector.is_empty() {
-        println!("|{:^40}|{:^10}|", "NONE".color("red"), "0.000".color("red"));
+// get size and last used time of path in single walk. Last used time is latest
+// access or modification time of files present inside path. Only modification
+// time is used for directory since reading directory itself updates its access
+// time
+pub(crate) fn get_size_and_last_used(path: &Path) -> Result<(u64, SystemTime)> {
+    let metadata = path.metadata()?;
+    let mut last_used = metadata.modified()?;
+    let mut size = 0;
+    if metadata.is_dir() {
+        for entry in fs::read_dir(path)? {
+            let (entry_size, entry_last_used) = get_size_and_last_used(&entry?.path())?;
+            size += entry_size;
+            last_used = last_used.max(entry_last_used);
+        }
     } else {
-        (0..top_number).for_each(|i| print_index_value_crate(&vector, i));
+        size = metadata.len();
+        // access time can be older than modified time for noatime file system
+        if let Ok(accessed) = metadata.accessed() {
+            last_used = last_used.max(accessed);
+        }
     }
-    print_dash(dash_len);
-}
-
-// print crate name
-#[allow(clippy::cast_precision_loss)]
-pub(crate) fn print_index_value_crate(vector: &[(&String, &u64)], i: usize) {
-    let crate_name = vector[i].0;
-    let size = vector[i].1;
-    let size = (*size as f64) / 1000_f64.powi(2);
-    println!("|{:^40}|{:^10.3}|", crate_name, size);
+    Ok((size, last_used))
 }
 
-fn query_param_widths() -> (usize, usize) {
-    (50, 10)
-}
-
-pub(crate) fn query_full_width() -> usize {
-    let (a, b) = query_param_widths();
-    a + b + 1
-}
-
-pub(crate) fn query_print(first_param: &str, second_param: &str) {
-    let (first_path_width, second_path_width) = query_param_widths();
-    println!(
-        "{:first_width$} {:>second_width$}",
-        first_param,
-        second_param,
-        first_width = first_path_width,
-        second_width = second_path_width
-    );
+/// list rustc version such as rustc 1.75.0 (82e1608df 2023-12-21) of all
+/// toolchains installed in rustup home. Version is read from channel manifest of
+/// toolchain and for toolchain without manifest such as linked toolchain it is
+/// read by running rustc of toolchain
+pub fn list_installed_toolchains(rustup_dir: &Path) -> Result<Vec<String>> {
+    let mut toolchains = Vec::new();
+    let toolchain_dir = rustup_dir.join("toolchains");
+    if !toolchain_dir.exists() {
+        return Ok(toolchains);
+    }
+    for entry in fs::read_dir(&toolchain_dir).context("failed to read toolchains directory")? {
+        let path = entry?.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let manifest = path.join("lib/rustlib/multirust-channel-manifest.toml");
+        let manifest_version = fs::read_to_string(&manifest)
+            .ok()
+            .and_then(|content| toml::from_str::<toml::Value>(&content).ok())
+            .and_then(|manifest| {
+                manifest
+                    .get("pkg")?
+                    .get("rustc")?
+                    .get("version")?
+                    .as_str()
+                    .map(|version| format!("rustc {version}"))
+            });
+        let version = manifest_version.or_else(|| {
+            let output = std::process::Command::new(path.join("bin").join("rustc"))
+                .arg("-V")
+                .output()
+                .ok()?;
+            output
+                .status
+                .success()
+                .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        });
+        if let Some(version) = version {
+            if !toolchains.contains(&version) {
+                toolchains.push(version);
+            }
+        }
+    }
+    toolchains.sort();
+    Ok(toolchains)
 }
 
 #[cfg(test)]
 mod test {
-    use super::{clear_version_value, convert_pretty};
+    use super::{clear_version_value, crate_matches, glob_match};
 
     #[test]
     fn test_clear_version_value() {
@@ -247,17 +271,25 @@ mod test {
     }
 
     #[test]
-    fn test_convert_pretty() {
-        assert_eq!(convert_pretty(0), "0 B".to_string());
-        assert_eq!(convert_pretty(12), "12 B".to_string());
-        assert_eq!(convert_pretty(1234), "1.234 kB".to_string());
-        assert_eq!(convert_pretty(23908), "23.908 kB".to_string());
-        assert_eq!(convert_pretty(874940334), "874.94 MB".to_string());
-        assert_eq!(convert_pretty(8849909404), "8.85 GB".to_string());
-        assert_eq!(convert_pretty(3417849409404), "3.418 TB".to_string());
-        assert_eq!(
-            convert_pretty(93453982182159417),
-            "93453.982 TB".to_string()
-        );
+    fn test_glob_match() {
+        assert!(glob_match("serde", "serde"));
+        assert!(!glob_match("serde", "serde_json"));
+        assert!(glob_match("*-sys", "openssl-sys"));
+        assert!(glob_match("serde*", "serde_json"));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(glob_match("rand-0.?.*", "rand-0.8.5"));
+        assert!(!glob_match("*-sys", "openssl-sys-extra"));
+        assert!(glob_match("*", ""));
+    }
+
+    #[test]
+    fn test_crate_matches() {
+        assert!(crate_matches("openssl-sys", "openssl-sys-0.9.80"));
+        assert!(crate_matches("openssl-sys-0.9.80", "openssl-sys-0.9.80"));
+        assert!(crate_matches("*-sys", "openssl-sys-0.9.80"));
+        assert!(!crate_matches("openssl", "openssl-sys-0.9.80"));
+        assert!(!crate_matches("foo-1.0.0", "foo-1.0.0-rc.1"));
+        assert!(crate_matches("internal", "internal-eab33b6"));
+        assert!(crate_matches("internal", "internal-HEAD"));
     }
 }
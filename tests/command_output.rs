@@ -96,3 +96,110 @@ fn test_unset_help() {
         .unwrap();
     assert!(status.success());
 }
+
+// test check select subcommand help
+#[test]
+fn test_select_help() {
+    let status = Command::new("cargo")
+        .arg("run")
+        .arg("--")
+        .arg("trim")
+        .arg("help")
+        .arg("select")
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+// test check restore subcommand help
+#[test]
+fn test_restore_help() {
+    let status = Command::new("cargo")
+        .arg("run")
+        .arg("--")
+        .arg("trim")
+        .arg("help")
+        .arg("restore")
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+// test check trash purge subcommand help
+#[test]
+fn test_trash_purge_help() {
+    let status = Command::new("cargo")
+        .arg("run")
+        .arg("--")
+        .arg("trim")
+        .arg("trash")
+        .arg("help")
+        .arg("purge")
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+// test check history subcommand help
+#[test]
+fn test_history_help() {
+    let status = Command::new("cargo")
+        .arg("run")
+        .arg("--")
+        .arg("trim")
+        .arg("help")
+        .arg("history")
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+// test check target subcommand help
+#[test]
+fn test_target_help() {
+    let status = Command::new("cargo")
+        .arg("run")
+        .arg("--")
+        .arg("trim")
+        .arg("help")
+        .arg("target")
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+// test check bin subcommand help
+#[test]
+fn test_bin_help() {
+    let status = Command::new("cargo")
+        .arg("run")
+        .arg("--")
+        .arg("trim")
+        .arg("help")
+        .arg("bin")
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+// test check old flag can be bundled with other flags
+#[test]
+fn test_old_flag_bundle() {
+    let cargo_home =
+        std::env::temp_dir().join(format!("cargo-trim-command-old-{}", std::process::id()));
+    std::fs::create_dir_all(&cargo_home).unwrap();
+    let status = Command::new("cargo")
+        .arg("run")
+        .arg("--")
+        .arg("trim")
+        .arg("--cargo-home")
+        .arg(&cargo_home)
+        .arg("-on")
+        // keep config and data file of run inside temp dir instead of user directory
+        .env("XDG_CONFIG_HOME", &cargo_home)
+        .env("XDG_DATA_HOME", &cargo_home)
+        .status()
+        .unwrap();
+    std::fs::remove_dir_all(&cargo_home).unwrap();
+    assert!(status.success());
+}